@@ -1,5 +1,53 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/** Every compile stage of a snippet, exposed to JavaScript */
+export interface ExploreResult {
+  /** Outline of the source JSX as JSON */
+  jsx: string
+  /** The intermediate representation as JSON */
+  ir: string
+  /** The templates the output creates, in order */
+  templates: Array<string>
+  /** The transformed code */
+  code: string
+  /** Diagnostics reported during the transform */
+  diagnostics: Array<JsDiagnostic>
+}
+
+/** Compile JSX source code and return every stage along the way */
+export declare function exploreJsx(source: string, options?: JsTransformOptions | undefined | null): ExploreResult
+
+/** Count what a file's JSX costs at runtime. Runs the full transform */
+export declare function analyzeJsx(source: string, options?: JsTransformOptions | undefined | null): JsFileStats
+
+/** Report problems in JSX source code, without printing the output */
+export declare function checkJsx(source: string, options?: JsTransformOptions | undefined | null): Array<JsDiagnostic>
+
+/** A diagnostic exposed to JavaScript */
+export interface JsDiagnostic {
+  /** "warning" or "error" */
+  severity: string
+  message: string
+  /** Byte offset where the offending source starts */
+  start: number
+  /** Byte offset where the offending source ends */
+  end: number
+}
+
+/** Per-file statistics exposed to JavaScript */
+export interface JsFileStats {
+  /** Component elements, built-ins included */
+  components: number
+  /** Native (and custom) elements */
+  elements: number
+  /** Reactive attributes, spreads and children of native elements */
+  dynamicBindings: number
+  /** Events handled through delegation, in order of first use */
+  delegatedEvents: Array<string>
+  /** Total length of the templates the output creates */
+  templateBytes: number
+}
+
 /** Transform options exposed to JavaScript */
 export interface JsTransformOptions {
   /**
@@ -7,6 +55,8 @@ export interface JsTransformOptions {
    * @default "solid-js/web"
    */
   moduleName?: string
+  /** Exports available from `module_name`; importing anything else is reported */
+  runtimeExports?: Array<string>
   /**
    * Generate mode: "dom", "ssr", or "universal"
    * @default "dom"
@@ -22,11 +72,36 @@ export interface JsTransformOptions {
    * @default true
    */
   delegateEvents?: boolean
+  /**
+   * Extra events to delegate
+   * @default []
+   */
+  delegatedEvents?: Array<string>
+  /**
+   * Whether `delegated_events` replaces the built-in table instead of extending it
+   * @default false
+   */
+  replaceDelegatedEvents?: boolean
   /**
    * Whether to wrap conditionals
    * @default true
    */
   wrapConditionals?: boolean
+  /**
+   * Comment marking an expression to be read once rather than tracked
+   * @default "@once"
+   */
+  staticMarker?: string
+  /**
+   * Compile-time constants, e.g. `{ "__DEV__": "false", "import.meta.env.SSR": "true" }`
+   * @default {}
+   */
+  define?: Record<string, string>
+  /**
+   * Modules to import `use:` directives from, e.g. `{ "tooltip": "my-directives" }`
+   * @default {}
+   */
+  directiveImports?: Record<string, string>
   /**
    * Whether to pass context to custom elements
    * @default true
@@ -42,8 +117,116 @@ export interface JsTransformOptions {
    * @default false
    */
   sourceMap?: boolean
+  /**
+   * Whether to dump the intermediate representation of every JSX root as JSON
+   * @default false
+   */
+  emitIr?: boolean
+  /**
+   * Parse every file with JSX enabled, regardless of its extension
+   * @default false
+   */
+  jsx?: boolean
+  /**
+   * Syntax level of the generated code: "es2015" or "es5"
+   * @default "es2015"
+   */
+  target?: string
+  /**
+   * The Solid major version the output runs against: "solid-1" or "solid-2"
+   * @default "solid-1"
+   */
+  runtime?: string
+  /**
+   * Whose output to match: "none" or "babel"
+   * @default "none"
+   */
+  compat?: string
+  /**
+   * Quote strings and space functions the way Prettier does
+   * @default false
+   */
+  prettier?: boolean
+  /**
+   * Whether SSR warns about event handlers that only attach after hydration
+   * @default false
+   */
+  ssrEventDiagnostics?: boolean
+  /**
+   * Whether SSR wraps each component's output in boundary comments
+   * @default false
+   */
+  componentBoundaries?: boolean
+  /**
+   * Whether SSR compiles static JSX to plain HTML strings and exports them
+   * @default false
+   */
+  staticHtml?: boolean
+  /**
+   * Whether DOM and SSR templates drop whitespace next to block-level tags
+   * @default false
+   */
+  minifyWhitespace?: boolean
+  /**
+   * Whether component props made only of literals are hoisted to module-level constants
+   * @default false
+   */
+  hoistStaticProps?: boolean
+  /**
+   * Whether templates also drop closing tags inside elements that keep their own
+   * @default false
+   */
+  omitNestedClosingTags?: boolean
+  /**
+   * Whether templates drop the closing tags at their end
+   * @default false
+   */
+  omitLastClosingTag?: boolean
+  /**
+   * Whether template attribute values go unquoted when HTML allows it
+   * @default false
+   */
+  omitQuotes?: boolean
+  /**
+   * Whether to warn about HTML nesting the browser's parser would rearrange
+   * @default false
+   */
+  validate?: boolean
+  /**
+   * Extra void elements; a `!`-prefixed entry removes a built-in one
+   * @default []
+   */
+  voidElements?: Array<string>
+  /**
+   * Extra SVG elements; a `!`-prefixed entry removes a built-in one
+   * @default []
+   */
+  svgElements?: Array<string>
+  /**
+   * Extra boolean attributes; a `!`-prefixed entry removes a built-in one
+   * @default []
+   */
+  booleanAttributes?: Array<string>
 }
 
+/** A runtime helper exposed to JavaScript */
+export interface JsRuntimeHelper {
+  /** The export name */
+  name: string
+  /** The first solid-js version providing it */
+  since: string
+}
+
+/** Runtime helpers the compiled output may import */
+export interface RuntimeReport {
+  helpers: Array<JsRuntimeHelper>
+  /** The lowest solid-js version providing every helper */
+  minimumVersion: string
+}
+
+/** List the runtime helpers output compiled with these options may import */
+export declare function runtimeHelpers(options?: JsTransformOptions | undefined | null): RuntimeReport
+
 /** Transform JSX source code */
 export declare function transformJsx(source: string, options?: JsTransformOptions | undefined | null): TransformResult
 
@@ -53,4 +236,8 @@ export interface TransformResult {
   code: string
   /** Source map (if enabled) */
   map?: string
+  /** The intermediate representation as JSON (if `emitIr` is set) */
+  ir?: string
+  /** Diagnostics reported during the transform */
+  diagnostics: Array<JsDiagnostic>
 }
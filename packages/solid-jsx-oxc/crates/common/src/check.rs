@@ -2,12 +2,18 @@
 //! Ported from dom-expressions/src/shared/utils.js
 
 use oxc_ast::ast::{
-    Expression, JSXAttribute, JSXAttributeItem, JSXAttributeName, JSXAttributeValue, JSXElement,
-    JSXElementName, JSXMemberExpression, JSXMemberExpressionObject,
+    Expression, ImportDeclarationSpecifier, JSXAttribute, JSXAttributeItem, JSXAttributeName,
+    JSXAttributeValue, JSXChild, JSXElement, JSXElementName, JSXMemberExpression,
+    JSXMemberExpressionObject, LogicalOperator, ObjectPropertyKind, Program, PropertyKind,
+    Statement, UnaryOperator,
 };
+use oxc_span::{GetSpan, Span};
 
-use crate::constants::{BUILT_INS, SVG_ELEMENTS};
+use crate::constants::{BUILT_INS, CLOSES_PARAGRAPH, ENUMERATED_ATTRIBUTES, SVG_ELEMENTS};
+use crate::diagnostic::Diagnostic;
 use crate::expression::expr_to_string;
+use crate::json::json_string;
+use crate::pragma::StaticMarks;
 
 /// Check if a tag name represents a component (starts with uppercase or contains dot)
 pub fn is_component(tag: &str) -> bool {
@@ -58,7 +64,11 @@ fn get_member_expression_name(member: &JSXMemberExpression) -> String {
 
 /// Check if an expression is dynamic (needs effect wrapping)
 /// This is a simplified version - full implementation would need scope analysis
-pub fn is_dynamic(expr: &Expression) -> bool {
+pub fn is_dynamic(expr: &Expression, marks: &StaticMarks) -> bool {
+    // `/*@once*/ value` is read once
+    if marks.contains(expr.span()) {
+        return false;
+    }
     match expr {
         // Literals are static
         Expression::StringLiteral(_)
@@ -82,25 +92,31 @@ pub fn is_dynamic(expr: &Expression) -> bool {
         Expression::ConditionalExpression(_) | Expression::LogicalExpression(_) => true,
 
         // Binary/unary with dynamic operands
-        Expression::BinaryExpression(b) => is_dynamic(&b.left) || is_dynamic(&b.right),
-        Expression::UnaryExpression(u) => is_dynamic(&u.argument),
+        Expression::BinaryExpression(b) => {
+            is_dynamic(&b.left, marks) || is_dynamic(&b.right, marks)
+        }
+        Expression::UnaryExpression(u) => is_dynamic(&u.argument, marks),
 
         // Arrow functions themselves are static (the reference)
         Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_) => false,
 
         // Object/array literals depend on their contents
         Expression::ObjectExpression(o) => o.properties.iter().any(|p| match p {
-            oxc_ast::ast::ObjectPropertyKind::ObjectProperty(prop) => is_dynamic(&prop.value),
+            oxc_ast::ast::ObjectPropertyKind::ObjectProperty(prop) => {
+                is_dynamic(&prop.value, marks)
+            }
             oxc_ast::ast::ObjectPropertyKind::SpreadProperty(spread) => {
-                is_dynamic(&spread.argument)
+                is_dynamic(&spread.argument, marks)
             }
         }),
         Expression::ArrayExpression(a) => a.elements.iter().any(|el| match el {
-            oxc_ast::ast::ArrayExpressionElement::SpreadElement(s) => is_dynamic(&s.argument),
+            oxc_ast::ast::ArrayExpressionElement::SpreadElement(s) => {
+                is_dynamic(&s.argument, marks)
+            }
             oxc_ast::ast::ArrayExpressionElement::Elision(_) => false,
             _ => {
                 if let Some(expr) = el.as_expression() {
-                    is_dynamic(expr)
+                    is_dynamic(expr, marks)
                 } else {
                     false
                 }
@@ -128,6 +144,111 @@ pub fn find_prop<'a>(element: &'a JSXElement<'a>, name: &str) -> Option<&'a JSXA
     None
 }
 
+/// Find the `innerHTML`, `textContent` or `innerText` attribute that sets an
+/// element's content in place of its children.
+pub fn find_child_property<'a>(element: &'a JSXElement<'a>) -> Option<&'a JSXAttribute<'a>> {
+    ["innerHTML", "textContent", "innerText"]
+        .into_iter()
+        .find_map(|name| find_prop(element, name))
+}
+
+/// Warn about children that a child property on the same element replaces
+pub fn child_property_conflict(
+    element: &JSXElement,
+    property: &JSXAttribute,
+) -> Option<Diagnostic> {
+    let has_children = element.children.iter().any(|child| match child {
+        JSXChild::Text(text) => !text.value.trim().is_empty(),
+        JSXChild::ExpressionContainer(container) => container.expression.as_expression().is_some(),
+        _ => true,
+    });
+    has_children.then(|| {
+        Diagnostic::warning(
+            format!(
+                "`{}` replaces the children of <{}>",
+                get_attr_name(&property.name),
+                get_tag_name(element)
+            ),
+            property.span,
+        )
+    })
+}
+
+/// Warn about children of an HTML element that the browser's parser wouldn't
+/// keep where they're written, e.g. a `<div>` in a `<p>`, an `<a>` in an `<a>` or
+/// a `<td>` outside a `<tr>`. The parser moves them elsewhere, so the template it
+/// builds no longer has the shape the generated code walks.
+pub fn nesting_diagnostics(element: &JSXElement, tag_name: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for child in &element.children {
+        let JSXChild::Element(child) = child else {
+            continue;
+        };
+        let child_tag = get_tag_name(child);
+        if !is_component(&child_tag) && !can_contain(tag_name, &child_tag) {
+            diagnostics.push(Diagnostic::warning(
+                format!(
+                    "<{}> cannot be a child of <{}>; the browser would move it out of place",
+                    child_tag, tag_name
+                ),
+                child.opening_element.span,
+            ));
+        }
+    }
+
+    // Interactive content can't be nested at any depth
+    if matches!(tag_name, "a" | "button" | "form") {
+        if let Some(nested) = find_nested(&element.children, tag_name) {
+            diagnostics.push(Diagnostic::warning(
+                format!(
+                    "<{0}> cannot be nested inside another <{0}>; the browser would split them",
+                    tag_name
+                ),
+                nested,
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Whether the HTML parser keeps `child` as a direct child of `parent`
+fn can_contain(parent: &str, child: &str) -> bool {
+    match child {
+        "tr" => matches!(parent, "thead" | "tbody" | "tfoot"),
+        "td" | "th" => parent == "tr",
+        "thead" | "tbody" | "tfoot" | "caption" | "colgroup" => parent == "table",
+        "col" => parent == "colgroup",
+        // Tables only hold their own parts
+        _ if matches!(
+            parent,
+            "table" | "thead" | "tbody" | "tfoot" | "tr" | "colgroup"
+        ) =>
+        {
+            matches!(child, "script" | "template")
+        }
+        _ => !(parent == "p" && CLOSES_PARAGRAPH.contains(child)),
+    }
+}
+
+/// The span of the first `tag_name` element among the HTML descendants of a
+/// node's children
+fn find_nested(children: &[JSXChild], tag_name: &str) -> Option<Span> {
+    children.iter().find_map(|child| match child {
+        JSXChild::Element(element) => {
+            let child_tag = get_tag_name(element);
+            if child_tag == tag_name {
+                Some(element.opening_element.span)
+            } else if is_component(&child_tag) {
+                None
+            } else {
+                find_nested(&element.children, tag_name)
+            }
+        }
+        JSXChild::Fragment(fragment) => find_nested(&fragment.children, tag_name),
+        _ => None,
+    })
+}
+
 /// Find a JSX attribute by name and return its value as a string.
 ///
 /// Handles expression containers, string literals, and boolean attributes (no value = true).
@@ -146,7 +267,7 @@ pub fn get_attr_value(attr: &JSXAttribute<'_>) -> Option<String> {
             .expression
             .as_expression()
             .map(|e| expr_to_string(e)),
-        Some(JSXAttributeValue::StringLiteral(lit)) => Some(format!("\"{}\"", lit.value)),
+        Some(JSXAttributeValue::StringLiteral(lit)) => Some(json_string(&lit.value)),
         None => Some("true".to_string()),
         _ => None,
     }
@@ -165,7 +286,195 @@ pub fn get_attr_name(name: &JSXAttributeName) -> String {
     }
 }
 
+/// Whether an attribute sets classes (`class`, `className` or `classList`)
+pub fn is_class_key(key: &str) -> bool {
+    matches!(key, "class" | "className" | "classList")
+}
+
+/// Check if an attribute key is an event handler (`onClick`, `on:click`, `oncapture:click`).
+///
+/// Lowercase keys like `onclick` or `open` are plain HTML attributes.
+pub fn is_event_attr(key: &str) -> bool {
+    if key.starts_with("on:") || key.starts_with("oncapture:") {
+        return true;
+    }
+    key.strip_prefix("on")
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_uppercase())
+}
+
+/// Check if an attribute takes `"true"`/`"false"` (`draggable`, `contentEditable`).
+///
+/// Boolean values must be written out as strings instead of toggling the attribute.
+pub fn is_enumerated_attr(key: &str) -> bool {
+    ENUMERATED_ATTRIBUTES.contains(key.to_ascii_lowercase().as_str())
+}
+
+/// Check if an element's whitespace is significant: `pre`/`textarea` and elements
+/// hinting at `white-space` styling through their class or inline style.
+///
+/// Dynamic `class`/`style` values can't be inspected, so they count as preserving.
+pub fn preserves_whitespace(element: &JSXElement<'_>, tag_name: &str) -> bool {
+    if matches!(tag_name, "pre" | "textarea" | "listing" | "plaintext") {
+        return true;
+    }
+
+    element.opening_element.attributes.iter().any(|attr| {
+        let JSXAttributeItem::Attribute(attr) = attr else {
+            return false;
+        };
+        let key = get_attr_name(&attr.name);
+        if key != "class" && key != "className" && key != "style" {
+            return false;
+        }
+        match &attr.value {
+            Some(JSXAttributeValue::StringLiteral(lit)) if key == "style" => {
+                lit.value.contains("white-space")
+            }
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                lit.value.split_whitespace().any(|class| {
+                    class.starts_with("whitespace-")
+                        && class != "whitespace-normal"
+                        && class != "whitespace-nowrap"
+                })
+            }
+            Some(_) => true,
+            None => false,
+        }
+    })
+}
+
+/// Check if JSX renders the same markup every time: native elements, text and
+/// string attributes only, with no components, spreads or expressions
+pub fn is_static_markup(children: &[JSXChild]) -> bool {
+    children.iter().all(|child| match child {
+        JSXChild::Text(_) => true,
+        JSXChild::Element(element) => is_static_element(element),
+        JSXChild::Fragment(fragment) => is_static_markup(&fragment.children),
+        // `{/* comments */}` render nothing
+        JSXChild::ExpressionContainer(container) => container.expression.as_expression().is_none(),
+        JSXChild::Spread(_) => false,
+    })
+}
+
+/// Check if an element renders the same markup every time (see [`is_static_markup`])
+pub fn is_static_element(element: &JSXElement) -> bool {
+    !is_component(&get_tag_name(element))
+        && element.opening_element.attributes.iter().all(|item| {
+            matches!(
+                item,
+                JSXAttributeItem::Attribute(attr)
+                    if matches!(attr.value, None | Some(JSXAttributeValue::StringLiteral(_)))
+            )
+        })
+        && is_static_markup(&element.children)
+}
+
+/// Check if an expression is a literal: a string, number, boolean, `null`, or a
+/// template literal without expressions
+pub fn is_literal(expr: &Expression) -> bool {
+    match expr {
+        Expression::StringLiteral(_)
+        | Expression::NumericLiteral(_)
+        | Expression::BigIntLiteral(_)
+        | Expression::BooleanLiteral(_)
+        | Expression::NullLiteral(_) => true,
+        Expression::TemplateLiteral(template) => template.expressions.is_empty(),
+        Expression::UnaryExpression(unary) => {
+            unary.operator == UnaryOperator::UnaryNegation
+                && matches!(unary.argument, Expression::NumericLiteral(_))
+        }
+        _ => false,
+    }
+}
+
+/// Whether an expression is an object or array literal built only from literals,
+/// so a single instance can be shared by every render
+pub fn is_static_value(expr: &Expression) -> bool {
+    match expr {
+        Expression::ObjectExpression(object) => object.properties.iter().all(|property| {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                return false;
+            };
+            property.kind == PropertyKind::Init
+                && !property.computed
+                && !property.method
+                && !property.shorthand
+                && (is_literal(&property.value) || is_static_value(&property.value))
+        }),
+        Expression::ArrayExpression(array) => array.elements.iter().all(|element| {
+            element
+                .as_expression()
+                .is_some_and(|expr| is_literal(expr) || is_static_value(expr))
+        }),
+        Expression::ParenthesizedExpression(paren) => is_static_value(&paren.expression),
+        _ => false,
+    }
+}
+
+/// Whether an expression can leave out a value: `null`, `undefined`, or a
+/// conditional with such a branch, like `{active ? "page" : undefined}`
+pub fn may_be_nullish(expr: &Expression) -> bool {
+    match expr {
+        Expression::NullLiteral(_) => true,
+        Expression::Identifier(ident) => ident.name == "undefined",
+        Expression::UnaryExpression(unary) => unary.operator == UnaryOperator::Void,
+        Expression::ConditionalExpression(cond) => {
+            may_be_nullish(&cond.consequent) || may_be_nullish(&cond.alternate)
+        }
+        Expression::LogicalExpression(logical) => {
+            logical.operator != LogicalOperator::And && may_be_nullish(&logical.right)
+        }
+        Expression::ParenthesizedExpression(paren) => may_be_nullish(&paren.expression),
+        _ => false,
+    }
+}
+
+/// Check if JSX children are constant: only text and literal expressions.
+///
+/// Constant children can be passed as a plain value rather than through a getter.
+pub fn is_constant_children(children: &[JSXChild]) -> bool {
+    children.iter().all(|child| match child {
+        JSXChild::Text(_) => true,
+        JSXChild::ExpressionContainer(container) => {
+            container.expression.as_expression().is_none_or(is_literal)
+        }
+        _ => false,
+    })
+}
+
 /// Check if a JSX attribute name is namespaced (e.g., `on:click`, `use:directive`).
 pub fn is_namespaced_attr(name: &JSXAttributeName) -> bool {
     matches!(name, JSXAttributeName::NamespacedName(_))
 }
+
+/// Find evidence that a module is already compiled output: `template` or `ssr`
+/// imported from the runtime module under the compiler's `_$` alias, or a
+/// hoisted `_tmpl$` declaration. Hand-written imports of those helpers don't
+/// count.
+///
+/// Returns the span of the first such statement.
+pub fn find_compiled_marker(program: &Program, module_name: &str) -> Option<Span> {
+    program.body.iter().find_map(|stmt| {
+        let compiled = match stmt {
+            Statement::ImportDeclaration(import) => {
+                import.source.value == module_name
+                    && import.specifiers.iter().flatten().any(|specifier| {
+                        matches!(
+                            specifier,
+                            ImportDeclarationSpecifier::ImportSpecifier(spec)
+                                if matches!(spec.local.name.as_str(), "_$template" | "_$ssr")
+                        )
+                    })
+            }
+            Statement::VariableDeclaration(decl) => decl.declarations.iter().any(|declarator| {
+                declarator
+                    .id
+                    .get_identifier_name()
+                    .is_some_and(|name| name.starts_with("_tmpl$"))
+            }),
+            _ => false,
+        };
+        compiled.then(|| stmt.span())
+    })
+}
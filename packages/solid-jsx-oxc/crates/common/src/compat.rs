@@ -0,0 +1,183 @@
+//! Output matching babel-plugin-jsx-dom-expressions
+//!
+//! Teams migrating from the Babel plugin diff old and new build output. This pass
+//! rewrites the names and module-level shape of a transformed program so that
+//! diff is down to whitespace (see [`Compat`](crate::Compat)):
+//!
+//! - each helper gets its own import
+//! - generated names are numbered per prefix the way Babel's `generateUid` does:
+//!   `_el$`, `_el$2`, `_el$3`, ...
+//! - templates are cloned with `_tmpl$()`
+//! - declarations of generated names use `var`
+
+use std::collections::{HashMap, HashSet};
+
+use oxc_allocator::{CloneIn, TakeIn};
+use oxc_ast::ast::{
+    Argument, BindingIdentifier, Expression, IdentifierReference, Program, Statement,
+    VariableDeclarationKind,
+};
+use oxc_ast::AstBuilder;
+use oxc_ast_visit::{walk_mut, VisitMut};
+use oxc_semantic::{Scoping, SemanticBuilder, SymbolId};
+
+/// Rewrite a transformed program to match the Babel plugin's output
+pub fn babel_compat<'a>(program: &mut Program<'a>, ast: AstBuilder<'a>, module_name: &str) {
+    split_helper_imports(program, ast, module_name);
+
+    let scoping = SemanticBuilder::new()
+        .build(program)
+        .semantic
+        .into_scoping();
+    let mut renames: HashMap<SymbolId, String> = HashMap::new();
+
+    // Numbered in the order the transform created them
+    let mut generated: Vec<(&str, usize, SymbolId)> = scoping
+        .symbol_ids()
+        .filter_map(|symbol| {
+            let (prefix, number) = generated_name(scoping.symbol_name(symbol))?;
+            Some((prefix, number, symbol))
+        })
+        .collect();
+    generated.sort_by_key(|(_, number, _)| *number);
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut templates = HashSet::new();
+    for (prefix, _, symbol) in generated {
+        let count = counts.entry(prefix).or_default();
+        *count += 1;
+        let name = if *count == 1 {
+            prefix.to_string()
+        } else {
+            format!("{}{}", prefix, count)
+        };
+        if prefix == "_tmpl$" {
+            templates.insert(symbol);
+        }
+        renames.insert(symbol, name);
+    }
+
+    let mut rewrite = Rewrite {
+        ast,
+        scoping: &scoping,
+        renames,
+        templates,
+    };
+    rewrite.visit_program(program);
+}
+
+/// Split a name the transforms generate into its prefix and number
+/// (`_el$12` into `_el$` and 12)
+fn generated_name(name: &str) -> Option<(&str, usize)> {
+    let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let generated = prefix.len() > 2
+        && prefix.starts_with('_')
+        && prefix.ends_with('$')
+        && prefix[1..prefix.len() - 1]
+            .chars()
+            .all(|c| c.is_ascii_alphabetic());
+    if !generated {
+        return None;
+    }
+    Some((prefix, name[prefix.len()..].parse().ok()?))
+}
+
+/// Give each helper of the generated import its own declaration, in order
+fn split_helper_imports<'a>(program: &mut Program<'a>, ast: AstBuilder<'a>, module_name: &str) {
+    let statements = std::mem::replace(&mut program.body, ast.vec());
+    let mut body = ast.vec_with_capacity(statements.len());
+    for stmt in statements {
+        let Statement::ImportDeclaration(mut import) = stmt else {
+            body.push(stmt);
+            continue;
+        };
+        if import.source.value != module_name || !import.span.is_unspanned() {
+            body.push(Statement::ImportDeclaration(import));
+            continue;
+        }
+        for specifier in import.specifiers.take().into_iter().flatten() {
+            body.push(Statement::ImportDeclaration(ast.alloc_import_declaration(
+                import.span,
+                Some(ast.vec1(specifier)),
+                import.source.clone_in(ast.allocator),
+                None,
+                None::<oxc_ast::ast::WithClause<'a>>,
+                import.import_kind,
+            )));
+        }
+    }
+    program.body = body;
+}
+
+struct Rewrite<'a, 's> {
+    ast: AstBuilder<'a>,
+    scoping: &'s Scoping,
+    renames: HashMap<SymbolId, String>,
+    templates: HashSet<SymbolId>,
+}
+
+impl<'a> VisitMut<'a> for Rewrite<'a, '_> {
+    fn visit_binding_identifier(&mut self, ident: &mut BindingIdentifier<'a>) {
+        if let Some(name) = ident.symbol_id.get().and_then(|id| self.renames.get(&id)) {
+            ident.name = self.ast.atom(name);
+        }
+    }
+
+    fn visit_identifier_reference(&mut self, ident: &mut IdentifierReference<'a>) {
+        if let Some(name) = self.symbol(ident).and_then(|id| self.renames.get(&id)) {
+            ident.name = self.ast.atom(name);
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &mut Statement<'a>) {
+        if let Statement::VariableDeclaration(decl) = stmt {
+            let generated = decl.declarations.iter().all(|declarator| {
+                declarator
+                    .id
+                    .get_binding_identifier()
+                    .and_then(|ident| ident.symbol_id.get())
+                    .is_some_and(|id| self.renames.contains_key(&id))
+            });
+            if generated {
+                decl.kind = VariableDeclarationKind::Var;
+            }
+        }
+        walk_mut::walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &mut Expression<'a>) {
+        // `_tmpl$.cloneNode(true)` to `_tmpl$()`
+        if let Expression::CallExpression(call) = expr {
+            let clones_template = match (&call.callee, call.arguments.as_slice()) {
+                (Expression::StaticMemberExpression(member), [Argument::BooleanLiteral(arg)]) => {
+                    member.property.name == "cloneNode"
+                        && arg.value
+                        && self.is_template(&member.object)
+                }
+                _ => false,
+            };
+            if clones_template {
+                if let Expression::StaticMemberExpression(member) = &mut call.callee {
+                    let object = member.object.take_in(self.ast);
+                    call.callee = object;
+                    call.arguments.clear();
+                }
+            }
+        }
+        walk_mut::walk_expression(self, expr);
+    }
+}
+
+impl Rewrite<'_, '_> {
+    /// Whether an expression names a template declaration
+    fn is_template(&self, expr: &Expression) -> bool {
+        matches!(expr, Expression::Identifier(ident)
+            if self.symbol(ident).is_some_and(|id| self.templates.contains(&id)))
+    }
+
+    /// The symbol a reference resolves to
+    fn symbol(&self, ident: &IdentifierReference) -> Option<SymbolId> {
+        let reference = ident.reference_id.get()?;
+        self.scoping.get_reference(reference).symbol_id()
+    }
+}
@@ -39,6 +39,59 @@ pub static PROPERTIES: Set<&'static str> = phf_set! {
     "selected",
 };
 
+/// Boolean attributes: present when truthy, omitted otherwise
+pub static BOOLEAN_ATTRIBUTES: Set<&'static str> = phf_set! {
+    "allowfullscreen",
+    "async",
+    "alpha",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "indeterminate",
+    "inert",
+    "ismap",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "seamless",
+    "selected",
+    "adauctionheaders",
+    "browsingtopics",
+    "credentialless",
+    "defaultchecked",
+    "defaultmuted",
+    "defaultselected",
+    "defer",
+    "disablepictureinpicture",
+    "disableremoteplayback",
+    "preservespitch",
+    "shadowrootclonable",
+    "shadowrootcustomelementregistry",
+    "shadowrootdelegatesfocus",
+    "shadowrootserializable",
+    "sharedstoragewritable",
+};
+
+/// Enumerated attributes taking `"true"`/`"false"`, where an empty or missing
+/// value means something else than `false`
+pub static ENUMERATED_ATTRIBUTES: Set<&'static str> = phf_set! {
+    "contenteditable",
+    "draggable",
+    "spellcheck",
+};
+
 /// Child properties that affect children
 pub static CHILD_PROPERTIES: Set<&'static str> = phf_set! {
     "innerHTML",
@@ -47,12 +100,310 @@ pub static CHILD_PROPERTIES: Set<&'static str> = phf_set! {
     "children",
 };
 
+/// Attribute namespaces compiled on their own rather than passed to a spread
+pub static NON_SPREAD_NAMESPACES: Set<&'static str> = phf_set! {
+    "class",
+    "style",
+    "use",
+    "prop",
+    "attr",
+    "bool",
+};
+
 /// Attribute aliases (JSX name -> DOM name)
 pub static ALIASES: phf::Map<&'static str, &'static str> = phf::phf_map! {
     "className" => "class",
     "htmlFor" => "for",
 };
 
+/// Attributes set through a differently named property, on the listed tags
+/// only (on every tag when none are listed)
+pub static PROP_ALIASES: phf::Map<&'static str, (&'static str, &[&'static str])> = phf::phf_map! {
+    "class" => ("className", &[]),
+    "for" => ("htmlFor", &["label", "output"]),
+    "formnovalidate" => ("formNoValidate", &["button", "input"]),
+    "ismap" => ("isMap", &["img"]),
+    "nomodule" => ("noModule", &["script"]),
+    "playsinline" => ("playsInline", &["video"]),
+    "readonly" => ("readOnly", &["input", "textarea"]),
+};
+
+/// The property an attribute is set through on `tag`, when it is aliased there
+pub fn prop_alias(name: &str, tag: &str) -> Option<&'static str> {
+    let (property, tags) = PROP_ALIASES.get(name)?;
+    (tags.is_empty() || tags.contains(&tag)).then_some(*property)
+}
+
+/// Namespace URIs of the prefixed attributes SVG uses (`xlink:href`, `xml:lang`)
+pub static SVG_NAMESPACE: phf::Map<&'static str, &'static str> = phf::phf_map! {
+    "xlink" => "http://www.w3.org/1999/xlink",
+    "xml" => "http://www.w3.org/XML/1998/namespace",
+};
+
+/// The namespace URI of a prefixed attribute name
+pub fn attribute_namespace(name: &str) -> Option<&'static str> {
+    let (prefix, _) = name.split_once(':')?;
+    SVG_NAMESPACE.get(prefix).copied()
+}
+
+/// The named character references of XHTML, the entities JSX text and string
+/// attributes may use
+pub static XHTML_ENTITIES: phf::Map<&'static str, char> = phf::phf_map! {
+    "quot" => '\u{22}',
+    "amp" => '\u{26}',
+    "apos" => '\u{27}',
+    "lt" => '\u{3C}',
+    "gt" => '\u{3E}',
+    "nbsp" => '\u{A0}',
+    "iexcl" => '\u{A1}',
+    "cent" => '\u{A2}',
+    "pound" => '\u{A3}',
+    "curren" => '\u{A4}',
+    "yen" => '\u{A5}',
+    "brvbar" => '\u{A6}',
+    "sect" => '\u{A7}',
+    "uml" => '\u{A8}',
+    "copy" => '\u{A9}',
+    "ordf" => '\u{AA}',
+    "laquo" => '\u{AB}',
+    "not" => '\u{AC}',
+    "shy" => '\u{AD}',
+    "reg" => '\u{AE}',
+    "macr" => '\u{AF}',
+    "deg" => '\u{B0}',
+    "plusmn" => '\u{B1}',
+    "sup2" => '\u{B2}',
+    "sup3" => '\u{B3}',
+    "acute" => '\u{B4}',
+    "micro" => '\u{B5}',
+    "para" => '\u{B6}',
+    "middot" => '\u{B7}',
+    "cedil" => '\u{B8}',
+    "sup1" => '\u{B9}',
+    "ordm" => '\u{BA}',
+    "raquo" => '\u{BB}',
+    "frac14" => '\u{BC}',
+    "frac12" => '\u{BD}',
+    "frac34" => '\u{BE}',
+    "iquest" => '\u{BF}',
+    "Agrave" => '\u{C0}',
+    "Aacute" => '\u{C1}',
+    "Acirc" => '\u{C2}',
+    "Atilde" => '\u{C3}',
+    "Auml" => '\u{C4}',
+    "Aring" => '\u{C5}',
+    "AElig" => '\u{C6}',
+    "Ccedil" => '\u{C7}',
+    "Egrave" => '\u{C8}',
+    "Eacute" => '\u{C9}',
+    "Ecirc" => '\u{CA}',
+    "Euml" => '\u{CB}',
+    "Igrave" => '\u{CC}',
+    "Iacute" => '\u{CD}',
+    "Icirc" => '\u{CE}',
+    "Iuml" => '\u{CF}',
+    "ETH" => '\u{D0}',
+    "Ntilde" => '\u{D1}',
+    "Ograve" => '\u{D2}',
+    "Oacute" => '\u{D3}',
+    "Ocirc" => '\u{D4}',
+    "Otilde" => '\u{D5}',
+    "Ouml" => '\u{D6}',
+    "times" => '\u{D7}',
+    "Oslash" => '\u{D8}',
+    "Ugrave" => '\u{D9}',
+    "Uacute" => '\u{DA}',
+    "Ucirc" => '\u{DB}',
+    "Uuml" => '\u{DC}',
+    "Yacute" => '\u{DD}',
+    "THORN" => '\u{DE}',
+    "szlig" => '\u{DF}',
+    "agrave" => '\u{E0}',
+    "aacute" => '\u{E1}',
+    "acirc" => '\u{E2}',
+    "atilde" => '\u{E3}',
+    "auml" => '\u{E4}',
+    "aring" => '\u{E5}',
+    "aelig" => '\u{E6}',
+    "ccedil" => '\u{E7}',
+    "egrave" => '\u{E8}',
+    "eacute" => '\u{E9}',
+    "ecirc" => '\u{EA}',
+    "euml" => '\u{EB}',
+    "igrave" => '\u{EC}',
+    "iacute" => '\u{ED}',
+    "icirc" => '\u{EE}',
+    "iuml" => '\u{EF}',
+    "eth" => '\u{F0}',
+    "ntilde" => '\u{F1}',
+    "ograve" => '\u{F2}',
+    "oacute" => '\u{F3}',
+    "ocirc" => '\u{F4}',
+    "otilde" => '\u{F5}',
+    "ouml" => '\u{F6}',
+    "divide" => '\u{F7}',
+    "oslash" => '\u{F8}',
+    "ugrave" => '\u{F9}',
+    "uacute" => '\u{FA}',
+    "ucirc" => '\u{FB}',
+    "uuml" => '\u{FC}',
+    "yacute" => '\u{FD}',
+    "thorn" => '\u{FE}',
+    "yuml" => '\u{FF}',
+    "OElig" => '\u{152}',
+    "oelig" => '\u{153}',
+    "Scaron" => '\u{160}',
+    "scaron" => '\u{161}',
+    "Yuml" => '\u{178}',
+    "fnof" => '\u{192}',
+    "circ" => '\u{2C6}',
+    "tilde" => '\u{2DC}',
+    "Alpha" => '\u{391}',
+    "Beta" => '\u{392}',
+    "Gamma" => '\u{393}',
+    "Delta" => '\u{394}',
+    "Epsilon" => '\u{395}',
+    "Zeta" => '\u{396}',
+    "Eta" => '\u{397}',
+    "Theta" => '\u{398}',
+    "Iota" => '\u{399}',
+    "Kappa" => '\u{39A}',
+    "Lambda" => '\u{39B}',
+    "Mu" => '\u{39C}',
+    "Nu" => '\u{39D}',
+    "Xi" => '\u{39E}',
+    "Omicron" => '\u{39F}',
+    "Pi" => '\u{3A0}',
+    "Rho" => '\u{3A1}',
+    "Sigma" => '\u{3A3}',
+    "Tau" => '\u{3A4}',
+    "Upsilon" => '\u{3A5}',
+    "Phi" => '\u{3A6}',
+    "Chi" => '\u{3A7}',
+    "Psi" => '\u{3A8}',
+    "Omega" => '\u{3A9}',
+    "alpha" => '\u{3B1}',
+    "beta" => '\u{3B2}',
+    "gamma" => '\u{3B3}',
+    "delta" => '\u{3B4}',
+    "epsilon" => '\u{3B5}',
+    "zeta" => '\u{3B6}',
+    "eta" => '\u{3B7}',
+    "theta" => '\u{3B8}',
+    "iota" => '\u{3B9}',
+    "kappa" => '\u{3BA}',
+    "lambda" => '\u{3BB}',
+    "mu" => '\u{3BC}',
+    "nu" => '\u{3BD}',
+    "xi" => '\u{3BE}',
+    "omicron" => '\u{3BF}',
+    "pi" => '\u{3C0}',
+    "rho" => '\u{3C1}',
+    "sigmaf" => '\u{3C2}',
+    "sigma" => '\u{3C3}',
+    "tau" => '\u{3C4}',
+    "upsilon" => '\u{3C5}',
+    "phi" => '\u{3C6}',
+    "chi" => '\u{3C7}',
+    "psi" => '\u{3C8}',
+    "omega" => '\u{3C9}',
+    "thetasym" => '\u{3D1}',
+    "upsih" => '\u{3D2}',
+    "piv" => '\u{3D6}',
+    "ensp" => '\u{2002}',
+    "emsp" => '\u{2003}',
+    "thinsp" => '\u{2009}',
+    "zwnj" => '\u{200C}',
+    "zwj" => '\u{200D}',
+    "lrm" => '\u{200E}',
+    "rlm" => '\u{200F}',
+    "ndash" => '\u{2013}',
+    "mdash" => '\u{2014}',
+    "lsquo" => '\u{2018}',
+    "rsquo" => '\u{2019}',
+    "sbquo" => '\u{201A}',
+    "ldquo" => '\u{201C}',
+    "rdquo" => '\u{201D}',
+    "bdquo" => '\u{201E}',
+    "dagger" => '\u{2020}',
+    "Dagger" => '\u{2021}',
+    "bull" => '\u{2022}',
+    "hellip" => '\u{2026}',
+    "permil" => '\u{2030}',
+    "prime" => '\u{2032}',
+    "Prime" => '\u{2033}',
+    "lsaquo" => '\u{2039}',
+    "rsaquo" => '\u{203A}',
+    "oline" => '\u{203E}',
+    "frasl" => '\u{2044}',
+    "euro" => '\u{20AC}',
+    "image" => '\u{2111}',
+    "weierp" => '\u{2118}',
+    "real" => '\u{211C}',
+    "trade" => '\u{2122}',
+    "alefsym" => '\u{2135}',
+    "larr" => '\u{2190}',
+    "uarr" => '\u{2191}',
+    "rarr" => '\u{2192}',
+    "darr" => '\u{2193}',
+    "harr" => '\u{2194}',
+    "crarr" => '\u{21B5}',
+    "lArr" => '\u{21D0}',
+    "uArr" => '\u{21D1}',
+    "rArr" => '\u{21D2}',
+    "dArr" => '\u{21D3}',
+    "hArr" => '\u{21D4}',
+    "forall" => '\u{2200}',
+    "part" => '\u{2202}',
+    "exist" => '\u{2203}',
+    "empty" => '\u{2205}',
+    "nabla" => '\u{2207}',
+    "isin" => '\u{2208}',
+    "notin" => '\u{2209}',
+    "ni" => '\u{220B}',
+    "prod" => '\u{220F}',
+    "sum" => '\u{2211}',
+    "minus" => '\u{2212}',
+    "lowast" => '\u{2217}',
+    "radic" => '\u{221A}',
+    "prop" => '\u{221D}',
+    "infin" => '\u{221E}',
+    "ang" => '\u{2220}',
+    "and" => '\u{2227}',
+    "or" => '\u{2228}',
+    "cap" => '\u{2229}',
+    "cup" => '\u{222A}',
+    "int" => '\u{222B}',
+    "there4" => '\u{2234}',
+    "sim" => '\u{223C}',
+    "cong" => '\u{2245}',
+    "asymp" => '\u{2248}',
+    "ne" => '\u{2260}',
+    "equiv" => '\u{2261}',
+    "le" => '\u{2264}',
+    "ge" => '\u{2265}',
+    "sub" => '\u{2282}',
+    "sup" => '\u{2283}',
+    "nsub" => '\u{2284}',
+    "sube" => '\u{2286}',
+    "supe" => '\u{2287}',
+    "oplus" => '\u{2295}',
+    "otimes" => '\u{2297}',
+    "perp" => '\u{22A5}',
+    "sdot" => '\u{22C5}',
+    "lceil" => '\u{2308}',
+    "rceil" => '\u{2309}',
+    "lfloor" => '\u{230A}',
+    "rfloor" => '\u{230B}',
+    "lang" => '\u{2329}',
+    "rang" => '\u{232A}',
+    "loz" => '\u{25CA}',
+    "spades" => '\u{2660}',
+    "clubs" => '\u{2663}',
+    "hearts" => '\u{2665}',
+    "diams" => '\u{2666}',
+};
+
 /// Events that can be delegated (bubbling events)
 pub static DELEGATED_EVENTS: Set<&'static str> = phf_set! {
     "beforeinput",
@@ -161,6 +512,178 @@ pub static VOID_ELEMENTS: Set<&'static str> = phf_set! {
     "wbr",
 };
 
+/// Block-level elements; whitespace next to them never renders
+pub static BLOCK_ELEMENTS: Set<&'static str> = phf_set! {
+    "address",
+    "article",
+    "aside",
+    "base",
+    "blockquote",
+    "body",
+    "br",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "link",
+    "main",
+    "meta",
+    "nav",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+};
+
+/// Elements whose start tag closes an open `<p>`, so they can't be inside one
+pub static CLOSES_PARAGRAPH: Set<&'static str> = phf_set! {
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "search",
+    "section",
+    "table",
+    "ul",
+};
+
+/// Elements whose closing tag is kept even at the end of a template, as the
+/// HTML parser doesn't close them where the template expects
+pub static ALWAYS_CLOSE: Set<&'static str> = phf_set! {
+    "title",
+    "style",
+    "a",
+    "strong",
+    "small",
+    "b",
+    "u",
+    "i",
+    "em",
+    "s",
+    "code",
+    "object",
+    "table",
+    "button",
+    "textarea",
+    "select",
+    "iframe",
+    "script",
+    "noscript",
+    "template",
+    "fieldset",
+};
+
+/// Inline elements; a block element can't be left open inside one
+pub static INLINE_ELEMENTS: Set<&'static str> = phf_set! {
+    "a",
+    "abbr",
+    "acronym",
+    "b",
+    "bdi",
+    "bdo",
+    "big",
+    "br",
+    "button",
+    "canvas",
+    "cite",
+    "code",
+    "data",
+    "datalist",
+    "del",
+    "dfn",
+    "em",
+    "embed",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "map",
+    "mark",
+    "meter",
+    "noscript",
+    "object",
+    "output",
+    "picture",
+    "progress",
+    "q",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "select",
+    "slot",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "svg",
+    "template",
+    "textarea",
+    "time",
+    "tt",
+    "u",
+    "var",
+    "video",
+};
+
 /// Solid's built-in control flow components
 pub static BUILT_INS: Set<&'static str> = phf_set! {
     "For",
@@ -0,0 +1,80 @@
+//! Bookkeeping shared by the DOM and SSR transform contexts
+//!
+//! Each backend threads its own context through the transform, but both track
+//! helper imports, generated names, hoisted constants and diagnostics the same
+//! way. That part lives here, so code written against [`TransformContext`] runs
+//! unchanged in either backend.
+
+use indexmap::IndexSet;
+
+use crate::diagnostic::Diagnostic;
+use crate::pragma::StaticMarks;
+
+/// Module-level state every transform context carries
+#[derive(Default)]
+pub struct ModuleState {
+    /// Helper imports needed
+    pub helpers: IndexSet<String>,
+
+    /// Variable counter for unique names
+    pub var_counter: usize,
+
+    /// Depth of elements whose whitespace must be kept verbatim (`pre`, `textarea`, ...)
+    pub preserve_whitespace: usize,
+
+    /// Module-level constants as `(name, initializer)`
+    pub hoisted: Vec<(String, String)>,
+
+    /// Diagnostics reported during the transform
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Expressions marked to be read once
+    pub static_marks: StaticMarks,
+}
+
+/// A backend's transform context
+pub trait TransformContext {
+    /// The module-level state of the context
+    fn module(&self) -> &ModuleState;
+
+    /// The module-level state of the context, mutably
+    fn module_mut(&mut self) -> &mut ModuleState;
+
+    /// Generate a unique variable name
+    fn generate_uid(&mut self, prefix: &str) -> String {
+        let module = self.module_mut();
+        module.var_counter += 1;
+        format!("_{}{}", prefix, module.var_counter)
+    }
+
+    /// Whether we're inside an element that keeps its whitespace verbatim
+    fn preserves_whitespace(&self) -> bool {
+        self.module().preserve_whitespace > 0
+    }
+
+    /// Hoist a value to a module-level constant and return its name.
+    /// Identical values share one constant.
+    fn hoist(&mut self, prefix: &str, init: String) -> String {
+        let existing = self
+            .module()
+            .hoisted
+            .iter()
+            .find(|(_, existing)| *existing == init);
+        if let Some((name, _)) = existing {
+            return name.clone();
+        }
+        let name = self.generate_uid(prefix);
+        self.module_mut().hoisted.push((name.clone(), init));
+        name
+    }
+
+    /// Register a helper import
+    fn register_helper(&mut self, name: &str) {
+        self.module_mut().helpers.insert(name.to_string());
+    }
+
+    /// Report a diagnostic
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.module_mut().diagnostics.push(diagnostic);
+    }
+}
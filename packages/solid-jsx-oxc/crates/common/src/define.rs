@@ -0,0 +1,296 @@
+//! Compile-time constant replacement (`define`)
+//!
+//! Configured identifiers and member chains (`__DEV__`, `import.meta.env.SSR`) are
+//! swapped for literals before JSX is analyzed, and branches on the resulting
+//! literals are folded, so dev-only or SSR-only code never becomes an effect.
+//! JSX branches on literals written in the source (`{true ? <A /> : <B />}`,
+//! `<Show when={false}>`) are folded the same way, so only the taken one is
+//! compiled.
+
+use oxc_allocator::{Allocator, CloneIn, TakeIn};
+use oxc_ast::ast::{
+    Expression, IdentifierReference, JSXAttributeItem, JSXAttributeName, JSXAttributeValue,
+    JSXChild, JSXElement, JSXElementName, JSXExpression, LogicalOperator, Statement, UnaryOperator,
+};
+use oxc_ast::AstBuilder;
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType, Span};
+
+use crate::options::TransformOptions;
+
+/// Apply `define` replacements to `expr`, folding the branch it guards if it
+/// becomes a literal test.
+///
+/// Run this on entering an expression: dropped branches are then never visited,
+/// so no templates or helpers are registered for them. `is_global` decides whether
+/// an identifier refers to a global, so local bindings shadowing a defined name
+/// are left alone.
+pub fn substitute_constants<'a>(
+    expr: &mut Expression<'a>,
+    options: &TransformOptions<'a>,
+    ast: AstBuilder<'a>,
+    is_global: &impl Fn(&IdentifierReference) -> bool,
+) {
+    if options.define.is_empty() {
+        return;
+    }
+    if let Some(value) = define_value(expr, options, is_global) {
+        if let Some(replacement) = parse_define_value(value, ast.allocator) {
+            *expr = replacement;
+        }
+        return;
+    }
+
+    // Resolve the test first so a literal can decide the branch
+    match expr {
+        Expression::LogicalExpression(logical) => {
+            substitute_constants(&mut logical.left, options, ast, is_global)
+        }
+        Expression::ConditionalExpression(conditional) => {
+            substitute_constants(&mut conditional.test, options, ast, is_global)
+        }
+        Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::LogicalNot => {
+            substitute_constants(&mut unary.argument, options, ast, is_global)
+        }
+        _ => return,
+    }
+    if let Some(folded) = fold_constant(expr, ast) {
+        *expr = folded;
+        substitute_constants(expr, options, ast, is_global);
+    }
+}
+
+/// Replacement source for `expr` if it matches a `define` entry
+fn define_value<'o>(
+    expr: &Expression,
+    options: &TransformOptions<'o>,
+    is_global: &impl Fn(&IdentifierReference) -> bool,
+) -> Option<&'o str> {
+    let (key, root) = define_key(expr)?;
+    if root.is_some_and(|ident| !is_global(ident)) {
+        return None;
+    }
+    options
+        .define
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| *value)
+}
+
+/// Dotted name of an identifier or static member chain, with its root identifier
+/// (`None` for `import.meta` chains)
+fn define_key<'e, 'a>(
+    expr: &'e Expression<'a>,
+) -> Option<(String, Option<&'e IdentifierReference<'a>>)> {
+    match expr {
+        Expression::Identifier(ident) => Some((ident.name.to_string(), Some(&**ident))),
+        Expression::MetaProperty(meta) => {
+            Some((format!("{}.{}", meta.meta.name, meta.property.name), None))
+        }
+        Expression::StaticMemberExpression(member) => {
+            let (object, root) = define_key(&member.object)?;
+            Some((format!("{}.{}", object, member.property.name), root))
+        }
+        _ => None,
+    }
+}
+
+/// Parse a `define` replacement into an expression
+fn parse_define_value<'a>(value: &'a str, allocator: &'a Allocator) -> Option<Expression<'a>> {
+    let program = Parser::new(allocator, value, SourceType::tsx())
+        .parse()
+        .program;
+    match program.body.first() {
+        Some(Statement::ExpressionStatement(stmt)) => Some(stmt.expression.clone_in(allocator)),
+        _ => None,
+    }
+}
+
+/// Fold conditional and logical expressions with a literal test and a JSX branch,
+/// keeping only the branch taken.
+///
+/// Run this on entering an expression, like [`substitute_constants`].
+pub fn fold_jsx_branches<'a>(expr: &mut Expression<'a>, ast: AstBuilder<'a>) {
+    while has_jsx_branch(expr) {
+        match fold_constant(expr, ast) {
+            Some(folded) => *expr = folded,
+            None => break,
+        }
+    }
+}
+
+/// Whether a conditional or logical expression has JSX among its branches
+fn has_jsx_branch(expr: &Expression) -> bool {
+    let is_jsx = |expr: &Expression| {
+        let expr = expr.without_parentheses();
+        matches!(expr, Expression::JSXElement(_) | Expression::JSXFragment(_))
+            || has_jsx_branch(expr)
+    };
+    match expr {
+        Expression::ConditionalExpression(conditional) => {
+            is_jsx(&conditional.consequent) || is_jsx(&conditional.alternate)
+        }
+        Expression::LogicalExpression(logical) => is_jsx(&logical.left) || is_jsx(&logical.right),
+        _ => false,
+    }
+}
+
+/// What a `<Show>` with a literal `when` renders: its children when the test
+/// is truthy, its fallback (or `null`) otherwise. The children are kept as the
+/// element itself when there's one, and in a fragment otherwise.
+///
+/// Returns `None` if `element` isn't a `<Show>`, its test isn't a literal, it
+/// has spread props, or its children take the value through a callback.
+pub fn fold_show<'a>(element: &mut JSXElement<'a>, ast: AstBuilder<'a>) -> Option<Expression<'a>> {
+    let JSXElementName::IdentifierReference(tag) = &element.opening_element.name else {
+        return None;
+    };
+    if tag.name != "Show" {
+        return None;
+    }
+    let mut when = None;
+    let mut fallback = None;
+    for item in element.opening_element.attributes.iter_mut() {
+        let JSXAttributeItem::Attribute(attr) = item else {
+            return None;
+        };
+        let attr = &mut **attr;
+        let JSXAttributeName::Identifier(name) = &attr.name else {
+            continue;
+        };
+        match name.name.as_str() {
+            "when" => when = Some(&mut attr.value),
+            "fallback" => fallback = Some(&mut attr.value),
+            _ => {}
+        }
+    }
+    let shown = match when? {
+        None => true,
+        Some(JSXAttributeValue::StringLiteral(lit)) => !lit.value.is_empty(),
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            is_truthy(container.expression.as_expression()?)?
+        }
+        Some(_) => return None,
+    };
+
+    let span = element.span;
+    if !shown {
+        return Some(match fallback.and_then(Option::take) {
+            Some(JSXAttributeValue::ExpressionContainer(mut container)) => {
+                match container.expression.as_expression_mut() {
+                    Some(expr) => expr.take_in(ast),
+                    None => ast.expression_null_literal(span),
+                }
+            }
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                ast.expression_string_literal(lit.span, lit.value, None)
+            }
+            Some(JSXAttributeValue::Element(fallback)) => Expression::JSXElement(fallback),
+            Some(JSXAttributeValue::Fragment(fallback)) => Expression::JSXFragment(fallback),
+            None => ast.expression_null_literal(span),
+        });
+    }
+
+    let is_callback = element.children.iter().any(|child| {
+        matches!(
+            child,
+            JSXChild::ExpressionContainer(container) if matches!(
+                container.expression.as_expression().map(Expression::without_parentheses),
+                Some(Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_))
+            )
+        )
+    });
+    if is_callback {
+        return None;
+    }
+    let children = std::mem::replace(&mut element.children, ast.vec());
+    let mut rendered = children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| match child {
+            JSXChild::Text(text) => !text.value.trim().is_empty(),
+            JSXChild::ExpressionContainer(container) => {
+                container.expression.as_expression().is_some()
+            }
+            _ => true,
+        });
+    let single = match (rendered.next(), rendered.next()) {
+        (Some((index, JSXChild::Element(_))), None) => Some(index),
+        _ => None,
+    };
+    if let Some(index) = single {
+        if let Some(JSXChild::Element(child)) = children.into_iter().nth(index) {
+            return Some(Expression::JSXElement(child));
+        }
+        return None;
+    }
+    Some(ast.expression_jsx_fragment(
+        span,
+        ast.jsx_opening_fragment(span),
+        children,
+        ast.jsx_closing_fragment(span),
+    ))
+}
+
+/// The JSX child that renders `expr`
+pub fn expression_child<'a>(expr: Expression<'a>, ast: AstBuilder<'a>) -> JSXChild<'a> {
+    match expr {
+        Expression::JSXElement(element) => JSXChild::Element(element),
+        Expression::JSXFragment(fragment) => JSXChild::Fragment(fragment),
+        expr => {
+            let span = expr.span();
+            JSXChild::ExpressionContainer(
+                ast.alloc_jsx_expression_container(span, JSXExpression::from(expr)),
+            )
+        }
+    }
+}
+
+/// Fold a logical, conditional or `!` expression whose test is a literal.
+///
+/// The kept branch is moved out rather than cloned so it keeps its semantic ids.
+fn fold_constant<'a>(expr: &mut Expression<'a>, ast: AstBuilder<'a>) -> Option<Expression<'a>> {
+    let placeholder = || ast.expression_null_literal(Span::default());
+    match expr {
+        Expression::LogicalExpression(logical) => {
+            let keep_left = match logical.operator {
+                LogicalOperator::And => !is_truthy(&logical.left)?,
+                LogicalOperator::Or => is_truthy(&logical.left)?,
+                LogicalOperator::Coalesce => {
+                    is_truthy(&logical.left)?;
+                    !matches!(logical.left, Expression::NullLiteral(_))
+                }
+            };
+            let kept = if keep_left {
+                &mut logical.left
+            } else {
+                &mut logical.right
+            };
+            Some(std::mem::replace(kept, placeholder()))
+        }
+        Expression::ConditionalExpression(conditional) => {
+            let kept = if is_truthy(&conditional.test)? {
+                &mut conditional.consequent
+            } else {
+                &mut conditional.alternate
+            };
+            Some(std::mem::replace(kept, placeholder()))
+        }
+        Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::LogicalNot => {
+            let value = !is_truthy(&unary.argument)?;
+            Some(ast.expression_boolean_literal(Span::default(), value))
+        }
+        _ => None,
+    }
+}
+
+/// Truthiness of a literal, or `None` if `expr` isn't one
+fn is_truthy(expr: &Expression) -> Option<bool> {
+    match expr {
+        Expression::BooleanLiteral(lit) => Some(lit.value),
+        Expression::NullLiteral(_) => Some(false),
+        Expression::NumericLiteral(lit) => Some(lit.value != 0.0 && !lit.value.is_nan()),
+        Expression::StringLiteral(lit) => Some(!lit.value.is_empty()),
+        _ => None,
+    }
+}
@@ -1,9 +1,17 @@
 //! Expression utilities for working with OXC AST
 
-use oxc_ast::ast::{Expression, JSXChild, JSXElement, Statement};
+use oxc_ast::ast::{
+    BinaryOperator, Expression, JSXChild, JSXElement, LogicalOperator, ObjectExpression,
+    ObjectPropertyKind, PropertyKey, Statement, UnaryOperator,
+};
 use oxc_codegen::{Codegen, CodegenOptions};
 use oxc_span::Span;
 
+use crate::check::{get_tag_name, is_dynamic};
+use crate::constants::{BLOCK_ELEMENTS, XHTML_ENTITIES};
+use crate::context::TransformContext;
+use crate::pragma::StaticMarks;
+
 /// Convert an Expression AST node to its source code string
 pub fn expr_to_string(expr: &Expression<'_>) -> String {
     let mut codegen = Codegen::new().with_options(CodegenOptions::default());
@@ -19,7 +27,7 @@ pub fn stmt_to_string(stmt: &Statement<'_>) -> String {
         Statement::ExpressionStatement(expr_stmt) => expr_to_string(&expr_stmt.expression),
         _ => {
             // Fallback - this is less common
-            format!("/* unsupported statement */")
+            "/* unsupported statement */".to_string()
         }
     }
 }
@@ -68,53 +76,130 @@ pub fn escape_html(text: &str, quote_escape: bool) -> String {
     result
 }
 
+/// Decode the character references in JSX text or a string attribute:
+/// `&amp;`, `&nbsp;`, `&#39;`, `&#x27;`. Anything else is left as written.
+pub fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // Entity names are at most 10 characters, as in Babel
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..1 + end])?, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The character an entity name (between `&` and `;`) stands for
+fn decode_entity(name: &str) -> Option<char> {
+    let (digits, radix) = if let Some(hex) = name.strip_prefix("#x") {
+        (hex, 16)
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        (decimal, 10)
+    } else {
+        return XHTML_ENTITIES.get(name).copied();
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(digits, radix).ok()?)
+}
+
 /// Trim whitespace from JSX text (preserving significant spaces)
 ///
 /// JSX whitespace rules:
-/// - Text with newlines: trim leading/trailing whitespace (indentation)
-/// - Inline text (no newlines): preserve trailing space (e.g., ". " between expressions)
+/// - Lines after the first lose their indentation, lines before the last their
+///   trailing whitespace
+/// - Whitespace-only lines are dropped, and the rest joined with a space
+/// - Spaces on the line of a neighbouring tag or expression are kept: the
+///   trailing space of `Hello {name}`
 /// - Multiple whitespace collapses to single space
+///
+/// Only ASCII whitespace counts, so a decoded `&nbsp;` is kept.
 pub fn trim_whitespace(text: &str) -> String {
-    let has_newline = text.contains('\n');
+    let is_space = |c: char| c.is_ascii_whitespace();
+    let text = text.replace('\r', "");
+    let text = if text.contains('\n') {
+        let last = text.matches('\n').count();
+        text.split('\n')
+            .enumerate()
+            .map(|(i, line)| {
+                let line = if i == 0 {
+                    line
+                } else {
+                    line.trim_start_matches(is_space)
+                };
+                if i == last {
+                    line
+                } else {
+                    line.trim_end_matches(is_space)
+                }
+            })
+            .filter(|line| !line.chars().all(is_space))
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        text
+    };
 
-    // Collapse multiple whitespace into single space
-    let mut result = String::new();
+    let mut result = String::with_capacity(text.len());
     let mut prev_was_space = false;
-
     for c in text.chars() {
-        if c.is_whitespace() {
-            if !prev_was_space && !result.is_empty() {
+        if is_space(c) {
+            if !prev_was_space {
                 result.push(' ');
-                prev_was_space = true;
             }
+            prev_was_space = true;
         } else {
             result.push(c);
             prev_was_space = false;
         }
     }
+    result
+}
 
-    // Only trim if text contained newlines (multi-line JSX text with indentation)
-    // Preserve trailing space for inline text like ". " between expressions
-    if has_newline {
-        result.trim().to_string()
-    } else {
-        result
+/// Drop whitespace next to block-level siblings, where browsers would collapse it anyway
+pub fn trim_around_blocks(content: &str, children: &[JSXChild<'_>], index: usize) -> String {
+    let is_block = |child: Option<&JSXChild<'_>>| match child {
+        Some(JSXChild::Element(el)) => BLOCK_ELEMENTS.contains(get_tag_name(el).as_str()),
+        _ => false,
+    };
+
+    let mut text = content;
+    if index > 0 && is_block(children.get(index - 1)) {
+        text = text.trim_start();
+    }
+    if is_block(children.get(index + 1)) {
+        text = text.trim_end();
     }
+    text.to_string()
 }
 
 /// Convert event name from JSX format (onClick or on:click) to DOM format (click)
 pub fn to_event_name(name: &str) -> String {
-    if name.starts_with("on:") {
-        // Handle on:click -> click (namespaced form)
-        name[3..].to_string()
-    } else if name.starts_with("on") {
-        let event = &name[2..];
-        // Handle onClick -> click (lowercase first char)
-        if let Some(first) = event.chars().next() {
-            format!("{}{}", first.to_lowercase(), &event[first.len_utf8()..])
-        } else {
-            String::new()
-        }
+    if let Some(event) = name
+        .strip_prefix("on:")
+        .or_else(|| name.strip_prefix("oncapture:"))
+    {
+        // Namespaced forms keep the exact event name (on:my-event -> my-event)
+        event.to_string()
+    } else if let Some(event) = name.strip_prefix("on") {
+        // DOM event names are lowercase (onMouseEnter -> mouseenter)
+        event.to_ascii_lowercase()
     } else {
         name.to_string()
     }
@@ -142,3 +227,354 @@ pub fn get_children_callback(element: &JSXElement<'_>) -> String {
     }
     "() => undefined".to_string()
 }
+
+/// The code of a render callback passed as an element's only child, as in
+/// `<Show when={user()}>{u => <p>{u.name}</p>}</Show>`.
+pub fn render_callback(element: &JSXElement<'_>) -> Option<String> {
+    let mut children = element.children.iter().filter(|child| match child {
+        JSXChild::Text(text) => !text.value.trim().is_empty(),
+        // `{/* comments */}` render nothing
+        JSXChild::ExpressionContainer(container) => container.expression.as_expression().is_some(),
+        _ => true,
+    });
+    let JSXChild::ExpressionContainer(container) = children.next()? else {
+        return None;
+    };
+    if children.next().is_some() {
+        return None;
+    }
+    match container.expression.as_expression()? {
+        expr @ (Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_)) => {
+            Some(expr_to_string(expr))
+        }
+        _ => None,
+    }
+}
+
+/// The classes a `classList` object literal turns on, as `cond ? "name" : ""`
+/// expressions.
+///
+/// Returns `None` unless every key is known at compile time.
+pub fn class_list_conditions(expr: &Expression<'_>) -> Option<Vec<String>> {
+    let Expression::ObjectExpression(obj) = expr else {
+        return None;
+    };
+    obj.properties
+        .iter()
+        .map(|prop| {
+            let ObjectPropertyKind::ObjectProperty(prop) = prop else {
+                return None;
+            };
+            if prop.computed {
+                return None;
+            }
+            let name = match &prop.key {
+                PropertyKey::StaticIdentifier(id) => id.name.as_str(),
+                PropertyKey::StringLiteral(lit) => lit.value.as_str(),
+                _ => return None,
+            };
+            Some(format!(
+                "{} ? \"{}\" : \"\"",
+                expr_to_string(&prop.value),
+                name.replace('\\', "\\\\").replace('"', "\\\"")
+            ))
+        })
+        .collect()
+}
+
+/// Try to convert a static object expression to a style string
+pub fn object_to_style_string(obj: &ObjectExpression) -> Option<String> {
+    let mut styles = Vec::new();
+
+    for prop in &obj.properties {
+        if let ObjectPropertyKind::ObjectProperty(prop) = prop {
+            // Get key
+            let key = match &prop.key {
+                PropertyKey::StaticIdentifier(id) => {
+                    // Convert camelCase to kebab-case
+                    camel_to_kebab(&id.name)
+                }
+                PropertyKey::StringLiteral(lit) => lit.value.to_string(),
+                _ => return None, // Dynamic key, can't inline
+            };
+
+            // Get value - must be a static literal
+            let value = match &prop.value {
+                Expression::StringLiteral(lit) => lit.value.to_string(),
+                Expression::NumericLiteral(num) => {
+                    // Add px for numeric values (except certain properties)
+                    let num_str = num.value.to_string();
+                    if !key.starts_with("--") && needs_px_suffix(&key) && num.value != 0.0 {
+                        format!("{}px", num_str)
+                    } else {
+                        num_str
+                    }
+                }
+                _ => return None, // Dynamic value, can't inline
+            };
+
+            styles.push(format!("{}: {}", key, value));
+        } else {
+            return None; // Spread or method, can't inline
+        }
+    }
+
+    Some(styles.join("; "))
+}
+
+/// Convert camelCase to kebab-case
+fn camel_to_kebab(s: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('-');
+            }
+            result.push(c.to_lowercase().next().unwrap());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Check if a CSS property needs px suffix for numeric values
+fn needs_px_suffix(prop: &str) -> bool {
+    // Properties that don't need px suffix
+    let unitless = [
+        "animation-iteration-count",
+        "border-image-outset",
+        "border-image-slice",
+        "border-image-width",
+        "box-flex",
+        "box-flex-group",
+        "box-ordinal-group",
+        "column-count",
+        "columns",
+        "flex",
+        "flex-grow",
+        "flex-positive",
+        "flex-shrink",
+        "flex-negative",
+        "flex-order",
+        "grid-row",
+        "grid-row-end",
+        "grid-row-span",
+        "grid-row-start",
+        "grid-column",
+        "grid-column-end",
+        "grid-column-span",
+        "grid-column-start",
+        "font-weight",
+        "line-clamp",
+        "line-height",
+        "opacity",
+        "order",
+        "orphans",
+        "tab-size",
+        "widows",
+        "z-index",
+        "zoom",
+        "fill-opacity",
+        "flood-opacity",
+        "stop-opacity",
+        "stroke-dasharray",
+        "stroke-dashoffset",
+        "stroke-miterlimit",
+        "stroke-opacity",
+        "stroke-width",
+    ];
+    !unitless.contains(&prop)
+}
+
+/// The text of an expression built only from string and number literals, so it
+/// can be inlined into a template: `{"foo" + "bar"}`, `{1 + 2}`, `` {`a${1}`} ``.
+///
+/// `None` when the value needs the runtime, including numbers JavaScript would
+/// print in exponent notation.
+pub fn constant_text(expr: &Expression<'_>) -> Option<String> {
+    evaluate(expr)?.into_text()
+}
+
+/// A value computed at compile time
+enum Constant {
+    String(String),
+    Number(f64),
+}
+
+impl Constant {
+    fn into_text(self) -> Option<String> {
+        match self {
+            Constant::String(value) => Some(value),
+            // -0 prints as "0"
+            Constant::Number(0.0) => Some("0".to_string()),
+            Constant::Number(value) if (1e-6..1e21).contains(&value.abs()) => {
+                Some(value.to_string())
+            }
+            Constant::Number(_) => None,
+        }
+    }
+}
+
+fn evaluate(expr: &Expression<'_>) -> Option<Constant> {
+    match expr {
+        Expression::StringLiteral(lit) => Some(Constant::String(lit.value.to_string())),
+        Expression::NumericLiteral(lit) => Some(Constant::Number(lit.value)),
+        Expression::ParenthesizedExpression(paren) => evaluate(&paren.expression),
+        Expression::UnaryExpression(unary) => {
+            let Constant::Number(value) = evaluate(&unary.argument)? else {
+                return None;
+            };
+            match unary.operator {
+                UnaryOperator::UnaryNegation => Some(Constant::Number(-value)),
+                UnaryOperator::UnaryPlus => Some(Constant::Number(value)),
+                _ => None,
+            }
+        }
+        Expression::TemplateLiteral(template) => {
+            let mut text = String::new();
+            for (i, quasi) in template.quasis.iter().enumerate() {
+                text.push_str(quasi.value.cooked.as_ref()?);
+                if let Some(expr) = template.expressions.get(i) {
+                    text.push_str(&evaluate(expr)?.into_text()?);
+                }
+            }
+            Some(Constant::String(text))
+        }
+        Expression::BinaryExpression(binary) => {
+            let left = evaluate(&binary.left)?;
+            let right = evaluate(&binary.right)?;
+            match (binary.operator, left, right) {
+                (BinaryOperator::Addition, Constant::Number(a), Constant::Number(b)) => {
+                    Some(Constant::Number(a + b))
+                }
+                (BinaryOperator::Addition, left, right) => {
+                    Some(Constant::String(left.into_text()? + &right.into_text()?))
+                }
+                (operator, Constant::Number(a), Constant::Number(b)) => {
+                    let value = match operator {
+                        BinaryOperator::Subtraction => a - b,
+                        BinaryOperator::Multiplication => a * b,
+                        BinaryOperator::Division => a / b,
+                        BinaryOperator::Remainder => a % b,
+                        _ => return None,
+                    };
+                    Some(Constant::Number(value))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A template literal joining static classes and class expressions with spaces,
+/// for setting `class`, `className` and `classList` of one element at once
+pub fn class_template_literal(static_classes: &str, dynamic: &[String]) -> String {
+    let mut parts: Vec<String> = vec![];
+    if !static_classes.is_empty() {
+        parts.push(
+            static_classes
+                .replace('\\', "\\\\")
+                .replace('`', "\\`")
+                .replace("${", "\\${"),
+        );
+    }
+    parts.extend(dynamic.iter().map(|expr| format!("${{{}}}", expr)));
+    format!("`{}`", parts.join(" "))
+}
+
+/// A conditional choosing between dynamic branches (usually JSX), with its test
+/// memoized so a branch is only recreated when the test flips:
+/// `memo(() => !!cond())() ? <A/> : <B/>`. `&&` is handled the same way, and so
+/// are conditionals nested in the alternate.
+///
+/// Returns `None` unless the test is dynamic too, in which case the expression
+/// is used as written. The caller registers the `memo` helper.
+pub fn memo_condition(expr: &Expression<'_>, marks: &StaticMarks) -> Option<String> {
+    let (test, rest) = condition_parts(expr, marks)?;
+    Some(format!("{}(){}", memo_test(test), rest))
+}
+
+/// [`memo_condition`] for a child `insert`, which re-runs its function: the test
+/// is memoized once, outside that function:
+/// `(() => { const _c$ = memo(() => !!cond()); return () => _c$() ? <A/> : <B/>; })()`
+///
+/// Registers the `memo` helper when the conditional is memoized.
+pub fn memo_condition_insert(
+    expr: &Expression<'_>,
+    context: &mut impl TransformContext,
+) -> Option<String> {
+    let (test, rest) = condition_parts(expr, &context.module().static_marks)?;
+    context.register_helper("memo");
+    let memo_name = context.generate_uid("c$");
+    Some(format!(
+        "(() => {{ const {} = {}; return () => {}(){}; }})()",
+        memo_name,
+        memo_test(test),
+        memo_name,
+        rest
+    ))
+}
+
+/// Split a memoizable conditional into its test and the code following it
+fn condition_parts<'e, 'a>(
+    expr: &'e Expression<'a>,
+    marks: &StaticMarks,
+) -> Option<(&'e Expression<'a>, String)> {
+    match expr {
+        Expression::ConditionalExpression(cond) => {
+            if !is_dynamic(&cond.test, marks)
+                || !(is_dynamic(&cond.consequent, marks) || is_dynamic(&cond.alternate, marks))
+            {
+                return None;
+            }
+            let alternate = memo_condition(&cond.alternate, marks)
+                .unwrap_or_else(|| branch_code(&cond.alternate));
+            Some((
+                &cond.test,
+                format!(" ? {} : {}", branch_code(&cond.consequent), alternate),
+            ))
+        }
+        Expression::LogicalExpression(logical)
+            if logical.operator == LogicalOperator::And
+                && is_dynamic(&logical.left, marks)
+                && is_dynamic(&logical.right, marks) =>
+        {
+            let right = match &logical.right {
+                Expression::ConditionalExpression(_)
+                | Expression::LogicalExpression(_)
+                | Expression::SequenceExpression(_)
+                | Expression::AssignmentExpression(_)
+                | Expression::ArrowFunctionExpression(_) => {
+                    format!("({})", expr_to_string(&logical.right))
+                }
+                right => expr_to_string(right),
+            };
+            Some((&logical.left, format!(" && {}", right)))
+        }
+        _ => None,
+    }
+}
+
+/// `memo(() => !!test)`; comparisons already produce a boolean
+fn memo_test(test: &Expression<'_>) -> String {
+    let test_code = expr_to_string(test);
+    match test {
+        Expression::BinaryExpression(_) => format!("_$memo(() => {})", test_code),
+        Expression::Identifier(_)
+        | Expression::CallExpression(_)
+        | Expression::StaticMemberExpression(_)
+        | Expression::ComputedMemberExpression(_)
+        | Expression::UnaryExpression(_) => format!("_$memo(() => !!{})", test_code),
+        _ => format!("_$memo(() => !!({}))", test_code),
+    }
+}
+
+/// A conditional branch, parenthesized where a bare comma would end it
+fn branch_code(expr: &Expression<'_>) -> String {
+    match expr {
+        Expression::SequenceExpression(_) => format!("({})", expr_to_string(expr)),
+        _ => expr_to_string(expr),
+    }
+}
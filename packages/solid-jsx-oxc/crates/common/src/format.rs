@@ -0,0 +1,125 @@
+//! Prettier-compatible spacing and quoting for generated code
+//!
+//! Downstream snapshot tests and checked-in generated files churn when a
+//! formatter rewrites the output. This pass applies Prettier's defaults to the
+//! printed code so running Prettier over it is a no-op for the parts it touches:
+//! string quotes and the space after `function` in anonymous functions.
+//! Line wrapping is left as printed.
+
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{Expression, Function, JSXAttributeValue, StringLiteral};
+use oxc_ast_visit::{walk, Visit};
+use oxc_parser::Parser;
+use oxc_span::{SourceType, Span};
+
+/// Reformat printed code the way Prettier would (with its default options).
+///
+/// Code that doesn't parse is returned unchanged.
+pub fn prettier_format(code: &str, source_type: SourceType) -> String {
+    let allocator = Allocator::default();
+    let parsed = Parser::new(&allocator, code, source_type).parse();
+    if !parsed.errors.is_empty() {
+        return code.to_string();
+    }
+
+    let mut edits = Edits {
+        code,
+        edits: vec![],
+    };
+    edits.visit_program(&parsed.program);
+
+    // Apply from the end so earlier offsets stay valid
+    let mut result = code.to_string();
+    edits.edits.sort_by_key(|(span, _)| span.start);
+    for (span, text) in edits.edits.into_iter().rev() {
+        result.replace_range(span.start as usize..span.end as usize, &text);
+    }
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Collects text replacements for the printed code
+struct Edits<'c> {
+    code: &'c str,
+    edits: Vec<(Span, String)>,
+}
+
+impl<'a> Visit<'a> for Edits<'_> {
+    fn visit_string_literal(&mut self, lit: &StringLiteral<'a>) {
+        let raw = lit.span.source_text(self.code);
+        let quoted = prettier_quote(raw);
+        if quoted != raw {
+            self.edits.push((lit.span, quoted));
+        }
+    }
+
+    // JSX attributes always use double quotes
+    fn visit_jsx_attribute_value(&mut self, value: &JSXAttributeValue<'a>) {
+        if !matches!(value, JSXAttributeValue::StringLiteral(_)) {
+            walk::walk_jsx_attribute_value(self, value);
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &Expression<'a>) {
+        if let Expression::FunctionExpression(func) = expr {
+            self.space_anonymous_function(func);
+        }
+        walk::walk_expression(self, expr);
+    }
+}
+
+impl Edits<'_> {
+    /// `function() {}` to `function () {}`. Object methods are function
+    /// expressions too, but don't start with the keyword.
+    fn space_anonymous_function(&mut self, func: &Function) {
+        if func.id.is_some() {
+            return;
+        }
+        let text = func.span.source_text(self.code);
+        let text = text.strip_prefix("async").map_or(text, str::trim_start);
+        let Some(rest) = text.strip_prefix("function") else {
+            return;
+        };
+        let rest = rest.strip_prefix('*').unwrap_or(rest);
+        if rest.starts_with('(') {
+            let offset = func.span.end - rest.len() as u32;
+            self.edits
+                .push((Span::new(offset, offset), " ".to_string()));
+        }
+    }
+}
+
+/// Requote a string literal the way Prettier does: double quotes, unless the
+/// string contains more double quotes than single quotes. Escapes of the quote
+/// that no longer needs one are dropped; other escapes are kept as written.
+fn prettier_quote(raw: &str) -> String {
+    let content = &raw[1..raw.len() - 1];
+    let doubles = content.matches('"').count();
+    let singles = content.matches('\'').count();
+    let quote = if doubles > singles { '\'' } else { '"' };
+
+    let mut result = String::with_capacity(raw.len());
+    result.push(quote);
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\'')) if escaped != quote => result.push(escaped),
+                Some(escaped) => {
+                    result.push('\\');
+                    result.push(escaped);
+                }
+                None => result.push('\\'),
+            },
+            c if c == quote => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push(quote);
+    result
+}
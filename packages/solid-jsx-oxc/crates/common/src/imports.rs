@@ -0,0 +1,140 @@
+//! Importing the runtime helpers the generated code calls
+
+use oxc_ast::ast::{
+    IdentifierReference, ImportDeclarationSpecifier, ImportOrExportKind, ModuleExportName, Program,
+    Statement,
+};
+use oxc_ast::AstBuilder;
+use oxc_ast_visit::VisitMut;
+use oxc_semantic::Scoping;
+use oxc_span::Span;
+
+/// Import `helpers` from `module_name` under the `_$name` aliases the generated
+/// code calls them by:
+///
+/// `import { template as _$template, insert as _$insert } from "solid-js/web";`
+///
+/// A file that already imports from the module gets the helpers added to that
+/// declaration instead of a second one. A helper it already imports is called
+/// by the local name it has there, unless the file declares that name again
+/// somewhere it could shadow the import.
+pub fn import_helpers<'a, 'h>(
+    program: &mut Program<'a>,
+    helpers: impl IntoIterator<Item = &'h String>,
+    module_name: &str,
+    scoping: &Scoping,
+    ast: AstBuilder<'a>,
+) {
+    let span = Span::default();
+    let existing = program
+        .body
+        .iter()
+        .position(|stmt| is_named_import_from(stmt, module_name));
+
+    let mut renames: Vec<(String, String)> = vec![];
+    let mut specifiers = ast.vec();
+    for helper in helpers {
+        let alias = format!("_${}", helper);
+        let local = existing.and_then(|index| imported_local(&program.body[index], helper));
+        match local {
+            Some(local) if local == alias => {}
+            Some(local) if is_declared_once(scoping, &local) => renames.push((alias, local)),
+            _ => {
+                let helper = ast.allocator.alloc_str(helper);
+                let imported = ModuleExportName::IdentifierName(ast.identifier_name(span, helper));
+                let local = ast.binding_identifier(span, ast.atom(&alias));
+                let specifier =
+                    ast.import_specifier(span, imported, local, ImportOrExportKind::Value);
+                specifiers.push(ImportDeclarationSpecifier::ImportSpecifier(
+                    ast.alloc(specifier),
+                ));
+            }
+        }
+    }
+
+    if !specifiers.is_empty() {
+        match existing.map(|index| &mut program.body[index]) {
+            Some(Statement::ImportDeclaration(import)) => {
+                let existing = import.specifiers.get_or_insert_with(|| ast.vec());
+                for specifier in specifiers {
+                    existing.push(specifier);
+                }
+            }
+            _ => {
+                let import = ast.import_declaration(
+                    span,
+                    Some(specifiers),
+                    ast.string_literal(span, ast.atom(module_name), None),
+                    None,
+                    None::<oxc_ast::ast::WithClause<'a>>,
+                    ImportOrExportKind::Value,
+                );
+                program
+                    .body
+                    .insert(0, Statement::ImportDeclaration(ast.alloc(import)));
+            }
+        }
+    }
+
+    if !renames.is_empty() {
+        HelperNames { ast, renames }.visit_program(program);
+    }
+}
+
+/// Whether a statement imports named bindings from `module_name`, so more can be
+/// added to it. `import * as web` and `import type` declarations can't take them.
+fn is_named_import_from(stmt: &Statement, module_name: &str) -> bool {
+    let Statement::ImportDeclaration(import) = stmt else {
+        return false;
+    };
+    import.source.value == module_name
+        && import.import_kind.is_value()
+        && import.specifiers.as_ref().is_some_and(|specifiers| {
+            !specifiers.iter().any(|specifier| {
+                matches!(
+                    specifier,
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(_)
+                )
+            })
+        })
+}
+
+/// The local name an import declaration binds `helper` to
+fn imported_local(stmt: &Statement, helper: &str) -> Option<String> {
+    let Statement::ImportDeclaration(import) = stmt else {
+        return None;
+    };
+    import.specifiers.as_ref()?.iter().find_map(|specifier| {
+        let ImportDeclarationSpecifier::ImportSpecifier(specifier) = specifier else {
+            return None;
+        };
+        (specifier.import_kind.is_value() && specifier.imported.name().as_str() == helper)
+            .then(|| specifier.local.name.to_string())
+    })
+}
+
+/// Whether `name` is declared once in the whole file, so every use of it is the
+/// same binding
+fn is_declared_once(scoping: &Scoping, name: &str) -> bool {
+    scoping
+        .symbol_ids()
+        .filter(|&symbol| scoping.symbol_name(symbol) == name)
+        .count()
+        == 1
+}
+
+/// Calls helpers by the names the file already imports them as
+struct HelperNames<'a> {
+    ast: AstBuilder<'a>,
+    /// `_$name` aliases and the local names replacing them
+    renames: Vec<(String, String)>,
+}
+
+impl<'a> VisitMut<'a> for HelperNames<'a> {
+    fn visit_identifier_reference(&mut self, ident: &mut IdentifierReference<'a>) {
+        let name = ident.name.as_str();
+        if let Some((_, local)) = self.renames.iter().find(|(alias, _)| alias == name) {
+            ident.name = self.ast.atom(local);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Minimal JSON output for the IR dump
+//!
+//! The dump is written by hand rather than through a serialization framework, so
+//! the crates stay free of extra dependencies for a debugging aid.
+
+/// The intermediate representation of a file, dumped for debugging
+pub struct IrDump {
+    /// Every JSX root and the file-level state, as JSON
+    pub json: String,
+    /// The templates the output creates, in order: the HTML of each DOM template,
+    /// or the static text of each SSR template with `${}` where values go
+    pub templates: Vec<String>,
+}
+
+/// A JSON string literal
+pub fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// A JSON array of encoded values
+pub fn json_array(values: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+/// A JSON object of encoded values, keyed in the given order
+pub fn json_object<'k>(fields: impl IntoIterator<Item = (&'k str, String)>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
@@ -1,15 +1,48 @@
+#![forbid(unsafe_code)]
+
 pub mod check;
+pub mod compat;
 pub mod constants;
+pub mod context;
+pub mod define;
+pub mod diagnostic;
 pub mod expression;
+pub mod format;
+pub mod imports;
+pub mod json;
+pub mod lower;
 pub mod options;
+pub mod pragma;
+pub mod props;
+pub mod runtime;
+pub mod stats;
+pub mod summary;
 
 pub use check::{
-    find_prop, find_prop_value, get_attr_name, get_attr_value, get_tag_name, is_built_in,
-    is_component, is_dynamic, is_namespaced_attr, is_svg_element,
+    child_property_conflict, find_child_property, find_compiled_marker, find_prop, find_prop_value,
+    get_attr_name, get_attr_value, get_tag_name, is_built_in, is_class_key, is_component,
+    is_constant_children, is_dynamic, is_enumerated_attr, is_event_attr, is_literal,
+    is_namespaced_attr, is_static_element, is_static_markup, is_static_value, is_svg_element,
+    may_be_nullish, nesting_diagnostics, preserves_whitespace,
 };
+pub use compat::babel_compat;
 pub use constants::*;
+pub use context::{ModuleState, TransformContext};
+pub use define::{expression_child, fold_jsx_branches, fold_show, substitute_constants};
+pub use diagnostic::{Diagnostic, Severity};
 pub use expression::{
-    escape_html, expr_to_string, get_children_callback, stmt_to_string, to_event_name,
+    class_list_conditions, class_template_literal, constant_text, decode_entities, escape_html,
+    expr_to_string, get_children_callback, memo_condition, memo_condition_insert,
+    object_to_style_string, render_callback, stmt_to_string, to_event_name, trim_around_blocks,
     trim_whitespace,
 };
+pub use format::prettier_format;
+pub use imports::import_helpers;
+pub use json::{json_array, json_object, json_string, IrDump};
+pub use lower::lower_to_es5;
 pub use options::*;
+pub use pragma::{remove_jsx_comments, JsxIgnore, Pragmas, StaticMarks};
+pub use props::{attribute_value_code, child_values, wrap_element_value, ComponentProps};
+pub use runtime::{check_runtime_exports, minimum_version, required_helpers, RuntimeHelper};
+pub use stats::{jsx_stats, FileStats};
+pub use summary::jsx_summary;
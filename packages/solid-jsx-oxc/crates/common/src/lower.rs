@@ -0,0 +1,296 @@
+//! Syntax lowering for older output targets
+//!
+//! The transforms emit arrow functions, `const` declarations, template literals and
+//! object methods.
+//! Consumers that ship the output without a downlevel step can ask for ES5 syntax
+//! instead (see [`OutputTarget`](crate::OutputTarget)).
+
+use oxc_allocator::{CloneIn, TakeIn, Vec as ArenaVec};
+use oxc_ast::ast::{
+    Argument, ArrowFunctionExpression, Expression, FunctionBody, FunctionType, IdentifierReference,
+    MetaProperty, ObjectProperty, Program, PropertyKind, Statement, Super,
+    TaggedTemplateExpression, TemplateElement, TemplateLiteral, ThisExpression,
+    VariableDeclaration, VariableDeclarationKind,
+};
+use oxc_ast::{AstBuilder, NONE};
+use oxc_ast_visit::{walk, walk_mut, Visit, VisitMut};
+use oxc_parser::Parser;
+use oxc_span::{Atom, SourceType, Span};
+
+use crate::diagnostic::Diagnostic;
+use crate::expression::expr_to_string;
+
+/// Rewrite a transformed program to ES5 syntax.
+///
+/// - arrow functions become function expressions
+/// - `let`/`const` directly in a program or function body become `var`
+/// - template literals become `"".concat(...)` calls, tagged ones `tag([...], ...)`
+/// - methods and shorthand properties become `key: function () {}` and `key: key`
+///
+/// Arrow functions that use `this` or `arguments` are kept and reported, and so
+/// are block-scoped declarations inside nested blocks, as `var` would change
+/// their scope. Module syntax is left for the bundler.
+pub fn lower_to_es5<'a>(program: &mut Program<'a>, ast: AstBuilder<'a>) -> Vec<Diagnostic> {
+    let mut lowering = Es5Lowering {
+        ast,
+        diagnostics: vec![],
+    };
+    lowering.visit_program(program);
+    lowering.diagnostics
+}
+
+struct Es5Lowering<'a> {
+    ast: AstBuilder<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> VisitMut<'a> for Es5Lowering<'a> {
+    fn visit_program(&mut self, program: &mut Program<'a>) {
+        declare_with_var(&mut program.body);
+        walk_mut::walk_program(self, program);
+    }
+
+    fn visit_function_body(&mut self, body: &mut FunctionBody<'a>) {
+        declare_with_var(&mut body.statements);
+        walk_mut::walk_function_body(self, body);
+    }
+
+    // Declarations of program and function bodies are `var` by now, so any
+    // `let` or `const` left is in a nested block
+    fn visit_variable_declaration(&mut self, decl: &mut VariableDeclaration<'a>) {
+        let kind = match decl.kind {
+            VariableDeclarationKind::Let => Some("let"),
+            VariableDeclarationKind::Const => Some("const"),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.diagnostics.push(Diagnostic::warning(
+                format!("`{}` in a nested block can't be lowered to ES5", kind),
+                decl.span,
+            ));
+        }
+        walk_mut::walk_variable_declaration(self, decl);
+    }
+
+    fn visit_object_property(&mut self, prop: &mut ObjectProperty<'a>) {
+        walk_mut::walk_object_property(self, prop);
+        // The value of a method is a function expression already
+        if prop.kind == PropertyKind::Init {
+            prop.method = false;
+            prop.shorthand = false;
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &mut Expression<'a>) {
+        // Lower nested expressions first, so each node is rebuilt from lowered parts
+        walk_mut::walk_expression(self, expr);
+
+        if let Expression::ArrowFunctionExpression(arrow) = expr {
+            let uses = lexical_this(arrow);
+            // Arrows around a kept arrow are kept too; report only the innermost
+            if uses.direct {
+                self.diagnostics.push(Diagnostic::warning(
+                    "arrow function using `this` or `arguments` can't be lowered to ES5",
+                    arrow.span,
+                ));
+            }
+            if uses.found {
+                return;
+            }
+        }
+
+        *expr = match expr.take_in(self.ast) {
+            Expression::ArrowFunctionExpression(arrow) => self.function_from_arrow(arrow.unbox()),
+            Expression::TemplateLiteral(literal) => self.concat_from_template(literal.unbox()),
+            Expression::TaggedTemplateExpression(tagged) => self.call_from_tagged(tagged.unbox()),
+            other => other,
+        };
+    }
+}
+
+impl<'a> Es5Lowering<'a> {
+    /// `(a) => a + 1` to `function (a) { return a + 1; }`
+    fn function_from_arrow(&self, arrow: ArrowFunctionExpression<'a>) -> Expression<'a> {
+        let mut body = arrow.body;
+        if arrow.expression {
+            if let Some(Statement::ExpressionStatement(stmt)) = body.statements.pop() {
+                let stmt = stmt.unbox();
+                let ret = self.ast.statement_return(stmt.span, Some(stmt.expression));
+                body.statements.push(ret);
+            }
+        }
+        self.ast.expression_function(
+            arrow.span,
+            FunctionType::FunctionExpression,
+            None,
+            false,
+            arrow.r#async,
+            false,
+            NONE,
+            NONE,
+            arrow.params,
+            NONE,
+            Some(body),
+        )
+    }
+
+    /// `` `a${b}c` `` to `"a".concat(b, "c")`, which converts `b` with `ToString`
+    /// just like the template did
+    fn concat_from_template(&self, literal: TemplateLiteral<'a>) -> Expression<'a> {
+        let mut quasis = literal.quasis.iter().map(cooked);
+        let head = quasis.next().unwrap_or(Atom::from(""));
+        if literal.expressions.is_empty() {
+            return self.ast.expression_string_literal(literal.span, head, None);
+        }
+
+        let callee = self.parse_expression(&format!("{}.concat", self.string_code(head)));
+        let mut args = self.ast.vec();
+        for (expr, quasi) in literal.expressions.into_iter().zip(quasis) {
+            args.push(Argument::from(expr));
+            if !quasi.is_empty() {
+                let text = self
+                    .ast
+                    .expression_string_literal(Span::default(), quasi, None);
+                args.push(Argument::from(text));
+            }
+        }
+        self.ast
+            .expression_call(literal.span, callee, NONE, args, false)
+    }
+
+    /// `` tag`a${b}c` `` to `tag(["a", "c"], b)`
+    fn call_from_tagged(&self, tagged: TaggedTemplateExpression<'a>) -> Expression<'a> {
+        let strings: Vec<String> = tagged
+            .quasi
+            .quasis
+            .iter()
+            .map(|quasi| self.string_code(cooked(quasi)))
+            .collect();
+        let strings = self.parse_expression(&format!("[{}]", strings.join(", ")));
+
+        let mut args = self.ast.vec();
+        args.push(Argument::from(strings));
+        for expr in tagged.quasi.expressions {
+            args.push(Argument::from(expr));
+        }
+        self.ast
+            .expression_call(tagged.span, tagged.tag, NONE, args, false)
+    }
+
+    /// A JavaScript string literal for `value`
+    fn string_code(&self, value: Atom<'a>) -> String {
+        expr_to_string(
+            &self
+                .ast
+                .expression_string_literal(Span::default(), value, None),
+        )
+    }
+
+    /// Parse generated code into an expression
+    fn parse_expression(&self, code: &str) -> Expression<'a> {
+        let allocator = self.ast.allocator;
+        let code = allocator.alloc_str(code);
+        let program = Parser::new(allocator, code, SourceType::mjs())
+            .parse()
+            .program;
+        match program.body.first() {
+            Some(Statement::ExpressionStatement(stmt)) => stmt.expression.clone_in(allocator),
+            _ => self.ast.expression_identifier(Span::default(), code),
+        }
+    }
+}
+
+/// The text of a template chunk, falling back to the raw text where the cooked
+/// value is undefined (invalid escapes in tagged templates)
+fn cooked<'a>(quasi: &TemplateElement<'a>) -> Atom<'a> {
+    quasi.value.cooked.unwrap_or(quasi.value.raw)
+}
+
+/// Declare `let`/`const` bindings with `var`.
+///
+/// Only used on program and function bodies, where `var` has the same scope.
+fn declare_with_var(statements: &mut ArenaVec<'_, Statement<'_>>) {
+    for stmt in statements.iter_mut() {
+        if let Statement::VariableDeclaration(decl) = stmt {
+            if matches!(
+                decl.kind,
+                VariableDeclarationKind::Let | VariableDeclarationKind::Const
+            ) {
+                decl.kind = VariableDeclarationKind::Var;
+            }
+        }
+    }
+}
+
+/// Find uses of the parent's `this`, `arguments`, `super` or `new.target` in an
+/// arrow function
+fn lexical_this(arrow: &ArrowFunctionExpression) -> LexicalThis {
+    let mut finder = LexicalThis::default();
+    finder.visit_formal_parameters(&arrow.params);
+    finder.visit_function_body(&arrow.body);
+    finder
+}
+
+#[derive(Default)]
+struct LexicalThis {
+    /// Used anywhere in the arrow, including nested arrows
+    found: bool,
+    /// Used outside nested arrows
+    direct: bool,
+    /// Depth of nested arrows being visited
+    nested: usize,
+}
+
+impl LexicalThis {
+    fn record(&mut self) {
+        self.found = true;
+        self.direct |= self.nested == 0;
+    }
+}
+
+impl<'a> Visit<'a> for LexicalThis {
+    fn visit_this_expression(&mut self, _: &ThisExpression) {
+        self.record();
+    }
+
+    fn visit_super(&mut self, _: &Super) {
+        self.record();
+    }
+
+    fn visit_meta_property(&mut self, meta: &MetaProperty<'a>) {
+        if meta.meta.name == "new" {
+            self.record();
+        }
+    }
+
+    fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+        if ident.name == "arguments" {
+            self.record();
+        }
+    }
+
+    fn visit_arrow_function_expression(&mut self, arrow: &ArrowFunctionExpression<'a>) {
+        self.nested += 1;
+        walk::walk_arrow_function_expression(self, arrow);
+        self.nested -= 1;
+    }
+
+    // Functions and classes bind their own `this`
+    fn visit_expression(&mut self, expr: &Expression<'a>) {
+        if !matches!(
+            expr,
+            Expression::FunctionExpression(_) | Expression::ClassExpression(_)
+        ) {
+            walk::walk_expression(self, expr);
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement<'a>) {
+        if !matches!(
+            stmt,
+            Statement::FunctionDeclaration(_) | Statement::ClassDeclaration(_)
+        ) {
+            walk::walk_statement(self, stmt);
+        }
+    }
+}
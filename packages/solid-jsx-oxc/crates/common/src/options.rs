@@ -1,8 +1,9 @@
 //! Transform options for the Solid JSX compiler
 
 use oxc_span::SourceType;
-use std::cell::RefCell;
-use std::collections::HashSet;
+use phf::Set;
+
+use crate::constants::{BOOLEAN_ATTRIBUTES, DELEGATED_EVENTS, SVG_ELEMENTS, VOID_ELEMENTS};
 
 /// Configuration options for the JSX transform
 #[derive(Default)]
@@ -10,6 +11,9 @@ pub struct TransformOptions<'a> {
     /// The module to import runtime helpers from
     pub module_name: &'a str,
 
+    /// Exports available from `module_name`; when set, importing anything else is reported
+    pub runtime_exports: Option<Vec<&'a str>>,
+
     /// Generate mode: "dom", "ssr", or "universal"
     pub generate: GenerateMode,
 
@@ -22,9 +26,21 @@ pub struct TransformOptions<'a> {
     /// Custom delegated events
     pub delegated_events: Vec<&'a str>,
 
+    /// Whether `delegated_events` replaces the built-in table instead of extending it
+    pub replace_delegated_events: bool,
+
     /// Whether to wrap conditionals
     pub wrap_conditionals: bool,
 
+    /// Compile-time constants: identifiers or member chains mapped to replacement source
+    /// (e.g. `("__DEV__", "false")`, `("import.meta.env.SSR", "true")`)
+    pub define: Vec<(&'a str, &'a str)>,
+
+    /// Modules to import `use:` directives from, by directive name
+    /// (e.g. `("tooltip", "my-directives")`). A directive is only imported when
+    /// the file doesn't declare it at the top level
+    pub directive_imports: Vec<(&'a str, &'a str)>,
+
     /// Whether to pass context to custom elements
     pub context_to_custom_elements: bool,
 
@@ -43,23 +59,77 @@ pub struct TransformOptions<'a> {
     /// Source type (tsx, jsx, etc.)
     pub source_type: SourceType,
 
+    /// Parse with JSX enabled whatever the filename's extension (e.g. JSX in `.js`/`.ts` files)
+    pub jsx: bool,
+
     /// Whether to generate source maps
     pub source_map: bool,
 
+    /// Whether to dump the intermediate representation of every JSX root as JSON
+    /// alongside the code, for debugging why a JSX shape compiled the way it did
+    pub emit_ir: bool,
+
+    /// Syntax level of the generated code
+    pub target: OutputTarget,
+
+    /// The Solid major version the output runs against
+    pub runtime: RuntimeVersion,
+
+    /// Whose output to match, for diffing builds while migrating
+    pub compat: Compat,
+
+    /// Quote strings and space functions the way Prettier does, so formatting the
+    /// output doesn't churn. Source map columns aren't adjusted for the edits
+    pub prettier: bool,
+
     /// Static marker comment
     pub static_marker: &'a str,
 
-    /// Collected templates
-    pub templates: RefCell<Vec<(String, bool)>>,
+    /// Whether SSR reports event handlers that only attach after hydration
+    pub ssr_event_diagnostics: bool,
+
+    /// Whether SSR wraps each component's output in `<!--c:Name:id-->` boundary comments
+    pub component_boundaries: bool,
+
+    /// Whether SSR compiles JSX that renders the same markup every time to a plain
+    /// string, without hydration keys, and exports the markup of exported
+    /// components that return only such JSX as `<Name>Html` constants
+    pub static_html: bool,
+
+    /// Whether templates drop whitespace next to block-level tags. DOM and SSR
+    /// output apply the same rules, so hydrated text nodes still line up.
+    pub minify_whitespace: bool,
+
+    /// Whether component props made only of literals are hoisted to module-level
+    /// constants shared by every render, along with constant object and array
+    /// props. Components that mutate their props object or compare it by
+    /// identity will see the same object each time
+    pub hoist_static_props: bool,
+
+    /// Whether templates also drop the closing tag of a last child inside an element
+    /// that keeps its own, where the HTML parser infers it. Needs `omit_last_closing_tag`
+    pub omit_nested_closing_tags: bool,
+
+    /// Whether templates drop the closing tags at their end
+    pub omit_last_closing_tag: bool,
 
-    /// Collected helper imports
-    pub helpers: RefCell<HashSet<String>>,
+    /// Whether template attribute values go unquoted when HTML allows it
+    pub omit_quotes: bool,
 
-    /// Collected delegated events
-    pub delegates: RefCell<HashSet<String>>,
+    /// Whether to warn about HTML nesting the browser's parser would rearrange
+    pub validate: bool,
+
+    /// Extra void elements; a `!`-prefixed entry removes a built-in one
+    pub void_elements: Vec<&'a str>,
+
+    /// Extra SVG elements; a `!`-prefixed entry removes a built-in one
+    pub svg_elements: Vec<&'a str>,
+
+    /// Extra boolean attributes; a `!`-prefixed entry removes a built-in one
+    pub boolean_attributes: Vec<&'a str>,
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GenerateMode {
     #[default]
     Dom,
@@ -67,15 +137,107 @@ pub enum GenerateMode {
     Universal,
 }
 
+impl GenerateMode {
+    /// Parse a generate mode name ("dom", "ssr" or "universal")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dom" => Some(Self::Dom),
+            "ssr" => Some(Self::Ssr),
+            "universal" => Some(Self::Universal),
+            _ => None,
+        }
+    }
+    /// The mode's name, as accepted by [`GenerateMode::parse`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dom => "dom",
+            Self::Ssr => "ssr",
+            Self::Universal => "universal",
+        }
+    }
+}
+
+/// Syntax level of the generated code
+///
+/// Generated code never uses optional chaining, and object getters are ES5, so
+/// both targets keep the `get children()` props of components.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// Arrow functions, `const` and template literals
+    #[default]
+    Es2015,
+    /// Function expressions, `var` and string concatenation, for consumers that
+    /// can't run a downlevel step after the transform
+    Es5,
+}
+
+impl OutputTarget {
+    /// Parse a target name ("es2015" or "es5")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "es2015" | "es6" => Some(Self::Es2015),
+            "es5" => Some(Self::Es5),
+            _ => None,
+        }
+    }
+}
+
+/// The Solid major version the output targets
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeVersion {
+    #[default]
+    Solid1,
+    /// Opt-in preview of the next runtime: effects take separate compute and
+    /// apply functions, and `mergeProps` is `merge`
+    Solid2,
+}
+
+impl RuntimeVersion {
+    /// Parse a runtime name ("solid-1" or "solid-2")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "solid-1" => Some(Self::Solid1),
+            "solid-2" => Some(Self::Solid2),
+            _ => None,
+        }
+    }
+}
+
+/// Whose output the generated code matches
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compat {
+    #[default]
+    None,
+    /// babel-plugin-jsx-dom-expressions: `_$`-aliased helper imports, Babel's
+    /// `_el$`, `_el$2` naming and `/*#__PURE__*/` templates, so old and new build
+    /// output only differ in whitespace
+    Babel,
+}
+
+impl Compat {
+    /// Parse a compat mode name ("none" or "babel")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "babel" => Some(Self::Babel),
+            _ => None,
+        }
+    }
+}
+
 impl<'a> TransformOptions<'a> {
     pub fn solid_defaults() -> Self {
         Self {
             module_name: "solid-js/web",
+            runtime_exports: None,
             generate: GenerateMode::Dom,
             hydratable: false,
             delegate_events: true,
             delegated_events: vec![],
+            replace_delegated_events: false,
             wrap_conditionals: true,
+            define: vec![],
+            directive_imports: vec![],
             context_to_custom_elements: true,
             built_ins: vec![
                 "For",
@@ -93,29 +255,114 @@ impl<'a> TransformOptions<'a> {
             memo_wrapper: "memo",
             filename: "input.jsx",
             source_type: SourceType::tsx(),
+            jsx: false,
             source_map: false,
+            emit_ir: false,
+            target: OutputTarget::Es2015,
+            runtime: RuntimeVersion::Solid1,
+            compat: Compat::None,
+            prettier: false,
             static_marker: "@once",
-            templates: RefCell::new(vec![]),
-            helpers: RefCell::new(HashSet::new()),
-            delegates: RefCell::new(HashSet::new()),
+            ssr_event_diagnostics: false,
+            component_boundaries: false,
+            static_html: false,
+            minify_whitespace: false,
+            hoist_static_props: false,
+            omit_nested_closing_tags: false,
+            omit_last_closing_tag: false,
+            omit_quotes: false,
+            validate: false,
+            void_elements: vec![],
+            svg_elements: vec![],
+            boolean_attributes: vec![],
         }
     }
 
-    /// Register a helper import
-    pub fn register_helper(&self, name: &str) {
-        self.helpers.borrow_mut().insert(name.to_string());
+    /// The helper that merges props objects
+    pub fn merge_props_helper(&self) -> &'static str {
+        match self.runtime {
+            RuntimeVersion::Solid1 => "mergeProps",
+            RuntimeVersion::Solid2 => "merge",
+        }
+    }
+
+    /// An effect re-running `apply` whenever `value` changes. Solid 2 tracks
+    /// the value in its own function and passes it to an untracked apply step.
+    pub fn effect_code(&self, value: &str, apply: impl FnOnce(&str) -> String) -> String {
+        match self.runtime {
+            RuntimeVersion::Solid1 => format!("_$effect(() => {})", apply(value)),
+            RuntimeVersion::Solid2 => {
+                // An object literal body would parse as a block
+                let value = if value.starts_with('{') {
+                    format!("({})", value)
+                } else {
+                    value.to_string()
+                };
+                format!("_$effect(() => {}, _v$ => {})", value, apply("_v$"))
+            }
+        }
+    }
+
+    /// Like [`effect_code`](Self::effect_code), with `apply` also given the value
+    /// it returned on the previous run (for helpers that diff, like `style`)
+    pub fn effect_prev_code(
+        &self,
+        value: &str,
+        apply: impl FnOnce(&str, &str) -> String,
+    ) -> String {
+        match self.runtime {
+            RuntimeVersion::Solid1 => format!("_$effect(_p$ => {})", apply(value, "_p$")),
+            RuntimeVersion::Solid2 => {
+                let value = if value.starts_with('{') {
+                    format!("({})", value)
+                } else {
+                    value.to_string()
+                };
+                format!(
+                    "_$effect(() => {}, (_v$, _p$) => {})",
+                    value,
+                    apply("_v$", "_p$")
+                )
+            }
+        }
+    }
+
+    /// Check if an event is delegated (when delegation is enabled at all)
+    pub fn is_delegated_event(&self, event: &str) -> bool {
+        if self.replace_delegated_events {
+            return self.delegated_events.contains(&event);
+        }
+        DELEGATED_EVENTS.contains(event) || self.delegated_events.contains(&event)
+    }
+
+    /// The module configured to import a `use:` directive from
+    pub fn directive_module(&self, name: &str) -> Option<&'a str> {
+        self.directive_imports
+            .iter()
+            .find(|(directive, _)| *directive == name)
+            .map(|(_, module)| *module)
     }
 
-    /// Register an event for delegation
-    pub fn register_delegate(&self, event: &str) {
-        self.delegates.borrow_mut().insert(event.to_string());
+    /// Check if a tag is a void element (no children, no closing tag)
+    pub fn is_void_element(&self, tag: &str) -> bool {
+        in_table(&VOID_ELEMENTS, &self.void_elements, tag)
     }
 
-    /// Push a template and return its index
-    pub fn push_template(&self, template: String, is_svg: bool) -> usize {
-        let mut templates = self.templates.borrow_mut();
-        let index = templates.len();
-        templates.push((template, is_svg));
-        index
+    /// Check if a tag is an SVG element
+    pub fn is_svg_element(&self, tag: &str) -> bool {
+        in_table(&SVG_ELEMENTS, &self.svg_elements, tag)
     }
+
+    /// Check if an attribute is a boolean attribute
+    pub fn is_boolean_attribute(&self, name: &str) -> bool {
+        in_table(&BOOLEAN_ATTRIBUTES, &self.boolean_attributes, name)
+    }
+}
+
+/// Look `name` up in a built-in table, applying the user's additions and `!` removals
+fn in_table(table: &Set<&'static str>, extra: &[&str], name: &str) -> bool {
+    let removed = extra
+        .iter()
+        .any(|entry| entry.strip_prefix('!') == Some(name));
+    !removed && (table.contains(name) || extra.contains(&name))
 }
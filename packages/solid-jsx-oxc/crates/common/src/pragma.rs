@@ -0,0 +1,179 @@
+//! Per-file pragma comments
+//!
+//! Pragmas let a single file opt out of (or reconfigure) the transform, which
+//! matters when one pipeline processes files written for different JSX runtimes.
+
+use oxc_ast::ast::{JSXEmptyExpression, Program};
+use oxc_ast_visit::Visit;
+use oxc_span::Span;
+
+use crate::options::GenerateMode;
+
+/// Pragmas found in a file's comments
+#[derive(Default, Debug)]
+pub struct Pragmas<'s> {
+    /// `@jsxImportSource <module>`
+    pub jsx_import_source: Option<&'s str>,
+
+    /// `@jsxRuntime <classic|automatic>`
+    pub jsx_runtime: Option<&'s str>,
+
+    /// `@solid-generate <dom|ssr|universal>`, overriding the `generate` option
+    pub generate: Option<GenerateMode>,
+}
+
+impl<'s> Pragmas<'s> {
+    /// Collect pragmas from the raw text of a file's comments (delimiters included)
+    pub fn from_comments(comments: impl IntoIterator<Item = &'s str>) -> Self {
+        let mut pragmas = Self::default();
+        for comment in comments {
+            let text = comment
+                .trim_start_matches("//")
+                .trim_start_matches("/*")
+                .trim_end_matches("*/");
+            if let Some(value) = pragma_value(text, "@jsxImportSource") {
+                pragmas.jsx_import_source = Some(value);
+            }
+            if let Some(value) = pragma_value(text, "@jsxRuntime") {
+                pragmas.jsx_runtime = Some(value);
+            }
+            if let Some(value) = pragma_value(text, "@solid-generate") {
+                pragmas.generate = GenerateMode::parse(value);
+            }
+        }
+        pragmas
+    }
+
+    /// Whether the file targets another JSX runtime and must be left untouched
+    pub fn skips_transform(&self, module_name: &str) -> bool {
+        if self.jsx_runtime == Some("classic") {
+            return true;
+        }
+        self.jsx_import_source
+            .is_some_and(|source| !is_solid_import_source(source, module_name))
+    }
+}
+
+/// Check if a `@jsxImportSource` module belongs to Solid (or the configured runtime)
+fn is_solid_import_source(source: &str, module_name: &str) -> bool {
+    source == "solid-js"
+        || source.starts_with("solid-js/")
+        || source == module_name
+        || module_name.starts_with(&format!("{}/", source))
+}
+
+/// JSX subtrees marked with a leading `@jsx-ignore` comment, left untransformed
+#[derive(Default)]
+pub struct JsxIgnore {
+    /// Start offsets of the nodes the marker comments are attached to
+    targets: Vec<u32>,
+    /// Spans of the marked JSX nodes seen so far
+    roots: Vec<Span>,
+}
+
+impl JsxIgnore {
+    /// Find the nodes `@jsx-ignore` comments are attached to
+    pub fn from_program(program: &Program) -> Self {
+        let targets = program
+            .comments
+            .iter()
+            .filter(|comment| comment.is_leading())
+            .filter(|comment| {
+                comment
+                    .span
+                    .source_text(program.source_text)
+                    .contains("@jsx-ignore")
+            })
+            .map(|comment| comment.attached_to)
+            .collect();
+        Self {
+            targets,
+            roots: vec![],
+        }
+    }
+
+    /// Note a JSX node on the way down, so its subtree can be skipped on the way up
+    pub fn enter(&mut self, span: Span) {
+        if self.targets.contains(&span.start) {
+            self.roots.push(span);
+        }
+    }
+
+    /// Whether a JSX node lies inside a marked subtree
+    pub fn contains(&self, span: Span) -> bool {
+        self.roots
+            .iter()
+            .any(|root| root.start <= span.start && span.end <= root.end)
+    }
+}
+
+/// Expressions with a leading static marker comment, `/*@once*/` by default.
+/// They are evaluated once rather than tracked, so `is_dynamic` reports them as
+/// static.
+#[derive(Default)]
+pub struct StaticMarks {
+    /// Start offsets of the marked expressions
+    starts: Vec<u32>,
+}
+
+impl StaticMarks {
+    /// Find the expressions `marker` comments are attached to
+    pub fn from_program(program: &Program, marker: &str) -> Self {
+        let starts = program
+            .comments
+            .iter()
+            .filter(|comment| comment.is_leading())
+            .filter(|comment| {
+                let text = comment.span.source_text(program.source_text);
+                let text = text
+                    .trim_start_matches("//")
+                    .trim_start_matches("/*")
+                    .trim_end_matches("*/");
+                text.trim() == marker
+            })
+            .map(|comment| comment.attached_to)
+            .collect();
+        Self { starts }
+    }
+
+    /// Whether the node starting at `span` is marked static
+    pub fn contains(&self, span: Span) -> bool {
+        self.starts.contains(&span.start)
+    }
+}
+
+/// Drop the comments of `{/* ... */}` JSX children. The containers compile to
+/// nothing, so their comments would land inside whatever code replaces the JSX.
+pub fn remove_jsx_comments(program: &mut Program) {
+    #[derive(Default)]
+    struct EmptyExpressions(Vec<Span>);
+
+    impl<'a> Visit<'a> for EmptyExpressions {
+        fn visit_jsx_empty_expression(&mut self, expr: &JSXEmptyExpression) {
+            self.0.push(expr.span);
+        }
+    }
+
+    let mut empty = EmptyExpressions::default();
+    empty.visit_program(program);
+    if empty.0.is_empty() {
+        return;
+    }
+    program.comments.retain(|comment| {
+        !empty
+            .0
+            .iter()
+            .any(|span| span.start <= comment.span.start && comment.span.end <= span.end)
+    });
+}
+
+/// Find `name` in a comment and return the token that follows it
+fn pragma_value<'s>(text: &'s str, name: &str) -> Option<&'s str> {
+    let start = text.find(name)? + name.len();
+    let rest = &text[start..];
+    // `@jsxImportSourceFoo` is a different pragma
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    rest.split_whitespace().next()
+}
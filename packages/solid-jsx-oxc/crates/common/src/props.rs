@@ -0,0 +1,250 @@
+//! Component props and attribute values
+//!
+//! Both backends pass props to `createComponent` the same way; only the code for
+//! JSX children differs, so that part is supplied by the caller.
+
+use oxc_ast::ast::{
+    Expression, JSXAttribute, JSXAttributeValue, JSXChild, JSXElement, JSXExpression,
+};
+use oxc_ast::AstBuilder;
+use oxc_span::GetSpan;
+
+use crate::check::{is_dynamic, is_literal, is_static_value};
+use crate::context::TransformContext;
+use crate::expression::{expr_to_string, memo_condition, trim_whitespace};
+use crate::json::json_string;
+use crate::options::TransformOptions;
+
+/// Code for an attribute or prop value. With `wrapConditionals`, a conditional
+/// between JSX branches only re-renders when its test flips.
+pub fn attribute_value_code(
+    expr: &Expression,
+    context: &mut impl TransformContext,
+    options: &TransformOptions,
+) -> String {
+    if options.wrap_conditionals {
+        if let Some(code) = memo_condition(expr, &context.module().static_marks) {
+            context.register_helper("memo");
+            return code;
+        }
+    }
+    expr_to_string(expr)
+}
+
+/// Put a JSX value written without braces (`fallback=<p>Loading</p>`) in an
+/// expression container, so it's compiled like any other JSX expression
+pub fn wrap_element_value<'a>(value: &mut Option<JSXAttributeValue<'a>>, ast: AstBuilder<'a>) {
+    let expr = match value.take() {
+        Some(JSXAttributeValue::Element(element)) => Expression::JSXElement(element),
+        Some(JSXAttributeValue::Fragment(fragment)) => Expression::JSXFragment(fragment),
+        other => {
+            *value = other;
+            return;
+        }
+    };
+    let container = ast.alloc_jsx_expression_container(expr.span(), JSXExpression::from(expr));
+    *value = Some(JSXAttributeValue::ExpressionContainer(container));
+}
+
+/// The code for each of an element's children.
+///
+/// Text, expressions and spreads are the same in every backend; nested elements
+/// and fragments are compiled by `transform_jsx`.
+pub fn child_values<'a, C: TransformContext>(
+    element: &JSXElement<'a>,
+    context: &mut C,
+    mut transform_jsx: impl FnMut(&JSXChild<'a>, &mut C) -> Option<String>,
+) -> Vec<String> {
+    let mut children: Vec<String> = vec![];
+
+    for child in &element.children {
+        match child {
+            JSXChild::Text(text) => {
+                let content = trim_whitespace(&text.value);
+                if !content.is_empty() {
+                    children.push(json_string(&content));
+                }
+            }
+            JSXChild::ExpressionContainer(container) => {
+                if let Some(expr) = container.expression.as_expression() {
+                    children.push(expr_to_string(expr));
+                }
+            }
+            JSXChild::Element(_) | JSXChild::Fragment(_) => {
+                children.extend(transform_jsx(child, context));
+            }
+            JSXChild::Spread(spread) => {
+                children.push(expr_to_string(&spread.expression));
+            }
+        }
+    }
+    children
+}
+
+/// Whether an expression is compiled static JSX: `_tmpl$1.cloneNode(true)`
+fn is_template_clone(expr: &Expression) -> bool {
+    let Expression::CallExpression(call) = expr else {
+        return false;
+    };
+    let Expression::StaticMemberExpression(member) = &call.callee else {
+        return false;
+    };
+    member.property.name == "cloneNode"
+        && matches!(&member.object, Expression::Identifier(ident) if ident.name.starts_with("_tmpl$"))
+}
+
+/// The props object of a component, collected attribute by attribute
+pub struct ComponentProps {
+    /// Prop objects and spreads, in source order
+    sources: Vec<PropsSource>,
+    /// Whether any prop is a getter, method or function
+    has_dynamic: bool,
+    /// Whether every prop is a literal, so the object can be shared between renders
+    all_literal: bool,
+}
+
+/// One argument of `mergeProps`
+enum PropsSource {
+    /// The props written between two spreads
+    Object(Vec<String>),
+    Spread(String),
+}
+
+impl Default for ComponentProps {
+    fn default() -> Self {
+        Self {
+            sources: vec![],
+            has_dynamic: false,
+            all_literal: true,
+        }
+    }
+}
+
+impl ComponentProps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `key="value"`, `key={value}` or valueless attribute. Dynamic values
+    /// become getters.
+    pub fn push_attribute(
+        &mut self,
+        key: &str,
+        attr: &JSXAttribute,
+        context: &mut impl TransformContext,
+        options: &TransformOptions,
+    ) {
+        match &attr.value {
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                self.push_static(format!("{}: {}", key, json_string(&lit.value)));
+            }
+            Some(JSXAttributeValue::ExpressionContainer(container)) => {
+                if let Some(expr) = container.expression.as_expression() {
+                    if options.hoist_static_props {
+                        // A constant object is created once, at module level
+                        if is_static_value(expr) {
+                            let name = context.hoist("static$", expr_to_string(expr));
+                            self.push_static(format!("{}: {}", key, name));
+                            return;
+                        }
+                        // Static JSX is cloned from its template on every read, so
+                        // its getter doesn't stop the object from being shared
+                        if is_template_clone(expr) {
+                            self.push_static(format!(
+                                "get {}() {{ return {}; }}",
+                                key,
+                                expr_to_string(expr)
+                            ));
+                            return;
+                        }
+                    }
+                    let expr_str = attribute_value_code(expr, context, options);
+                    self.all_literal &= is_literal(expr);
+                    if is_dynamic(expr, &context.module().static_marks) {
+                        self.push_dynamic(format!("get {}() {{ return {}; }}", key, expr_str));
+                    } else {
+                        self.push_static(format!("{}: {}", key, expr_str));
+                    }
+                }
+            }
+            None => self.push_static(format!("{}: true", key)),
+            _ => {}
+        }
+    }
+
+    /// Add a `{...props}` spread. Props written after it override its values
+    pub fn push_spread(&mut self, code: String) {
+        self.sources.push(PropsSource::Spread(code));
+    }
+
+    /// Add a plain `key: value` prop
+    pub fn push_static(&mut self, prop: String) {
+        match self.sources.last_mut() {
+            Some(PropsSource::Object(props)) => props.push(prop),
+            _ => self.sources.push(PropsSource::Object(vec![prop])),
+        }
+    }
+
+    /// Add a getter or method
+    pub fn push_dynamic(&mut self, prop: String) {
+        self.has_dynamic = true;
+        self.push_static(prop);
+    }
+
+    /// Add a `key: value` prop holding a function, such as a render callback. It
+    /// may close over local variables, so the object can't be shared
+    pub fn push_function(&mut self, key: &str, code: String) {
+        self.push_dynamic(format!("{}: {}", key, code));
+    }
+
+    /// Add the `children` prop, as a plain value when the children never change
+    pub fn push_children(&mut self, mut children: Vec<String>, constant: bool) {
+        let value = match children.len() {
+            0 => return,
+            1 => children.remove(0),
+            _ => format!("[{}]", children.join(", ")),
+        };
+        if constant {
+            self.push_static(format!("children: {}", value));
+        } else {
+            self.push_dynamic(format!("get children() {{ return {}; }}", value));
+        }
+    }
+
+    /// The code for the props object
+    pub fn finish(
+        mut self,
+        context: &mut impl TransformContext,
+        options: &TransformOptions,
+    ) -> String {
+        let object = |props: Vec<String>| format!("{{ {} }}", props.join(", "));
+
+        match self.sources.len() {
+            0 => "{}".to_string(),
+            // A lone spread is the props object itself
+            1 => match self.sources.remove(0) {
+                PropsSource::Spread(code) => code,
+                // Refs and children are getters or methods, so the object can't be shared
+                PropsSource::Object(props)
+                    if options.hoist_static_props && self.all_literal && !self.has_dynamic =>
+                {
+                    context.hoist("props$", object(props))
+                }
+                PropsSource::Object(props) => object(props),
+            },
+            _ => {
+                let merge = options.merge_props_helper();
+                context.register_helper(merge);
+                let args = self
+                    .sources
+                    .into_iter()
+                    .map(|source| match source {
+                        PropsSource::Object(props) => object(props),
+                        PropsSource::Spread(code) => code,
+                    })
+                    .collect::<Vec<_>>();
+                format!("_${}({})", merge, args.join(", "))
+            }
+        }
+    }
+}
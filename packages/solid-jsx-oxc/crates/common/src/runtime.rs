@@ -0,0 +1,126 @@
+//! Runtime helpers the generated code can import
+//!
+//! Framework authors shipping their own runtime build can check it against this
+//! list instead of discovering a missing export when the output first runs.
+
+use oxc_span::Span;
+
+use crate::diagnostic::Diagnostic;
+use crate::options::{GenerateMode, RuntimeVersion, TransformOptions};
+
+/// A helper imported from the runtime module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeHelper {
+    /// The export name (e.g. `insert`)
+    pub name: &'static str,
+    /// The first solid-js version providing it
+    pub since: &'static str,
+}
+
+const fn helper(name: &'static str, since: &'static str) -> RuntimeHelper {
+    RuntimeHelper { name, since }
+}
+
+/// Helpers emitted by the DOM backend
+static DOM_HELPERS: &[RuntimeHelper] = &[
+    helper("template", "1.0.0"),
+    helper("insert", "1.0.0"),
+    helper("effect", "1.0.0"),
+    helper("memo", "1.0.0"),
+    helper("createComponent", "1.0.0"),
+    helper("mergeProps", "1.0.0"),
+    helper("setAttribute", "1.0.0"),
+    helper("setAttributeNS", "1.0.0"),
+    helper("style", "1.0.0"),
+    helper("classList", "1.0.0"),
+    helper("spread", "1.0.0"),
+    helper("use", "1.0.0"),
+    helper("addEventListener", "1.0.0"),
+    helper("delegateEvents", "1.0.0"),
+    helper("getOwner", "1.0.0"),
+    helper("getNextElement", "1.0.0"),
+    helper("getNextMarker", "1.0.0"),
+    helper("runHydrationEvents", "1.0.0"),
+];
+
+/// Helpers emitted by the SSR backend
+static SSR_HELPERS: &[RuntimeHelper] = &[
+    helper("ssr", "1.0.0"),
+    helper("escape", "1.0.0"),
+    helper("memo", "1.0.0"),
+    helper("createComponent", "1.0.0"),
+    helper("mergeProps", "1.0.0"),
+    helper("ssrClassList", "1.0.0"),
+    helper("ssrStyle", "1.0.0"),
+    helper("ssrHydrationKey", "1.0.0"),
+    helper("NoHydration", "1.3.0"),
+    helper("ssrSpread", "1.0.0"),
+    helper("ssrAttribute", "1.4.0"),
+    helper("ssrElement", "1.4.0"),
+];
+
+/// The helpers output compiled with `options` may import
+pub fn required_helpers(options: &TransformOptions) -> Vec<RuntimeHelper> {
+    let helpers = match options.generate {
+        GenerateMode::Dom | GenerateMode::Universal => DOM_HELPERS,
+        GenerateMode::Ssr => SSR_HELPERS,
+    };
+    let delegates = options.delegate_events
+        && !(options.replace_delegated_events && options.delegated_events.is_empty());
+
+    helpers
+        .iter()
+        .filter(|helper| match helper.name {
+            "delegateEvents" => delegates,
+            "ssrHydrationKey" | "getNextElement" | "getNextMarker" | "runHydrationEvents" => {
+                options.hydratable
+            }
+            "getOwner" => options.context_to_custom_elements,
+            _ => true,
+        })
+        .map(|helper| match (helper.name, options.runtime) {
+            ("mergeProps", RuntimeVersion::Solid2) => RuntimeHelper {
+                name: "merge",
+                since: "2.0.0",
+            },
+            _ => *helper,
+        })
+        .collect()
+}
+
+/// Report imported helpers that the configured runtime doesn't export
+pub fn check_runtime_exports<'h>(
+    options: &TransformOptions,
+    helpers: impl IntoIterator<Item = &'h String>,
+) -> Vec<Diagnostic> {
+    let Some(exports) = &options.runtime_exports else {
+        return vec![];
+    };
+    helpers
+        .into_iter()
+        .filter(|helper| !exports.contains(&helper.as_str()))
+        .map(|helper| {
+            Diagnostic::warning(
+                format!("`{}` is not exported by {}", helper, options.module_name),
+                Span::default(),
+            )
+        })
+        .collect()
+}
+
+/// The lowest solid-js version that provides every helper in `helpers`
+pub fn minimum_version(helpers: &[RuntimeHelper]) -> &'static str {
+    helpers
+        .iter()
+        .map(|helper| helper.since)
+        .max_by_key(|version| parse_version(version))
+        .unwrap_or("1.0.0")
+}
+
+/// Split a `major.minor.patch` version into comparable numbers
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
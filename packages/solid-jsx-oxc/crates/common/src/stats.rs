@@ -0,0 +1,115 @@
+//! Per-file JSX statistics for codebase audits
+//!
+//! Counts are taken from the source JSX, so they're the same whichever backend
+//! the file would be compiled for.
+
+use indexmap::IndexSet;
+use oxc_ast::ast::{JSXAttributeItem, JSXAttributeValue, JSXChild, JSXElement, Program};
+use oxc_ast_visit::{walk, Visit};
+
+use crate::check::{get_attr_name, get_tag_name, is_component, is_dynamic, is_event_attr};
+use crate::expression::to_event_name;
+use crate::options::TransformOptions;
+use crate::pragma::StaticMarks;
+
+/// What a file's JSX costs at runtime
+#[derive(Default)]
+pub struct FileStats {
+    /// Component elements, built-ins included
+    pub components: usize,
+    /// Native (and custom) elements
+    pub elements: usize,
+    /// Reactive attributes, spreads and children of native elements, each one an
+    /// effect or insert at runtime
+    pub dynamic_bindings: usize,
+    /// Events handled through delegation, in order of first use
+    pub delegated_events: Vec<String>,
+    /// Total length of the templates the output creates
+    pub template_bytes: usize,
+}
+
+/// Count the components, elements, dynamic bindings and delegated events of a
+/// program. `template_bytes` is left for the caller, as it needs the transform.
+pub fn jsx_stats(program: &Program, options: &TransformOptions) -> FileStats {
+    let mut counter = Counter {
+        options,
+        marks: StaticMarks::from_program(program, options.static_marker),
+        stats: FileStats::default(),
+        delegated: IndexSet::new(),
+    };
+    counter.visit_program(program);
+    let mut stats = counter.stats;
+    stats.delegated_events = counter.delegated.into_iter().collect();
+    stats
+}
+
+struct Counter<'o, 'a> {
+    options: &'o TransformOptions<'a>,
+    marks: StaticMarks,
+    stats: FileStats,
+    delegated: IndexSet<String>,
+}
+
+impl<'a> Visit<'a> for Counter<'_, '_> {
+    fn visit_jsx_element(&mut self, element: &JSXElement<'a>) {
+        if is_component(&get_tag_name(element)) {
+            self.stats.components += 1;
+        } else {
+            self.stats.elements += 1;
+            self.count_native(element);
+        }
+        walk::walk_jsx_element(self, element);
+    }
+}
+
+impl Counter<'_, '_> {
+    fn count_native(&mut self, element: &JSXElement) {
+        for item in &element.opening_element.attributes {
+            let attr = match item {
+                JSXAttributeItem::Attribute(attr) => attr,
+                JSXAttributeItem::SpreadAttribute(_) => {
+                    self.stats.dynamic_bindings += 1;
+                    continue;
+                }
+            };
+            let key = get_attr_name(&attr.name);
+            if is_event_attr(&key) {
+                // Same rules as the DOM transform: `on:` and capture handlers
+                // are always attached directly
+                let event = to_event_name(&key);
+                if self.options.delegate_events
+                    && !key.starts_with("on:")
+                    && !key.starts_with("oncapture:")
+                    && !key.ends_with("Capture")
+                    && self.options.is_delegated_event(&event)
+                {
+                    self.delegated.insert(event);
+                }
+                continue;
+            }
+            if key == "ref" || key.starts_with("use:") {
+                continue;
+            }
+            if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
+                let expr = container.expression.as_expression();
+                if expr.is_some_and(|expr| is_dynamic(expr, &self.marks)) {
+                    self.stats.dynamic_bindings += 1;
+                }
+            }
+        }
+
+        for child in &element.children {
+            let dynamic = match child {
+                JSXChild::ExpressionContainer(container) => {
+                    let expr = container.expression.as_expression();
+                    expr.is_some_and(|expr| is_dynamic(expr, &self.marks))
+                }
+                JSXChild::Spread(_) => true,
+                _ => false,
+            };
+            if dynamic {
+                self.stats.dynamic_bindings += 1;
+            }
+        }
+    }
+}
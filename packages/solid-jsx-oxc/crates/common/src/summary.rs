@@ -0,0 +1,140 @@
+//! A JSON outline of the JSX in a file, before any transform runs
+//!
+//! Explorer UIs show it next to the IR and the output, so each node carries its
+//! source offsets for highlighting.
+
+use oxc_ast::ast::{
+    Expression, JSXAttributeItem, JSXAttributeValue, JSXChild, JSXElement, JSXFragment, Program,
+};
+use oxc_ast_visit::Visit;
+use oxc_span::Span;
+
+use crate::check::{get_attr_name, get_tag_name, is_component};
+use crate::expression::trim_whitespace;
+use crate::json::{json_array, json_object, json_string};
+
+/// The outermost JSX nodes of a program, as a JSON array of node outlines
+pub fn jsx_summary(program: &Program) -> String {
+    let mut roots = Roots::default();
+    roots.visit_program(program);
+    json_array(roots.nodes)
+}
+
+/// Collects the outline of each outermost JSX node
+#[derive(Default)]
+struct Roots {
+    nodes: Vec<String>,
+}
+
+impl<'a> Visit<'a> for Roots {
+    // Nested JSX is part of its root's outline, so neither walks further
+    fn visit_jsx_element(&mut self, element: &JSXElement<'a>) {
+        self.nodes.push(element_summary(element));
+    }
+
+    fn visit_jsx_fragment(&mut self, fragment: &JSXFragment<'a>) {
+        self.nodes.push(fragment_summary(fragment));
+    }
+}
+
+fn element_summary(element: &JSXElement) -> String {
+    let tag = get_tag_name(element);
+    let kind = if is_component(&tag) {
+        "component"
+    } else {
+        "element"
+    };
+    let attributes = element
+        .opening_element
+        .attributes
+        .iter()
+        .map(|item| match item {
+            JSXAttributeItem::Attribute(attr) => {
+                let name = ("name", json_string(&get_attr_name(&attr.name)));
+                match &attr.value {
+                    None => json_object([name, ("value", json_string("true"))]),
+                    Some(JSXAttributeValue::StringLiteral(_)) => {
+                        json_object([name, ("value", json_string("string"))])
+                    }
+                    Some(JSXAttributeValue::ExpressionContainer(container)) => json_object([
+                        name,
+                        ("value", json_string("expression")),
+                        (
+                            "jsx",
+                            container
+                                .expression
+                                .as_expression()
+                                .map_or_else(|| json_array([]), nested_jsx),
+                        ),
+                    ]),
+                    Some(_) => json_object([name, ("value", json_string("jsx"))]),
+                }
+            }
+            JSXAttributeItem::SpreadAttribute(spread) => json_object([
+                ("name", json_string("...")),
+                ("value", json_string("spread")),
+                ("jsx", nested_jsx(&spread.argument)),
+            ]),
+        });
+    json_object([
+        ("kind", json_string(kind)),
+        ("tag", json_string(&tag)),
+        ("start", element.span.start.to_string()),
+        ("end", element.span.end.to_string()),
+        ("attributes", json_array(attributes)),
+        ("children", children_summary(&element.children)),
+    ])
+}
+
+fn fragment_summary(fragment: &JSXFragment) -> String {
+    json_object([
+        ("kind", json_string("fragment")),
+        ("start", fragment.span.start.to_string()),
+        ("end", fragment.span.end.to_string()),
+        ("children", children_summary(&fragment.children)),
+    ])
+}
+
+/// Whitespace-only text is dropped, as the transforms drop it
+fn children_summary(children: &[JSXChild]) -> String {
+    json_array(children.iter().filter_map(|child| match child {
+        JSXChild::Element(element) => Some(element_summary(element)),
+        JSXChild::Fragment(fragment) => Some(fragment_summary(fragment)),
+        JSXChild::Text(text) => {
+            let content = trim_whitespace(&text.value);
+            (!content.is_empty()).then(|| {
+                json_object([
+                    ("kind", json_string("text")),
+                    ("value", json_string(&content)),
+                    ("start", text.span.start.to_string()),
+                    ("end", text.span.end.to_string()),
+                ])
+            })
+        }
+        JSXChild::ExpressionContainer(container) => {
+            let expr = container.expression.as_expression()?;
+            Some(expression_summary("expression", container.span, expr))
+        }
+        JSXChild::Spread(spread) => Some(expression_summary(
+            "spread",
+            spread.span,
+            &spread.expression,
+        )),
+    }))
+}
+
+/// An expression child, with the JSX nested in it (e.g. in a `<For>` callback)
+fn expression_summary(kind: &str, span: Span, expr: &Expression) -> String {
+    json_object([
+        ("kind", json_string(kind)),
+        ("start", span.start.to_string()),
+        ("end", span.end.to_string()),
+        ("jsx", nested_jsx(expr)),
+    ])
+}
+
+fn nested_jsx(expr: &Expression) -> String {
+    let mut roots = Roots::default();
+    roots.visit_expression(expr);
+    json_array(roots.nodes)
+}
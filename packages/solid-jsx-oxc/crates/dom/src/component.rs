@@ -1,19 +1,22 @@
 //! Component transform
 //! Handles <MyComponent /> -> createComponent(MyComponent, {...})
 
-use oxc_ast::ast::{JSXAttributeItem, JSXAttributeName, JSXAttributeValue, JSXChild, JSXElement};
+use oxc_ast::ast::{JSXAttributeItem, JSXAttributeValue, JSXChild, JSXElement};
 
 use common::{
-    expr_to_string, find_prop, get_children_callback, is_built_in, is_dynamic, TransformOptions,
+    child_values, expr_to_string, find_prop, get_attr_name, get_children_callback, get_tag_name,
+    is_built_in, is_constant_children, render_callback, ComponentProps, TransformContext,
+    TransformOptions,
 };
 
+use crate::element::ref_statements;
 use crate::ir::{BlockContext, ChildTransformer, Expr, TransformResult};
 
 /// Transform a component element
 pub fn transform_component<'a, 'b>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) -> TransformResult {
@@ -31,7 +34,7 @@ pub fn transform_component<'a, 'b>(
 
     // Generate createComponent call
     result.exprs.push(Expr {
-        code: format!("createComponent({}, {})", tag_name, props),
+        code: format!("_$createComponent({}, {})", tag_name, props),
     });
 
     result
@@ -41,27 +44,29 @@ pub fn transform_component<'a, 'b>(
 fn transform_builtin<'a, 'b>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) -> TransformResult {
     let mut result = TransformResult::default();
 
     match tag_name {
-        "For" => transform_for(element, &mut result, context, transform_child),
-        "Show" => transform_show(element, &mut result, context, transform_child),
-        "Switch" => transform_switch(element, &mut result, context, transform_child),
-        "Match" => transform_match(element, &mut result, context, transform_child),
+        "For" => transform_for(element, &mut result, context, options),
+        "Show" => transform_show(element, &mut result, context, options, transform_child),
+        "Switch" => transform_switch(element, &mut result, context, options, transform_child),
+        "Match" => transform_match(element, &mut result, context, options, transform_child),
         "Index" => transform_index(element, &mut result, context, transform_child),
-        "Suspense" => transform_suspense(element, &mut result, context, transform_child),
-        "Portal" => transform_portal(element, &mut result, context, transform_child),
+        "Suspense" => transform_suspense(element, &mut result, context, options, transform_child),
+        "Portal" => transform_portal(element, &mut result, context, options, transform_child),
         "Dynamic" => transform_dynamic(element, &mut result, context, options, transform_child),
-        "ErrorBoundary" => transform_error_boundary(element, &mut result, context, transform_child),
+        "ErrorBoundary" => {
+            transform_error_boundary(element, &mut result, context, options, transform_child)
+        }
         _ => {
             // Fallback to regular component transform
             context.register_helper("createComponent");
             result.exprs.push(Expr {
-                code: format!("createComponent({}, {{}})", tag_name),
+                code: format!("_$createComponent({}, {{}})", tag_name),
             });
         }
     }
@@ -81,45 +86,45 @@ fn get_prop_expr<'a>(element: &JSXElement<'a>, name: &str) -> String {
         .unwrap_or_else(|| "undefined".to_string())
 }
 
-/// Transform <For each={...}>{item => ...}</For>
-fn transform_for<'a, 'b>(
+/// Transform <For each={...} fallback={...}>{item => ...}</For>
+fn transform_for<'a>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
-    _transform_child: ChildTransformer<'a, 'b>,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
 ) {
     context.register_helper("createComponent");
-    // Note: For is expected to be imported by user from solid-js, not added here
+    // Note: For is expected to be imported by user from solid-js
 
-    let each_expr = get_prop_expr(element, "each");
-    let children = get_children_callback(element);
+    // The callback's JSX has already been compiled, so it is passed through as written
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    props.push_function("children", get_children_callback(element));
 
     result.exprs.push(Expr {
-        code: format!(
-            "createComponent(For, {{ each: {}, children: {} }})",
-            each_expr, children
-        ),
+        code: format!("_$createComponent(For, {})", props.finish(context, options)),
     });
 }
 
-/// Transform <Show when={...} fallback={...}>...</Show>
+/// Transform <Show when={...} keyed fallback={...}>...</Show>
 fn transform_show<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Show is expected to be imported by user from solid-js
 
-    let when_expr = get_prop_expr(element, "when");
-    let fallback_expr = get_prop_expr(element, "fallback");
-    let children = get_children_expr_transformed(element, context, transform_child);
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Show, {{ when: {}, fallback: {}, get children() {{ return {}; }} }})",
-            when_expr, fallback_expr, children
+            "_$createComponent(Show, {})",
+            props.finish(context, options)
         ),
     });
 }
@@ -128,18 +133,22 @@ fn transform_show<'a, 'b>(
 fn transform_switch<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Switch is expected to be imported by user from solid-js
 
-    let children = get_children_expr_transformed(element, context, transform_child);
+    // Each <Match> is compiled to its own createComponent call
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Switch, {{ get children() {{ return {}; }} }})",
-            children
+            "_$createComponent(Switch, {})",
+            props.finish(context, options)
         ),
     });
 }
@@ -148,19 +157,21 @@ fn transform_switch<'a, 'b>(
 fn transform_match<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Match is expected to be imported by user from solid-js
 
-    let when_expr = get_prop_expr(element, "when");
-    let children = get_children_expr_transformed(element, context, transform_child);
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Match, {{ when: {}, get children() {{ return {}; }} }})",
-            when_expr, children
+            "_$createComponent(Match, {})",
+            props.finish(context, options)
         ),
     });
 }
@@ -169,7 +180,7 @@ fn transform_match<'a, 'b>(
 fn transform_index<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     _transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
@@ -180,7 +191,7 @@ fn transform_index<'a, 'b>(
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Index, {{ each: {}, children: {} }})",
+            "_$createComponent(Index, {{ each: {}, children: {} }})",
             each_expr, children
         ),
     });
@@ -190,249 +201,206 @@ fn transform_index<'a, 'b>(
 fn transform_suspense<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Suspense is expected to be imported by user from solid-js
 
-    let fallback_expr = get_prop_expr(element, "fallback");
-    let children = get_children_expr_transformed(element, context, transform_child);
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Suspense, {{ fallback: {}, get children() {{ return {}; }} }})",
-            fallback_expr, children
+            "_$createComponent(Suspense, {})",
+            props.finish(context, options)
         ),
     });
 }
 
-/// Transform <Portal mount={...}>...</Portal>
+/// Transform <Portal mount={...} useShadow>...</Portal>
 fn transform_portal<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Portal is expected to be imported by user from solid-js/web
 
-    let mount_expr = get_prop_expr(element, "mount");
-    let children = get_children_expr_transformed(element, context, transform_child);
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    // Content like <g> or <circle> must be mounted in an SVG container
+    if find_prop(element, "isSVG").is_none() && has_svg_children(element, options) {
+        props.push_static("isSVG: true".to_string());
+    }
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(Portal, {{ mount: {}, get children() {{ return {}; }} }})",
-            mount_expr, children
+            "_$createComponent(Portal, {})",
+            props.finish(context, options)
         ),
     });
 }
 
+/// Whether every element child is SVG content, other than a root <svg>
+fn has_svg_children(element: &JSXElement<'_>, options: &TransformOptions<'_>) -> bool {
+    let mut tags = element.children.iter().filter_map(|child| match child {
+        JSXChild::Element(child) => Some(get_tag_name(child)),
+        _ => None,
+    });
+    let is_svg_content = |tag: &str| tag != "svg" && options.is_svg_element(tag);
+    tags.next().is_some_and(|tag| is_svg_content(&tag)) && tags.all(|tag| is_svg_content(&tag))
+}
+
 /// Transform <Dynamic component={...} {...props} />
 fn transform_dynamic<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: Dynamic is expected to be imported by user from solid-js/web
 
-    let component_expr = get_prop_expr(element, "component");
+    // `component` is an ordinary prop, merged with any spread props
     let props = build_props(element, context, options, transform_child);
 
     result.exprs.push(Expr {
-        code: format!(
-            "createComponent(Dynamic, {{ component: {}, ...{} }})",
-            component_expr, props
-        ),
+        code: format!("_$createComponent(Dynamic, {})", props),
     });
 }
 
-/// Transform <ErrorBoundary fallback={...}>...</ErrorBoundary>
+/// Transform <ErrorBoundary fallback={(err, reset) => ...}>...</ErrorBoundary>
 fn transform_error_boundary<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
     context.register_helper("createComponent");
     // Note: ErrorBoundary is expected to be imported by user from solid-js
 
-    let fallback_expr = get_prop_expr(element, "fallback");
-    let children = get_children_expr_transformed(element, context, transform_child);
+    // A callback fallback is passed as written, a JSX one behind a getter
+    let mut props = ComponentProps::new();
+    push_attributes(element, &mut props, context, options);
+    push_child_props(element, &mut props, context, transform_child);
 
     result.exprs.push(Expr {
         code: format!(
-            "createComponent(ErrorBoundary, {{ fallback: {}, get children() {{ return {}; }} }})",
-            fallback_expr, children
+            "_$createComponent(ErrorBoundary, {})",
+            props.finish(context, options)
         ),
     });
 }
 
+/// Add each attribute of a built-in as a prop: `each`, `when`, `fallback`...
+fn push_attributes<'a>(
+    element: &JSXElement<'a>,
+    props: &mut ComponentProps,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
+) {
+    for attr in &element.opening_element.attributes {
+        match attr {
+            JSXAttributeItem::Attribute(attr) => {
+                let key = get_attr_name(&attr.name);
+                if key != "children" {
+                    props.push_attribute(&key, attr, context, options);
+                }
+            }
+            JSXAttributeItem::SpreadAttribute(spread) => {
+                props.push_spread(expr_to_string(&spread.argument));
+            }
+        }
+    }
+}
+
+/// Add the `children` prop: a render callback as written, anything else as a
+/// value or getter
+fn push_child_props<'a, 'b>(
+    element: &JSXElement<'a>,
+    props: &mut ComponentProps,
+    context: &mut BlockContext,
+    transform_child: ChildTransformer<'a, 'b>,
+) {
+    if let Some(callback) = render_callback(element) {
+        props.push_function("children", callback);
+        return;
+    }
+    let children = child_values(element, context, |child, context| {
+        jsx_child_code(child, context, transform_child)
+    });
+    props.push_children(children, is_constant_children(&element.children));
+}
+
 /// Build props object for a component
 fn build_props<'a, 'b>(
     element: &JSXElement<'a>,
-    context: &BlockContext,
-    _options: &TransformOptions<'a>,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) -> String {
-    let mut static_props: Vec<String> = vec![];
-    let mut dynamic_props: Vec<String> = vec![];
-    let mut spreads: Vec<String> = vec![];
+    let mut props = ComponentProps::new();
+    let mut refs: Vec<String> = vec![];
 
     for attr in &element.opening_element.attributes {
         match attr {
             JSXAttributeItem::Attribute(attr) => {
-                let key = match &attr.name {
-                    JSXAttributeName::Identifier(id) => id.name.to_string(),
-                    JSXAttributeName::NamespacedName(ns) => {
-                        format!("{}:{}", ns.namespace.name, ns.name.name)
-                    }
-                };
+                let key = get_attr_name(&attr.name);
 
-                // Skip component and children props for Dynamic
-                if key == "component" || key == "children" {
+                // Children come from the element's body
+                if key == "children" {
                     continue;
                 }
 
-                // Handle ref prop specially - needs ref forwarding
+                // Handle ref prop specially - needs ref forwarding. Every ref on
+                // the component is applied by a single forwarding method
                 if key == "ref" {
                     if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
                         if let Some(expr) = container.expression.as_expression() {
-                            let expr_str = expr_to_string(expr);
-                            // Generate ref forwarding function that handles both callbacks and variables
-                            dynamic_props.push(format!(
-                                "ref(r$) {{ var _ref$ = {}; typeof _ref$ === \"function\" ? _ref$(r$) : {} = r$; }}",
-                                expr_str, expr_str
-                            ));
+                            refs.extend(ref_statements(expr, "r$", true, context));
                         }
                     }
                     continue;
                 }
 
-                match &attr.value {
-                    Some(JSXAttributeValue::StringLiteral(lit)) => {
-                        static_props.push(format!("{}: \"{}\"", key, lit.value));
-                    }
-                    Some(JSXAttributeValue::ExpressionContainer(container)) => {
-                        if let Some(expr) = container.expression.as_expression() {
-                            let expr_str = expr_to_string(expr);
-                            if is_dynamic(expr) {
-                                // Dynamic prop - use getter
-                                dynamic_props
-                                    .push(format!("get {}() {{ return {}; }}", key, expr_str));
-                            } else {
-                                static_props.push(format!("{}: {}", key, expr_str));
-                            }
-                        }
-                    }
-                    None => {
-                        static_props.push(format!("{}: true", key));
-                    }
-                    _ => {}
-                }
+                props.push_attribute(&key, attr, context, options);
             }
             JSXAttributeItem::SpreadAttribute(spread) => {
-                spreads.push(expr_to_string(&spread.argument));
+                props.push_spread(expr_to_string(&spread.argument));
             }
         }
     }
 
-    // Handle children
-    if !element.children.is_empty() {
-        let children_expr = get_children_expr_transformed(element, context, transform_child);
-        if !children_expr.is_empty() {
-            dynamic_props.push(format!("get children() {{ return {}; }}", children_expr));
-        }
+    if !refs.is_empty() {
+        props.push_dynamic(format!("ref(r$) {{ {}; }}", refs.join("; ")));
     }
 
-    // Combine all props
-    let all_props = static_props
-        .into_iter()
-        .chain(dynamic_props)
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    // Combine props
-    if !spreads.is_empty() {
-        context.register_helper("mergeProps");
-        let spread_list = spreads.join(", ");
-        if all_props.is_empty() {
-            format!("mergeProps({})", spread_list)
-        } else {
-            format!("mergeProps({}, {{ {} }})", spread_list, all_props)
-        }
-    } else if all_props.is_empty() {
-        "{}".to_string()
-    } else {
-        format!("{{ {} }}", all_props)
-    }
+    push_child_props(element, &mut props, context, transform_child);
+
+    props.finish(context, options)
 }
 
-/// Get children as an expression with recursive transformation
-fn get_children_expr_transformed<'a, 'b>(
-    element: &JSXElement<'a>,
-    context: &BlockContext,
+/// The code creating a JSX element or fragment child
+fn jsx_child_code<'a, 'b>(
+    child: &JSXChild<'a>,
+    context: &mut BlockContext,
     transform_child: ChildTransformer<'a, 'b>,
-) -> String {
-    let mut children: Vec<String> = vec![];
-
-    for child in &element.children {
-        match child {
-            JSXChild::Text(text) => {
-                let content = common::expression::trim_whitespace(&text.value);
-                if !content.is_empty() {
-                    children.push(format!(
-                        "\"{}\"",
-                        common::expression::escape_html(&content, false)
-                    ));
-                }
-            }
-            JSXChild::ExpressionContainer(container) => {
-                if let Some(expr) = container.expression.as_expression() {
-                    children.push(expr_to_string(expr));
-                }
-            }
-            JSXChild::Element(_) | JSXChild::Fragment(_) => {
-                // Transform the child JSX element/fragment
-                if let Some(result) = transform_child(child) {
-                    // Get the generated code from the result
-                    if !result.exprs.is_empty() {
-                        children.push(result.exprs[0].code.clone());
-                    } else if !result.template.is_empty() {
-                        // This is a native element - output the IIFE that creates it
-                        let tmpl_idx =
-                            context.push_template(result.template.clone(), result.is_svg);
-                        let tmpl_var = format!("_tmpl${}", tmpl_idx + 1);
-                        let elem_var = context.generate_uid("el$");
-
-                        let mut code = format!(
-                            "(() => {{ const {} = {}.cloneNode(true);",
-                            elem_var, tmpl_var
-                        );
-                        for expr in &result.exprs {
-                            code.push_str(&format!(" {};", expr.code));
-                        }
-                        code.push_str(&format!(" return {}; }})()", elem_var));
-                        children.push(code);
-                    }
-                }
-            }
-            JSXChild::Spread(spread) => {
-                children.push(expr_to_string(&spread.expression));
-            }
-        }
-    }
-
-    if children.len() == 1 {
-        children.pop().unwrap_or_default()
-    } else if children.is_empty() {
-        String::new()
-    } else {
-        format!("[{}]", children.join(", "))
-    }
+) -> Option<String> {
+    let result = transform_child(child, context)?;
+    result
+        .exprs
+        .into_iter()
+        .next()
+        .map(|expr| expr.code)
+        .filter(|code| !code.is_empty())
 }
 
 // find_prop and get_children_callback moved to common module
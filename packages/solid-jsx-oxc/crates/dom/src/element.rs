@@ -1,18 +1,31 @@
 //! Native element transform
 //! Handles <div>, <span>, etc. -> template + effects
 
-use oxc_ast::ast::{JSXAttribute, JSXAttributeItem, JSXAttributeValue, JSXElement};
+use oxc_ast::ast::{
+    ArrayExpressionElement, BinaryOperator, Expression, JSXAttribute, JSXAttributeItem,
+    JSXAttributeValue, JSXChild, JSXElement, UnaryOperator,
+};
 
 use common::{
-    constants::{ALIASES, DELEGATED_EVENTS, VOID_ELEMENTS},
+    attribute_value_code, child_property_conflict,
+    constants::{
+        ALIASES, ALWAYS_CLOSE, BLOCK_ELEMENTS, INLINE_ELEMENTS, NON_SPREAD_NAMESPACES, PROPERTIES,
+    },
     expr_to_string,
-    expression::{escape_html, to_event_name},
-    get_attr_name, is_component, is_dynamic, is_namespaced_attr, is_svg_element, TransformOptions,
+    expression::{
+        class_list_conditions, class_template_literal, constant_text, escape_html,
+        object_to_style_string, to_event_name, trim_around_blocks, trim_whitespace,
+    },
+    find_child_property, find_prop, get_attr_name, get_tag_name, is_class_key, is_component,
+    is_dynamic, is_enumerated_attr, is_event_attr, is_namespaced_attr, json_string,
+    memo_condition_insert, nesting_diagnostics, preserves_whitespace, Diagnostic, StaticMarks,
+    TransformContext, TransformOptions,
 };
 
 use crate::ir::{
     BlockContext, ChildTransformer, Declaration, DynamicBinding, Expr, TransformResult,
 };
+use crate::template::{generate_set_attr, set_attr_helper};
 use crate::transform::TransformInfo;
 
 /// Transform a native HTML/SVG element
@@ -20,13 +33,18 @@ pub fn transform_element<'a, 'b>(
     element: &JSXElement<'a>,
     tag_name: &str,
     info: &TransformInfo,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) -> TransformResult {
-    let is_svg = is_svg_element(tag_name);
-    let is_void = VOID_ELEMENTS.contains(tag_name);
-    let is_custom_element = tag_name.contains('-');
+    // Everything inside `<svg>` is SVG, and `<foreignObject>` content is HTML again
+    let is_svg = tag_name == "svg"
+        || info
+            .in_svg
+            .unwrap_or_else(|| options.is_svg_element(tag_name));
+    let is_void = options.is_void_element(tag_name);
+    // Customized built-ins (`<button is="my-button">`) upgrade like custom elements
+    let is_custom_element = tag_name.contains('-') || find_prop(element, "is").is_some();
 
     let mut result = TransformResult {
         tag_name: Some(tag_name.to_string()),
@@ -35,11 +53,18 @@ pub fn transform_element<'a, 'b>(
         ..Default::default()
     };
 
+    if options.validate && !is_svg {
+        for diagnostic in nesting_diagnostics(element, tag_name) {
+            context.report(diagnostic);
+        }
+    }
+
     // Check if this element needs runtime access (dynamic attributes, refs, events)
     let needs_runtime_access = element_needs_runtime_access(element);
 
     // Generate element ID if needed
-    if !info.skip_id && (info.top_level || needs_runtime_access) {
+    let owner_context = is_custom_element && options.context_to_custom_elements;
+    if !info.skip_id && (info.top_level || info.marker || needs_runtime_access || owner_context) {
         let elem_id = context.generate_uid("el$");
         result.id = Some(elem_id.clone());
 
@@ -62,47 +87,196 @@ pub fn transform_element<'a, 'b>(
     result.template = format!("<{}", tag_name);
     result.template_with_closing_tags = result.template.clone();
 
+    // Custom elements see the reactive owner they were created under
+    if owner_context {
+        if let Some(elem_id) = &result.id {
+            context.register_helper("getOwner");
+            result.exprs.push(Expr {
+                code: format!("{}._$owner = _$getOwner()", elem_id),
+            });
+        }
+    }
+
     // Transform attributes
     transform_attributes(element, &mut result, context, options);
 
+    // A form control's `value` and `checked` are set after its children, so a
+    // `<select>` has its options by the time its value is set
+    let child_properties: Vec<DynamicBinding> =
+        if matches!(tag_name, "input" | "select" | "textarea") {
+            let (deferred, rest) = std::mem::take(&mut result.dynamics)
+                .into_iter()
+                .partition(|binding| matches!(binding.key.as_str(), "value" | "checked"));
+            result.dynamics = rest;
+            deferred
+        } else {
+            vec![]
+        };
+
     // Close opening tag
+    if options.omit_quotes {
+        result.template = unquote_attributes(&result.template);
+    }
     result.template.push('>');
     result.template_with_closing_tags.push('>');
 
+    // `<noscript>` content is kept as static template text
+    if tag_name == "noscript" {
+        let mut dropped = false;
+        let markup = noscript_markup(&element.children, options, &mut dropped);
+        if dropped {
+            context.report(Diagnostic::warning(
+                "dynamic content inside <noscript> is only rendered on the server",
+                element.span,
+            ));
+        }
+        result.template.push_str(&markup);
+        result.template_with_closing_tags.push_str(&markup);
+        result.template.push_str("</noscript>");
+        result.template_with_closing_tags.push_str("</noscript>");
+        return result;
+    }
+
     // Transform children (if not void element)
     if !is_void {
-        // Pass down the root ID and path for children
-        // If this element has an ID, it becomes the new root for children
-        // and children's paths reset to be relative to this element
-        let child_info = TransformInfo {
-            root_id: result.id.clone().or_else(|| info.root_id.clone()),
-            path: if result.id.is_some() {
-                vec![]
-            } else {
-                info.path.clone()
-            },
-            top_level: false,
-            ..info.clone()
+        // The HTML parser closes the last element of a template by itself. Inside an
+        // element that keeps its closing tag, one that content follows, only
+        // `omit_nested_closing_tags` lets it, and never for tags like `<a>` or
+        // `<table>` it wouldn't close where the template expects
+        let close = !info.last_element
+            || !options.omit_last_closing_tag
+            || info
+                .to_be_closed
+                .as_ref()
+                .is_some_and(|tags| !options.omit_nested_closing_tags || tags.contains(tag_name));
+        let to_be_closed = if close {
+            let mut tags = info
+                .to_be_closed
+                .clone()
+                .unwrap_or_else(|| ALWAYS_CLOSE.iter().map(|tag| tag.to_string()).collect());
+            tags.insert(tag_name.to_string());
+            // A block element left open inside an inline one would escape it
+            if INLINE_ELEMENTS.contains(tag_name) {
+                tags.extend(BLOCK_ELEMENTS.iter().map(|tag| tag.to_string()));
+            }
+            Some(tags)
+        } else {
+            info.to_be_closed.clone()
         };
-        transform_children(
-            element,
-            &mut result,
-            &child_info,
-            context,
-            options,
-            transform_child,
-        );
+
+        // `innerHTML`, `textContent` and `innerText` replace the children
+        if let Some(property) = find_child_property(element) {
+            transform_child_property(element, property, &mut result, context);
+        } else {
+            // Pass down the root ID and path for children
+            // If this element has an ID, it becomes the new root for children
+            // and children's paths reset to be relative to this element
+            let child_info = TransformInfo {
+                root_id: result.id.clone().or_else(|| info.root_id.clone()),
+                path: if result.id.is_some() {
+                    vec![]
+                } else {
+                    info.path.clone()
+                },
+                top_level: false,
+                in_svg: Some(is_svg && tag_name != "foreignObject"),
+                to_be_closed,
+                ..info.clone()
+            };
+            // Same rules as SSR, so the server's text nodes match the template
+            let preserve = options.minify_whitespace && preserves_whitespace(element, tag_name);
+            if preserve {
+                context.module.preserve_whitespace += 1;
+            }
+            transform_children(
+                element,
+                &mut result,
+                &child_info,
+                context,
+                options,
+                transform_child,
+            );
+            if preserve {
+                context.module.preserve_whitespace -= 1;
+            }
+        }
 
         // Close tag
-        result.template.push_str(&format!("</{}>", tag_name));
+        if close {
+            result.template.push_str(&format!("</{}>", tag_name));
+        }
         result
             .template_with_closing_tags
             .push_str(&format!("</{}>", tag_name));
     }
+    result.dynamics.extend(child_properties);
 
     result
 }
 
+/// Static markup for `<noscript>` content.
+///
+/// Browsers running the client parse `<noscript>` content as text, so it can't be
+/// walked or updated: it's kept in the template as written, and dynamic parts
+/// (which only matter when rendering on the server) are dropped.
+fn noscript_markup(
+    children: &[JSXChild<'_>],
+    options: &TransformOptions<'_>,
+    dropped: &mut bool,
+) -> String {
+    let mut html = String::new();
+    for child in children {
+        match child {
+            JSXChild::Text(text) => {
+                html.push_str(&escape_html(&trim_whitespace(&text.value), false));
+            }
+            JSXChild::Element(element) => {
+                let tag = get_tag_name(element);
+                if is_component(&tag) {
+                    *dropped = true;
+                    continue;
+                }
+                html.push_str(&format!("<{}", tag));
+                for attr in &element.opening_element.attributes {
+                    let JSXAttributeItem::Attribute(attr) = attr else {
+                        *dropped = true;
+                        continue;
+                    };
+                    let key = get_attr_name(&attr.name);
+                    let key = ALIASES.get(key.as_str()).copied().unwrap_or(key.as_str());
+                    match &attr.value {
+                        Some(JSXAttributeValue::StringLiteral(lit)) => {
+                            let escaped = escape_html(&lit.value, true);
+                            html.push_str(&format!(" {}=\"{}\"", key, escaped));
+                        }
+                        None => html.push_str(&format!(" {}", key)),
+                        _ => *dropped = true,
+                    }
+                }
+                html.push('>');
+                if !options.is_void_element(&tag) {
+                    html.push_str(&noscript_markup(&element.children, options, dropped));
+                    html.push_str(&format!("</{}>", tag));
+                }
+            }
+            JSXChild::Fragment(fragment) => {
+                html.push_str(&noscript_markup(&fragment.children, options, dropped));
+            }
+            JSXChild::ExpressionContainer(container) => {
+                match container.expression.as_expression() {
+                    Some(Expression::StringLiteral(lit)) => {
+                        html.push_str(&escape_html(&lit.value, false));
+                    }
+                    Some(_) => *dropped = true,
+                    None => {}
+                }
+            }
+            JSXChild::Spread(_) => *dropped = true,
+        }
+    }
+    html
+}
+
 /// Check if an element needs runtime access
 fn element_needs_runtime_access(element: &JSXElement) -> bool {
     // Check attributes
@@ -122,7 +296,7 @@ fn element_needs_runtime_access(element: &JSXElement) -> bool {
                 }
 
                 // Event handlers need access
-                if key.starts_with("on") && key.len() > 2 {
+                if is_event_attr(&key) {
                     return true;
                 }
 
@@ -150,13 +324,14 @@ fn element_needs_runtime_access(element: &JSXElement) -> bool {
                         return true;
                     }
                 }
-                oxc_ast::ast::JSXChild::ExpressionContainer(_) => {
+                oxc_ast::ast::JSXChild::ExpressionContainer(_)
+                | oxc_ast::ast::JSXChild::Spread(_) => {
                     return true;
                 }
-                oxc_ast::ast::JSXChild::Fragment(fragment) => {
-                    if children_need_runtime_access(&fragment.children) {
-                        return true;
-                    }
+                oxc_ast::ast::JSXChild::Fragment(fragment)
+                    if children_need_runtime_access(&fragment.children) =>
+                {
+                    return true;
                 }
                 _ => {}
             }
@@ -172,38 +347,272 @@ fn element_needs_runtime_access(element: &JSXElement) -> bool {
 }
 
 /// Transform element attributes
+///
+/// Refs, event handlers, directives and spreads take effect in source order.
+/// From the first spread on, attributes are merged into the spread's props in
+/// order, so a later attribute overrides the spread rather than the other way
+/// round. Event handlers before a spread are reported, as the spread may
+/// replace them.
 fn transform_attributes<'a>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
 ) {
     let elem_id = result.id.clone();
+    let attributes = &element.opening_element.attributes;
+    let first_spread = attributes
+        .iter()
+        .position(|attr| matches!(attr, JSXAttributeItem::SpreadAttribute(_)));
+
+    // Several class attributes are combined, unless a spread may set the class too
+    let class_attrs: Vec<&JSXAttribute> = attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            JSXAttributeItem::Attribute(attr) if is_class_key(&get_attr_name(&attr.name)) => {
+                Some(&**attr)
+            }
+            _ => None,
+        })
+        .collect();
+    let merge_classes = first_spread.is_none() && class_attrs.len() > 1;
+    if merge_classes {
+        transform_merged_class(&class_attrs, elem_id.as_deref(), result);
+    }
 
-    for attr in &element.opening_element.attributes {
+    // `class:name={true}` is written into the template's class attribute, and
+    // `style:name="value"` into its style attribute
+    let mut static_toggles = vec![];
+    let mut static_styles = vec![];
+
+    // Where the spread call goes among the element's expressions
+    let mut spread_index = None;
+    let mut spread_args: Vec<String> = vec![];
+    let mut running_object: Vec<String> = vec![];
+    let mut dynamic_spread = false;
+
+    for (index, attr) in attributes.iter().enumerate() {
         match attr {
             JSXAttributeItem::Attribute(attr) => {
+                let key = get_attr_name(&attr.name);
+                if merge_classes && is_class_key(&key) {
+                    continue;
+                }
+                if let Some(name) = key.strip_prefix("class:") {
+                    match static_class_toggle(attr) {
+                        Some(true) => static_toggles.push(name.to_string()),
+                        Some(false) => {}
+                        None => {
+                            let elem_id =
+                                elem_id.as_deref().expect("class: requires an element id");
+                            transform_class_toggle(attr, name, elem_id, result, context, options);
+                        }
+                    }
+                    continue;
+                }
+                if let Some(name) = key.strip_prefix("style:") {
+                    match static_style_value(attr) {
+                        Some(value) => static_styles.push(format!("{}: {}", name, value)),
+                        None => {
+                            let elem_id =
+                                elem_id.as_deref().expect("style: requires an element id");
+                            transform_style_property(attr, name, elem_id, result, context, options);
+                        }
+                    }
+                    continue;
+                }
+                match first_spread {
+                    Some(first) if index > first && can_native_spread(&key) => {
+                        running_object.extend(spread_prop(
+                            attr,
+                            &key,
+                            &context.module.static_marks,
+                        ));
+                        continue;
+                    }
+                    Some(first) if index < first && is_event_attr(&key) => {
+                        context.report(Diagnostic::warning(
+                            format!(
+                                "`{}` is set before a spread that may replace it; move it after the spread",
+                                key
+                            ),
+                            attr.span,
+                        ));
+                    }
+                    _ => {}
+                }
                 transform_attribute(attr, elem_id.as_deref(), result, context, options);
             }
             JSXAttributeItem::SpreadAttribute(spread) => {
-                // Handle {...props} spread
-                let elem_id = elem_id
-                    .as_deref()
-                    .expect("Spread attributes require an element id");
-                context.register_helper("spread");
-                let spread_expr = expr_to_string(&spread.argument);
-                result.exprs.push(Expr {
-                    code: format!(
-                        "spread({}, {}, {}, {})",
-                        elem_id,
-                        spread_expr,
-                        result.is_svg,
-                        !element.children.is_empty()
-                    ),
-                });
+                spread_index.get_or_insert(result.exprs.len());
+                if !running_object.is_empty() {
+                    spread_args.push(format!("{{ {} }}", running_object.join(", ")));
+                    running_object.clear();
+                }
+                let (source, dynamic) =
+                    spread_source(&spread.argument, &context.module.static_marks);
+                dynamic_spread |= dynamic;
+                spread_args.push(source);
+            }
+        }
+    }
+
+    if !static_toggles.is_empty() {
+        let classes = escape_html(&static_toggles.join(" "), true);
+        extend_template_attribute(&mut result.template, "class", " ", &classes);
+    }
+    if !static_styles.is_empty() {
+        let styles = escape_html(&static_styles.join("; "), true);
+        extend_template_attribute(&mut result.template, "style", "; ", &styles);
+    }
+
+    // Handle {...props} spreads: a single call with all spread props merged
+    if let Some(index) = spread_index {
+        let elem_id = elem_id
+            .as_deref()
+            .expect("Spread attributes require an element id");
+        if !running_object.is_empty() {
+            spread_args.push(format!("{{ {} }}", running_object.join(", ")));
+        }
+        // Reactive sources are resolved by `mergeProps`, even on their own
+        let props = match spread_args.as_slice() {
+            [single] if !dynamic_spread => single.clone(),
+            args => {
+                let merge = options.merge_props_helper();
+                context.register_helper(merge);
+                format!("_${}({})", merge, args.join(", "))
+            }
+        };
+        context.register_helper("spread");
+        result.exprs.insert(
+            index,
+            Expr {
+                code: format!(
+                    "_$spread({}, {}, {}, {})",
+                    elem_id,
+                    props,
+                    result.is_svg,
+                    !element.children.is_empty()
+                ),
+            },
+        );
+    }
+}
+
+/// A spread argument as a `mergeProps` source, and whether it is reactive.
+/// `{...getProps()}` passes the getter and `{...props.attrs}` an accessor, so
+/// the spread re-runs when they change.
+fn spread_source(expr: &Expression, marks: &StaticMarks) -> (String, bool) {
+    match expr {
+        Expression::Identifier(_) => (expr_to_string(expr), false),
+        Expression::CallExpression(call)
+            if call.arguments.is_empty()
+                && !matches!(
+                    call.callee,
+                    Expression::CallExpression(_)
+                        | Expression::StaticMemberExpression(_)
+                        | Expression::ComputedMemberExpression(_)
+                ) =>
+        {
+            (expr_to_string(&call.callee), true)
+        }
+        _ if is_dynamic(expr, marks) => (format!("() => {}", expr_to_string(expr)), true),
+        _ => (expr_to_string(expr), false),
+    }
+}
+
+/// Combine `class`, `className` and `classList` on one element.
+///
+/// The static classes go in the template, and a single binding sets the whole
+/// class, so the attributes can't overwrite each other's classes.
+fn transform_merged_class(
+    attrs: &[&JSXAttribute],
+    elem_id: Option<&str>,
+    result: &mut TransformResult,
+) {
+    let mut static_classes = vec![];
+    let mut dynamic = vec![];
+    for attr in attrs {
+        match &attr.value {
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                let classes = lit.value.trim();
+                if !classes.is_empty() {
+                    static_classes.push(classes.to_string());
+                }
             }
+            Some(JSXAttributeValue::ExpressionContainer(container)) => {
+                let Some(expr) = container.expression.as_expression() else {
+                    continue;
+                };
+                if get_attr_name(&attr.name) != "classList" {
+                    dynamic.push(format!("{} || \"\"", expr_to_string(expr)));
+                } else if let Some(conditions) = class_list_conditions(expr) {
+                    dynamic.extend(conditions);
+                } else {
+                    dynamic.push(format!(
+                        "Object.entries({} || {{}}).filter((e) => e[1]).map((e) => e[0]).join(\" \")",
+                        expr_to_string(expr)
+                    ));
+                }
+            }
+            _ => {}
         }
     }
+
+    let static_classes = static_classes.join(" ");
+    if !static_classes.is_empty() {
+        result.template.push_str(&format!(
+            " class=\"{}\"",
+            escape_html(&static_classes, true)
+        ));
+    }
+    if dynamic.is_empty() {
+        return;
+    }
+    let elem_id = elem_id.expect("class bindings require an element id");
+    result.dynamics.push(DynamicBinding {
+        elem: elem_id.to_string(),
+        key: "class".to_string(),
+        value: class_template_literal(&static_classes, &dynamic),
+        is_svg: result.is_svg,
+        is_ce: result.has_custom_element,
+        tag_name: result.tag_name.clone().unwrap_or_default(),
+    });
+}
+
+/// Check if an attribute after a spread is passed through the spread's props
+fn can_native_spread(key: &str) -> bool {
+    if key == "ref" {
+        return false;
+    }
+    !key.split_once(':')
+        .is_some_and(|(namespace, _)| NON_SPREAD_NAMESPACES.contains(namespace))
+}
+
+/// An attribute as a property of a spread's props object
+fn spread_prop(attr: &JSXAttribute, key: &str, marks: &StaticMarks) -> Option<String> {
+    match &attr.value {
+        Some(JSXAttributeValue::StringLiteral(lit)) => {
+            let value = lit
+                .value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            Some(format!("\"{}\": \"{}\"", key, value))
+        }
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            let expr = container.expression.as_expression()?;
+            let expr_str = expr_to_string(expr);
+            // Handlers are attached once, so they aren't read through a getter
+            if is_dynamic(expr, marks) && !is_event_attr(key) {
+                Some(format!("get \"{}\"() {{ return {}; }}", key, expr_str))
+            } else {
+                Some(format!("\"{}\": {}", key, expr_str))
+            }
+        }
+        None => Some(format!("\"{}\": true", key)),
+        _ => None,
+    }
 }
 
 /// Transform a single attribute
@@ -211,7 +620,7 @@ fn transform_attribute<'a>(
     attr: &JSXAttribute<'a>,
     elem_id: Option<&str>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
 ) {
     let key = get_attr_name(&attr.name);
@@ -223,7 +632,7 @@ fn transform_attribute<'a>(
         return;
     }
 
-    if key.starts_with("on") {
+    if is_event_attr(&key) {
         let elem_id = elem_id.expect("event handlers require an element id");
         transform_event(attr, &key, elem_id, result, context, options);
         return;
@@ -238,27 +647,50 @@ fn transform_attribute<'a>(
     // Handle prop: prefix - direct DOM property assignment
     if key.starts_with("prop:") {
         let elem_id = elem_id.expect("prop: requires an element id");
-        transform_prop(attr, &key, elem_id, result, context);
+        transform_prop(attr, &key, elem_id, result, context, options);
         return;
     }
 
     // Handle attr: prefix - force attribute mode
     if key.starts_with("attr:") {
         let elem_id = elem_id.expect("attr: requires an element id");
-        transform_attr(attr, &key, elem_id, result, context);
+        transform_attr(attr, &key, elem_id, result, context, options);
+        return;
+    }
+
+    // Handle bool: prefix - toggle the attribute on truthiness
+    if let Some(name) = key.strip_prefix("bool:") {
+        transform_bool(attr, name, elem_id, result, context, options);
         return;
     }
 
     // Handle style attribute specially
     if key == "style" {
-        transform_style(attr, elem_id, result, context);
+        transform_style(attr, elem_id, result, context, options);
         return;
     }
 
+    // Boolean attributes are present while truthy. Those with a property of the
+    // same name keep setting it, as the property reflects the attribute
+    if options.is_boolean_attribute(&key) {
+        if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
+            if let Some(Expression::BooleanLiteral(lit)) = container.expression.as_expression() {
+                if lit.value {
+                    result.template.push_str(&format!(" {}", key));
+                }
+                return;
+            }
+            if result.is_svg || !PROPERTIES.contains(key.as_str()) {
+                transform_bool(attr, &key, elem_id, result, context, options);
+                return;
+            }
+        }
+    }
+
     // Handle innerHTML/textContent
-    if key == "innerHTML" || key == "textContent" {
+    if key == "innerHTML" || key == "textContent" || key == "innerText" {
         let elem_id = elem_id.expect("inner content requires an element id");
-        transform_inner_content(attr, &key, elem_id, result, context);
+        transform_inner_content(attr, &key, elem_id, result, context, options);
         return;
     }
 
@@ -275,8 +707,17 @@ fn transform_attribute<'a>(
         Some(JSXAttributeValue::ExpressionContainer(container)) => {
             // Dynamic attribute - needs effect
             if let Some(expr) = container.expression.as_expression() {
-                let expr_str = expr_to_string(expr);
-                if is_dynamic(expr) {
+                // Folded at build time, e.g. `{"btn-" + "primary"}`
+                if let Some(value) = constant_text(expr) {
+                    let attr_key = ALIASES.get(key.as_str()).copied().unwrap_or(key.as_str());
+                    let escaped = escape_html(&value, true);
+                    result
+                        .template
+                        .push_str(&format!(" {}=\"{}\"", attr_key, escaped));
+                    return;
+                }
+                let expr_str = attribute_value_code(expr, context, options);
+                if is_dynamic(expr, &context.module.static_marks) {
                     // Dynamic - wrap in effect
                     let elem_id = elem_id.expect("dynamic attributes require an element id");
                     result.dynamics.push(DynamicBinding {
@@ -288,20 +729,28 @@ fn transform_attribute<'a>(
                         tag_name: result.tag_name.clone().unwrap_or_default(),
                     });
                 } else {
-                    // Static expression - we need to evaluate it at build time
-                    // For now, treat as dynamic to be safe
+                    // Static - set once when the element is created
                     let elem_id = elem_id.expect("expression attributes require an element id");
-                    result.dynamics.push(DynamicBinding {
-                        elem: elem_id.to_string(),
-                        key: key.clone(),
-                        value: expr_str,
-                        is_svg: result.is_svg,
-                        is_ce: result.has_custom_element,
-                        tag_name: result.tag_name.clone().unwrap_or_default(),
+                    if let Some(helper) = set_attr_helper(&key) {
+                        context.register_helper(helper);
+                    }
+                    result.exprs.push(Expr {
+                        code: generate_set_attr(&DynamicBinding {
+                            elem: elem_id.to_string(),
+                            key: key.clone(),
+                            value: expr_str,
+                            is_svg: result.is_svg,
+                            is_ce: result.has_custom_element,
+                            tag_name: result.tag_name.clone().unwrap_or_default(),
+                        }),
                     });
                 }
             }
         }
+        None if is_enumerated_attr(&key) || key.starts_with("aria-") => {
+            // `<div draggable>` means true; an empty value is not "true"
+            result.template.push_str(&format!(" {}=\"true\"", key));
+        }
         None => {
             // Boolean attribute (e.g., disabled)
             result.template.push_str(&format!(" {}", key));
@@ -311,48 +760,113 @@ fn transform_attribute<'a>(
 }
 
 /// Transform ref attribute
+///
+/// Several `ref` attributes, and refs from spreads, are applied in source order.
 fn transform_ref<'a>(
     attr: &JSXAttribute<'a>,
     elem_id: &str,
     result: &mut TransformResult,
-    _context: &BlockContext,
+    context: &mut BlockContext,
 ) {
     if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
         if let Some(expr) = container.expression.as_expression() {
-            let ref_expr = expr_to_string(expr);
-            // Check if it's a function expression (arrow function or function expression)
-            if ref_expr.contains("=>") || ref_expr.starts_with("function") {
-                // It's an inline callback: ref={el => myRef = el}
-                // Just invoke it with the element
-                result.exprs.push(Expr {
-                    code: format!("({})({})", ref_expr, elem_id),
-                });
-            } else {
-                // It's a variable reference: ref={myRef}
-                // Could be a signal setter or plain variable - check at runtime
-                result.exprs.push(Expr {
-                    code: format!(
-                        "typeof {} === \"function\" ? {}({}) : {} = {}",
-                        ref_expr, ref_expr, elem_id, ref_expr, elem_id
-                    ),
-                });
+            for code in ref_statements(expr, elem_id, false, context) {
+                result.exprs.push(Expr { code });
             }
         }
     }
 }
 
+/// Statements applying a ref value to `target`, in order.
+///
+/// `ref={[a, setB]}` applies each entry in turn. An element is passed to ref
+/// functions through `use`, so they run untracked; `forward` is set for the
+/// `ref(r$)` method of a component, which calls them directly.
+pub(crate) fn ref_statements(
+    expr: &Expression,
+    target: &str,
+    forward: bool,
+    context: &mut BlockContext,
+) -> Vec<String> {
+    let expr = expr.get_inner_expression();
+    let ref_expr = expr_to_string(expr);
+    match expr {
+        Expression::ArrayExpression(array) => {
+            let mut statements = vec![];
+            for element in &array.elements {
+                match element {
+                    ArrayExpressionElement::SpreadElement(spread) => {
+                        let item = context.generate_uid("ref$");
+                        let call = ref_call(&item, target, forward, context);
+                        statements.push(format!(
+                            "for (const {} of {}) typeof {} === \"function\" && {}",
+                            item,
+                            expr_to_string(&spread.argument),
+                            item,
+                            call
+                        ));
+                    }
+                    ArrayExpressionElement::Elision(_) => {}
+                    element => {
+                        if let Some(expr) = element.as_expression() {
+                            statements.extend(ref_statements(expr, target, forward, context));
+                        }
+                    }
+                }
+            }
+            statements
+        }
+        // An inline callback: ref={el => myRef = el}
+        Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_) => {
+            vec![format!("({})({})", ref_expr, target)]
+        }
+        // An assignable binding: ref={myRef}. It may hold a callback, such as a
+        // signal setter, and is read once
+        Expression::Identifier(_)
+        | Expression::StaticMemberExpression(_)
+        | Expression::ComputedMemberExpression(_) => {
+            let value = context.generate_uid("ref$");
+            let call = ref_call(&value, target, forward, context);
+            vec![format!(
+                "var {} = {}; typeof {} === \"function\" ? {} : {} = {}",
+                value, ref_expr, value, call, ref_expr, target
+            )]
+        }
+        // Anything else is evaluated once and called if it's a function
+        _ => {
+            let value = context.generate_uid("ref$");
+            let call = ref_call(&value, target, forward, context);
+            vec![format!(
+                "var {} = {}; typeof {} === \"function\" && {}",
+                value, ref_expr, value, call
+            )]
+        }
+    }
+}
+
+/// The call passing `target` to the ref function `value`
+fn ref_call(value: &str, target: &str, forward: bool, context: &mut BlockContext) -> String {
+    if forward {
+        format!("{}({})", value, target)
+    } else {
+        context.register_helper("use");
+        format!("_$use({}, {})", value, target)
+    }
+}
+
 /// Transform event handler
 fn transform_event<'a>(
     attr: &JSXAttribute<'a>,
     key: &str,
     elem_id: &str,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
 ) {
-    // Check for capture mode (onClickCapture -> click with capture=true)
-    let is_capture = key.ends_with("Capture");
-    let base_key = if is_capture {
+    // Check for capture mode (onClickCapture or oncapture:click -> click with capture=true)
+    let namespaced_capture = key.starts_with("oncapture:");
+    let is_capture = namespaced_capture || key.ends_with("Capture");
+    let base_key = if is_capture && !namespaced_capture {
         &key[..key.len() - 7] // Remove "Capture" suffix
     } else {
         key
@@ -361,53 +875,87 @@ fn transform_event<'a>(
     let event_name = to_event_name(base_key);
 
     // Get the handler expression
-    let handler = if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
-        container
-            .expression
-            .as_expression()
-            .map(|e| expr_to_string(e))
-            .unwrap_or_else(|| "undefined".to_string())
-    } else {
-        "undefined".to_string()
+    let expr = match &attr.value {
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            container.expression.as_expression()
+        }
+        _ => None,
+    };
+    // `[handler, data]` calls the handler with the data as its first argument
+    let (handler, data) = match expr.and_then(bound_handler) {
+        Some((handler, data)) => (handler, Some(data)),
+        None => (
+            expr.map(expr_to_string)
+                .unwrap_or_else(|| "undefined".to_string()),
+            None,
+        ),
     };
 
-    // on: prefix forces non-delegation (direct addEventListener)
-    let force_no_delegate = key.starts_with("on:");
+    // on: and oncapture: prefixes force non-delegation (direct addEventListener)
+    let force_no_delegate = key.starts_with("on:") || namespaced_capture;
 
     // Capture events cannot be delegated
     // Check if this event should be delegated
     let should_delegate = !force_no_delegate
         && !is_capture
         && options.delegate_events
-        && (DELEGATED_EVENTS.contains(event_name.as_str())
-            || options.delegated_events.contains(&event_name.as_str()));
+        && options.is_delegated_event(&event_name);
 
     if should_delegate {
         context.register_delegate(&event_name);
+        // Only delegated events can be replayed once hydrated
+        result.has_hydratable_event |= options.hydratable;
         result.exprs.push(Expr {
             code: format!("{}.$${} = {}", elem_id, event_name, handler),
         });
+        if let Some(data) = data {
+            result.exprs.push(Expr {
+                code: format!("{}.$${}Data = {}", elem_id, event_name, data),
+            });
+        }
+    } else if let Some(data) = data {
+        result.exprs.push(Expr {
+            code: format!(
+                "{}.addEventListener(\"{}\", e => ({})({}, e), {})",
+                elem_id, event_name, handler, data, is_capture
+            ),
+        });
     } else {
         context.register_helper("addEventListener");
         result.exprs.push(Expr {
             code: format!(
-                "addEventListener({}, \"{}\", {}, {})",
+                "_$addEventListener({}, \"{}\", {}, {})",
                 elem_id, event_name, handler, is_capture
             ),
         });
     }
 }
 
+/// The handler and data of a `[handler, data]` event binding
+fn bound_handler(expr: &Expression) -> Option<(String, String)> {
+    let Expression::ArrayExpression(array) = expr else {
+        return None;
+    };
+    match array.elements.as_slice() {
+        [handler, data] => Some((
+            expr_to_string(handler.as_expression()?),
+            expr_to_string(data.as_expression()?),
+        )),
+        _ => None,
+    }
+}
+
 /// Transform use: directive
 fn transform_directive<'a>(
     attr: &JSXAttribute<'a>,
     key: &str,
     elem_id: &str,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
 ) {
     context.register_helper("use");
     let directive_name = &key[4..]; // Strip "use:"
+    context.register_directive(directive_name);
 
     let value = if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
         container
@@ -420,34 +968,210 @@ fn transform_directive<'a>(
     };
 
     result.exprs.push(Expr {
-        code: format!("use({}, {}, {})", directive_name, elem_id, value),
+        code: format!("_$use({}, {}, {})", directive_name, elem_id, value),
     });
 }
 
+/// Drop the quotes around the attribute values of an opening tag where HTML
+/// allows it: `class="btn"` becomes `class=btn`. Values are escaped, so the
+/// next quote always ends a value.
+fn unquote_attributes(tag: &str) -> String {
+    let mut unquoted = String::with_capacity(tag.len());
+    let mut rest = tag;
+    while let Some(start) = rest.find("=\"") {
+        let value_start = start + 2;
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let value = &rest[value_start..value_start + len];
+        unquoted.push_str(&rest[..=start]);
+        let needs_quotes = value.is_empty()
+            || value.contains(|c: char| c.is_ascii_whitespace() || "'=<>`".contains(c));
+        if needs_quotes {
+            unquoted.push_str(&format!("\"{}\"", value));
+        } else {
+            unquoted.push_str(value);
+        }
+        rest = &rest[value_start + len + 1..];
+    }
+    unquoted.push_str(rest);
+    unquoted
+}
+
+/// Add to an attribute of the opening tag being built, or add the attribute
+fn extend_template_attribute(template: &mut String, name: &str, separator: &str, value: &str) {
+    let prefix = format!(" {}=\"", name);
+    match template.find(&prefix) {
+        Some(start) => {
+            let value_start = start + prefix.len();
+            let value_end = value_start + template[value_start..].find('"').unwrap_or(0);
+            let existing = template[value_start..value_end].trim_end_matches([' ', ';']);
+            let end = value_start + existing.len();
+            let separator = if existing.is_empty() { "" } else { separator };
+            template.replace_range(end..value_end, &format!("{}{}", separator, value));
+        }
+        None => template.push_str(&format!("{}{}\"", prefix, value)),
+    }
+}
+
+/// The value of a `style:` attribute when it is known at build time
+fn static_style_value(attr: &JSXAttribute) -> Option<String> {
+    match &attr.value {
+        Some(JSXAttributeValue::StringLiteral(lit)) => Some(lit.value.to_string()),
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            constant_text(container.expression.as_expression()?)
+        }
+        _ => None,
+    }
+}
+
+/// Transform style: prefix (set a single style property)
+fn transform_style_property<'a>(
+    attr: &JSXAttribute<'a>,
+    name: &str,
+    elem_id: &str,
+    result: &mut TransformResult,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
+) {
+    let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value else {
+        return;
+    };
+    let Some(expr) = container.expression.as_expression() else {
+        return;
+    };
+    let expr_str = expr_to_string(expr);
+    let set_property =
+        |value: &str| format!("{}.style.setProperty(\"{}\", {})", elem_id, name, value);
+    let code = if is_dynamic(expr, &context.module.static_marks) {
+        context.register_helper("effect");
+        options.effect_code(&expr_str, set_property)
+    } else {
+        set_property(&expr_str)
+    };
+    result.exprs.push(Expr { code });
+}
+
+/// Whether an expression always evaluates to a string
+fn is_text_expression(expr: &Expression) -> bool {
+    match expr {
+        Expression::TemplateLiteral(_) | Expression::StringLiteral(_) => true,
+        Expression::ParenthesizedExpression(paren) => is_text_expression(&paren.expression),
+        // Adding anything to a string concatenates
+        Expression::BinaryExpression(binary) if binary.operator == BinaryOperator::Addition => {
+            is_text_expression(&binary.left) || is_text_expression(&binary.right)
+        }
+        Expression::UnaryExpression(unary) => unary.operator == UnaryOperator::Typeof,
+        Expression::CallExpression(call) => matches!(
+            &call.callee,
+            Expression::Identifier(ident) if ident.name == "String"
+        ),
+        _ => false,
+    }
+}
+
+/// Whether an expression child can share an insert with its neighbours: a
+/// dynamic read, call or string. Conditionals keep their own insert so
+/// `wrapConditionals` can memoize their test, and compiled JSX keeps its own so
+/// it isn't recreated whenever a neighbour changes.
+fn is_mergeable_child(expr: &Expression, marks: &StaticMarks) -> bool {
+    if !is_dynamic(expr, marks) {
+        return false;
+    }
+    match expr.without_parentheses() {
+        Expression::Identifier(_)
+        | Expression::StaticMemberExpression(_)
+        | Expression::ComputedMemberExpression(_) => true,
+        Expression::CallExpression(call) => match call.callee.without_parentheses() {
+            Expression::Identifier(ident) => !ident.name.starts_with("_$"),
+            callee => callee.is_member_expression(),
+        },
+        expr => is_text_expression(expr),
+    }
+}
+
+/// Whether a `class:` attribute is always on or always off (`class:active`,
+/// `class:active={false}`)
+fn static_class_toggle(attr: &JSXAttribute) -> Option<bool> {
+    match &attr.value {
+        None => Some(true),
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            match container.expression.as_expression()? {
+                Expression::BooleanLiteral(lit) => Some(lit.value),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Transform class: prefix (toggle a single class)
+fn transform_class_toggle<'a>(
+    attr: &JSXAttribute<'a>,
+    name: &str,
+    elem_id: &str,
+    result: &mut TransformResult,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
+) {
+    let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value else {
+        return;
+    };
+    let Some(expr) = container.expression.as_expression() else {
+        return;
+    };
+    let expr_str = expr_to_string(expr);
+    let toggle = |value: &str| format!("{}.classList.toggle(\"{}\", {})", elem_id, name, value);
+    let code = if is_dynamic(expr, &context.module.static_marks) {
+        context.register_helper("effect");
+        options.effect_code(&expr_str, toggle)
+    } else {
+        toggle(&expr_str)
+    };
+    result.exprs.push(Expr { code });
+}
+
 /// Transform prop: prefix (direct DOM property assignment)
 fn transform_prop<'a>(
     attr: &JSXAttribute<'a>,
     key: &str,
     elem_id: &str,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
 ) {
     let prop_name = &key[5..]; // Strip "prop:"
 
-    if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
-        if let Some(expr) = container.expression.as_expression() {
-            let expr_str = expr_to_string(expr);
-            if is_dynamic(expr) {
-                context.register_helper("effect");
-                result.exprs.push(Expr {
-                    code: format!("effect(() => {}.{} = {})", elem_id, prop_name, expr_str),
-                });
-            } else {
-                result.exprs.push(Expr {
-                    code: format!("{}.{} = {}", elem_id, prop_name, expr_str),
-                });
+    match &attr.value {
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            if let Some(expr) = container.expression.as_expression() {
+                let expr_str = expr_to_string(expr);
+                if is_dynamic(expr, &context.module.static_marks) {
+                    context.register_helper("effect");
+                    result.exprs.push(Expr {
+                        code: options.effect_code(&expr_str, |value| {
+                            format!("{}.{} = {}", elem_id, prop_name, value)
+                        }),
+                    });
+                } else {
+                    result.exprs.push(Expr {
+                        code: format!("{}.{} = {}", elem_id, prop_name, expr_str),
+                    });
+                }
             }
         }
+        Some(JSXAttributeValue::StringLiteral(lit)) => {
+            result.exprs.push(Expr {
+                code: format!("{}.{} = {}", elem_id, prop_name, json_string(&lit.value)),
+            });
+        }
+        None => {
+            // `<input prop:checked />` means true
+            result.exprs.push(Expr {
+                code: format!("{}.{} = true", elem_id, prop_name),
+            });
+        }
+        _ => {}
     }
 }
 
@@ -457,37 +1181,105 @@ fn transform_attr<'a>(
     key: &str,
     elem_id: &str,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
 ) {
     let attr_name = &key[5..]; // Strip "attr:"
 
-    if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
-        if let Some(expr) = container.expression.as_expression() {
+    match &attr.value {
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            let Some(expr) = container.expression.as_expression() else {
+                return;
+            };
+            if let Some(value) = constant_text(expr) {
+                result.template.push_str(&format!(
+                    " {}=\"{}\"",
+                    attr_name,
+                    escape_html(&value, true)
+                ));
+                return;
+            }
             let expr_str = expr_to_string(expr);
-            context.register_helper("effect");
             context.register_helper("setAttribute");
-            result.exprs.push(Expr {
-                code: format!(
-                    "effect(() => {}.setAttribute(\"{}\", {}))",
-                    elem_id, attr_name, expr_str
-                ),
-            });
+            let set =
+                |value: &str| format!("_$setAttribute({}, \"{}\", {})", elem_id, attr_name, value);
+            let code = if is_dynamic(expr, &context.module.static_marks) {
+                context.register_helper("effect");
+                options.effect_code(&expr_str, set)
+            } else {
+                set(&expr_str)
+            };
+            result.exprs.push(Expr { code });
         }
-    } else if let Some(JSXAttributeValue::StringLiteral(lit)) = &attr.value {
-        // Static value - inline in template
-        let escaped = escape_html(&lit.value, true);
-        result
-            .template
-            .push_str(&format!(" {}=\"{}\"", attr_name, escaped));
+        Some(JSXAttributeValue::StringLiteral(lit)) => {
+            // Static value - inline in template
+            let escaped = escape_html(&lit.value, true);
+            result
+                .template
+                .push_str(&format!(" {}=\"{}\"", attr_name, escaped));
+        }
+        None => {
+            result.template.push_str(&format!(" {}", attr_name));
+        }
+        _ => {}
     }
 }
 
+/// Transform bool: prefix and boolean attributes (attribute present while the
+/// value is truthy)
+fn transform_bool<'a>(
+    attr: &JSXAttribute<'a>,
+    attr_name: &str,
+    elem_id: Option<&str>,
+    result: &mut TransformResult,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
+) {
+    let expr = match &attr.value {
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            match container.expression.as_expression() {
+                Some(expr) => expr,
+                None => return,
+            }
+        }
+        Some(JSXAttributeValue::StringLiteral(lit)) => {
+            // Only the empty string is falsy
+            if !lit.value.is_empty() {
+                result.template.push_str(&format!(" {}", attr_name));
+            }
+            return;
+        }
+        None => {
+            result.template.push_str(&format!(" {}", attr_name));
+            return;
+        }
+        _ => return,
+    };
+
+    let elem_id = elem_id.expect("bool: requires an element id");
+    let expr_str = expr_to_string(expr);
+    let toggle = |value: &str| {
+        format!(
+            "{}.toggleAttribute(\"{}\", !!({}))",
+            elem_id, attr_name, value
+        )
+    };
+    let code = if is_dynamic(expr, &context.module.static_marks) {
+        context.register_helper("effect");
+        options.effect_code(&expr_str, toggle)
+    } else {
+        toggle(&expr_str)
+    };
+    result.exprs.push(Expr { code });
+}
+
 /// Transform style attribute
 fn transform_style<'a>(
     attr: &JSXAttribute<'a>,
     elem_id: Option<&str>,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
 ) {
     match &attr.value {
         Some(JSXAttributeValue::StringLiteral(lit)) => {
@@ -498,6 +1290,13 @@ fn transform_style<'a>(
         }
         Some(JSXAttributeValue::ExpressionContainer(container)) => {
             if let Some(expr) = container.expression.as_expression() {
+                // A constant string, e.g. `{"color: " + "red"}`
+                if let Some(style_str) = constant_text(expr) {
+                    result
+                        .template
+                        .push_str(&format!(" style=\"{}\"", escape_html(&style_str, true)));
+                    return;
+                }
                 let expr_str = expr_to_string(expr);
 
                 // Check if it's an object expression (static object)
@@ -509,19 +1308,26 @@ fn transform_style<'a>(
                             .push_str(&format!(" style=\"{}\"", style_str));
                         return;
                     }
+
+                    let elem_id = elem_id.expect("style properties require an element id");
+                    if transform_custom_properties(obj, elem_id, result, context, options) {
+                        return;
+                    }
                 }
 
                 // Dynamic style - use style helper
                 let elem_id = elem_id.expect("style helper requires an element id");
                 context.register_helper("style");
-                if is_dynamic(expr) {
+                if is_dynamic(expr, &context.module.static_marks) {
                     context.register_helper("effect");
                     result.exprs.push(Expr {
-                        code: format!("effect(() => style({}, {}))", elem_id, expr_str),
+                        code: options.effect_prev_code(&expr_str, |value, prev| {
+                            format!("_$style({}, {}, {})", elem_id, value, prev)
+                        }),
                     });
                 } else {
                     result.exprs.push(Expr {
-                        code: format!("style({}, {})", elem_id, expr_str),
+                        code: format!("_$style({}, {})", elem_id, expr_str),
                     });
                 }
             }
@@ -531,148 +1337,152 @@ fn transform_style<'a>(
     }
 }
 
-/// Try to convert a static object expression to a style string
-fn object_to_style_string(obj: &oxc_ast::ast::ObjectExpression) -> Option<String> {
-    let mut styles = Vec::new();
-
+/// Set CSS custom properties (`--brand-color`) from a style object with
+/// `style.setProperty`, passing the remaining properties to the `style` helper.
+///
+/// Returns `false` when the object has no custom properties, or keys that aren't
+/// known at compile time.
+fn transform_custom_properties(
+    obj: &oxc_ast::ast::ObjectExpression,
+    elem_id: &str,
+    result: &mut TransformResult,
+    context: &mut BlockContext,
+    options: &TransformOptions,
+) -> bool {
+    let mut custom = vec![];
+    let mut rest = vec![];
     for prop in &obj.properties {
-        if let oxc_ast::ast::ObjectPropertyKind::ObjectProperty(prop) = prop {
-            // Get key
-            let key = match &prop.key {
-                oxc_ast::ast::PropertyKey::StaticIdentifier(id) => {
-                    // Convert camelCase to kebab-case
-                    camel_to_kebab(&id.name)
-                }
-                oxc_ast::ast::PropertyKey::StringLiteral(lit) => lit.value.to_string(),
-                _ => return None, // Dynamic key, can't inline
-            };
-
-            // Get value - must be a static literal
-            let value = match &prop.value {
-                oxc_ast::ast::Expression::StringLiteral(lit) => lit.value.to_string(),
-                oxc_ast::ast::Expression::NumericLiteral(num) => {
-                    // Add px for numeric values (except certain properties)
-                    let num_str = num.value.to_string();
-                    if needs_px_suffix(&key) && num.value != 0.0 {
-                        format!("{}px", num_str)
-                    } else {
-                        num_str
-                    }
-                }
-                _ => return None, // Dynamic value, can't inline
-            };
-
-            styles.push(format!("{}: {}", key, value));
+        let oxc_ast::ast::ObjectPropertyKind::ObjectProperty(prop) = prop else {
+            return false;
+        };
+        if prop.computed || prop.method {
+            return false;
+        }
+        let key = match &prop.key {
+            oxc_ast::ast::PropertyKey::StaticIdentifier(id) => id.name.to_string(),
+            oxc_ast::ast::PropertyKey::StringLiteral(lit) => lit.value.to_string(),
+            _ => return false,
+        };
+        if key.starts_with("--") {
+            custom.push((key, &prop.value));
         } else {
-            return None; // Spread or method, can't inline
+            rest.push((key, &prop.value));
         }
     }
+    if custom.is_empty() {
+        return false;
+    }
 
-    Some(styles.join("; "))
-}
-
-/// Convert camelCase to kebab-case
-fn camel_to_kebab(s: &str) -> String {
-    let mut result = String::new();
-    for (i, c) in s.chars().enumerate() {
-        if c.is_uppercase() {
-            if i > 0 {
-                result.push('-');
-            }
-            result.push(c.to_lowercase().next().unwrap());
+    if !rest.is_empty() {
+        let props: Vec<String> = rest
+            .iter()
+            .map(|(key, value)| format!("\"{}\": {}", key, expr_to_string(value)))
+            .collect();
+        let object = format!("{{ {} }}", props.join(", "));
+        context.register_helper("style");
+        if rest
+            .iter()
+            .any(|(_, value)| is_dynamic(value, &context.module.static_marks))
+        {
+            context.register_helper("effect");
+            result.exprs.push(Expr {
+                code: options.effect_prev_code(&object, |value, prev| {
+                    format!("_$style({}, {}, {})", elem_id, value, prev)
+                }),
+            });
         } else {
-            result.push(c);
+            result.exprs.push(Expr {
+                code: format!("_$style({}, {})", elem_id, object),
+            });
         }
     }
-    result
+
+    // The name is passed through as written; custom properties are case-sensitive
+    for (key, value) in custom {
+        let set_property =
+            |value: &str| format!("{}.style.setProperty(\"{}\", {})", elem_id, key, value);
+        let code = if is_dynamic(value, &context.module.static_marks) {
+            context.register_helper("effect");
+            options.effect_code(&expr_to_string(value), set_property)
+        } else {
+            set_property(&expr_to_string(value))
+        };
+        result.exprs.push(Expr { code });
+    }
+    true
 }
 
-/// Check if a CSS property needs px suffix for numeric values
-fn needs_px_suffix(prop: &str) -> bool {
-    // Properties that don't need px suffix
-    let unitless = [
-        "animation-iteration-count",
-        "border-image-outset",
-        "border-image-slice",
-        "border-image-width",
-        "box-flex",
-        "box-flex-group",
-        "box-ordinal-group",
-        "column-count",
-        "columns",
-        "flex",
-        "flex-grow",
-        "flex-positive",
-        "flex-shrink",
-        "flex-negative",
-        "flex-order",
-        "grid-row",
-        "grid-row-end",
-        "grid-row-span",
-        "grid-row-start",
-        "grid-column",
-        "grid-column-end",
-        "grid-column-span",
-        "grid-column-start",
-        "font-weight",
-        "line-clamp",
-        "line-height",
-        "opacity",
-        "order",
-        "orphans",
-        "tab-size",
-        "widows",
-        "z-index",
-        "zoom",
-        "fill-opacity",
-        "flood-opacity",
-        "stop-opacity",
-        "stroke-dasharray",
-        "stroke-dashoffset",
-        "stroke-miterlimit",
-        "stroke-opacity",
-        "stroke-width",
-    ];
-    !unitless.contains(&prop)
+/// Content set through `innerHTML`, `textContent` or `innerText`, which replaces
+/// the children. Static text goes straight into the template; everything else is
+/// assigned by `transform_inner_content`.
+fn transform_child_property(
+    element: &JSXElement,
+    property: &JSXAttribute,
+    result: &mut TransformResult,
+    context: &mut BlockContext,
+) {
+    if let Some(diagnostic) = child_property_conflict(element, property) {
+        context.report(diagnostic);
+    }
+    match &property.value {
+        Some(JSXAttributeValue::StringLiteral(lit))
+            if get_attr_name(&property.name) != "innerHTML" =>
+        {
+            let escaped = escape_html(&lit.value, false);
+            result.template.push_str(&escaped);
+            result.template_with_closing_tags.push_str(&escaped);
+        }
+        // A placeholder text node for `textContent` to update
+        Some(JSXAttributeValue::ExpressionContainer(_))
+            if get_attr_name(&property.name) == "textContent" =>
+        {
+            result.template.push(' ');
+            result.template_with_closing_tags.push(' ');
+        }
+        _ => {}
+    }
 }
 
-/// Transform innerHTML/textContent
+/// Transform innerHTML/textContent/innerText
 fn transform_inner_content<'a>(
     attr: &JSXAttribute<'a>,
     key: &str,
     elem_id: &str,
     result: &mut TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
+    options: &TransformOptions<'a>,
 ) {
     if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
         if let Some(expr) = container.expression.as_expression() {
             let expr_str = expr_to_string(expr);
+            // `textContent` updates the text node `transform_child_property`
+            // put in the template instead of replacing it
+            let target = if key == "textContent" {
+                format!("{}.firstChild.data", elem_id)
+            } else {
+                format!("{}.{}", elem_id, key)
+            };
 
-            if is_dynamic(expr) {
+            if is_dynamic(expr, &context.module.static_marks) {
                 context.register_helper("effect");
                 result.exprs.push(Expr {
-                    code: format!("effect(() => {}.{} = {})", elem_id, key, expr_str),
+                    code: options.effect_code(&expr_str, |value| format!("{} = {}", target, value)),
                 });
             } else {
                 result.exprs.push(Expr {
-                    code: format!("{}.{} = {}", elem_id, key, expr_str),
+                    code: format!("{} = {}", target, expr_str),
                 });
             }
         }
     } else if let Some(JSXAttributeValue::StringLiteral(lit)) = &attr.value {
-        // Static string - but we still need to set it at runtime for innerHTML
+        // Static string - but we still need to set it at runtime for innerHTML,
+        // as markup rather than escaped text
         if key == "innerHTML" {
             result.exprs.push(Expr {
-                code: format!(
-                    "{}.innerHTML = \"{}\"",
-                    elem_id,
-                    escape_html(&lit.value, false)
-                ),
+                code: format!("{}.innerHTML = {}", elem_id, json_string(&lit.value)),
             });
-        } else {
-            // textContent can be inlined in template
-            // But the element should have no children then
         }
+        // Static text is inlined in the template by `transform_child_property`
     }
 }
 
@@ -681,7 +1491,7 @@ fn transform_children<'a, 'b>(
     element: &JSXElement<'a>,
     result: &mut TransformResult,
     info: &TransformInfo,
-    context: &BlockContext,
+    context: &mut BlockContext,
     options: &TransformOptions<'a>,
     transform_child: ChildTransformer<'a, 'b>,
 ) {
@@ -703,9 +1513,14 @@ fn transform_children<'a, 'b>(
     }
 
     /// Check if children list is a single dynamic expression (no markers needed)
-    fn is_single_dynamic_child(children: &[oxc_ast::ast::JSXChild<'_>]) -> bool {
+    fn is_single_dynamic_child(
+        children: &[oxc_ast::ast::JSXChild<'_>],
+        marks: &StaticMarks,
+    ) -> bool {
         let mut expr_count = 0;
         let mut other_content = false;
+        // Adjacent expressions merged into one insert count once
+        let mut in_run = false;
 
         for child in children {
             match child {
@@ -713,46 +1528,231 @@ fn transform_children<'a, 'b>(
                     let content = common::expression::trim_whitespace(&text.value);
                     if !content.is_empty() {
                         other_content = true;
+                        in_run = false;
                     }
                 }
-                oxc_ast::ast::JSXChild::Element(_) => {
-                    other_content = true;
+                oxc_ast::ast::JSXChild::Element(element) => {
+                    in_run = false;
+                    if is_component(&get_tag_name(element)) {
+                        expr_count += 1;
+                    } else {
+                        other_content = true;
+                    }
                 }
                 oxc_ast::ast::JSXChild::ExpressionContainer(container) => {
-                    if container.expression.as_expression().is_some() {
-                        expr_count += 1;
+                    match container.expression.as_expression() {
+                        Some(expr) if constant_text(expr).is_some() => {
+                            other_content = true;
+                            in_run = false;
+                        }
+                        Some(expr) => {
+                            let mergeable = is_mergeable_child(expr, marks);
+                            if !(in_run && mergeable) {
+                                expr_count += 1;
+                            }
+                            in_run = mergeable;
+                        }
+                        None => {}
                     }
                 }
+                oxc_ast::ast::JSXChild::Spread(_) => {
+                    in_run = false;
+                    expr_count += 1;
+                }
                 oxc_ast::ast::JSXChild::Fragment(fragment) => {
+                    in_run = false;
                     // Recurse into fragments
-                    if !is_single_dynamic_child(&fragment.children) {
+                    if !is_single_dynamic_child(&fragment.children, marks) {
                         other_content = true;
                     } else {
                         expr_count += 1;
                     }
                 }
-                _ => {}
             }
         }
 
         expr_count == 1 && !other_content
     }
 
+    /// The expressions of the adjacent expression children from `index` that
+    /// share one insert, and the index after the last of them. Empty containers
+    /// and whitespace-only text between them don't break the run.
+    fn mergeable_run<'c, 'a>(
+        children: &'c [JSXChild<'a>],
+        index: usize,
+        marks: &StaticMarks,
+    ) -> Option<(Vec<&'c Expression<'a>>, usize)> {
+        let mut exprs = vec![];
+        let mut end = index;
+        for (offset, child) in children[index..].iter().enumerate() {
+            match child {
+                JSXChild::ExpressionContainer(container) => {
+                    match container.expression.as_expression() {
+                        Some(expr) if is_mergeable_child(expr, marks) => {
+                            exprs.push(expr);
+                            end = index + offset + 1;
+                        }
+                        Some(_) => break,
+                        None => {}
+                    }
+                }
+                JSXChild::Text(text) if trim_whitespace(&text.value).is_empty() => {}
+                _ => break,
+            }
+        }
+        (exprs.len() > 1).then_some((exprs, end))
+    }
+
+    /// The first sibling after `index` that ends up in the output
+    fn next_child<'c, 'a>(children: &'c [JSXChild<'a>], index: usize) -> Option<&'c JSXChild<'a>> {
+        children[index + 1..].iter().find(|child| match child {
+            JSXChild::Text(text) => !trim_whitespace(&text.value).is_empty(),
+            JSXChild::ExpressionContainer(container) => {
+                container.expression.as_expression().is_some()
+            }
+            _ => true,
+        })
+    }
+
+    /// Insert a dynamic child before the node that follows it, like the Babel
+    /// plugin: nothing follows at the end of the parent (`null`), a following
+    /// element is its own marker, and anything else gets a `<!>` placeholder in
+    /// the template. The element case is completed once the element has an id.
+    ///
+    /// Hydratable output always brackets the child with `<!$><!/>`, as the
+    /// server-rendered content between them is adopted rather than recreated.
+    #[allow(clippy::too_many_arguments)]
+    fn push_insert(
+        result: &mut TransformResult,
+        context: &mut BlockContext,
+        parent_id: &str,
+        value: String,
+        next: Option<&JSXChild>,
+        at_end: bool,
+        node_index: &mut usize,
+        pending: &mut Option<PendingInsert>,
+        hydratable: bool,
+    ) {
+        if hydratable {
+            result.template.push_str("<!$><!/>");
+            result.template_with_closing_tags.push_str("<!$><!/>");
+
+            let start_id = context.generate_uid("el$");
+            let marker_id = context.generate_uid("el$");
+            let nodes_id = context.generate_uid("co$");
+            result.declarations.push(Declaration {
+                name: start_id.clone(),
+                init: child_accessor(parent_id, *node_index),
+            });
+            context.register_helper("getNextMarker");
+            result.declarations.push(Declaration {
+                name: format!("[{}, {}]", marker_id, nodes_id),
+                init: format!("_$getNextMarker({}.nextSibling)", start_id),
+            });
+
+            result.exprs.push(Expr {
+                code: format!(
+                    "_$insert({}, {}, {}, {})",
+                    parent_id, value, marker_id, nodes_id
+                ),
+            });
+
+            *node_index += 2;
+            return;
+        }
+        match next {
+            None if at_end => result.exprs.push(Expr {
+                code: format!("_$insert({}, {}, null)", parent_id, value),
+            }),
+            Some(JSXChild::Element(next)) if !is_component(&get_tag_name(next)) => {
+                *pending = Some(PendingInsert {
+                    index: result.exprs.len(),
+                    parent_id: parent_id.to_string(),
+                    value,
+                });
+                result.exprs.push(Expr {
+                    code: String::new(),
+                });
+            }
+            _ => {
+                result.template.push_str("<!>");
+                result.template_with_closing_tags.push_str("<!>");
+
+                let marker_id = context.generate_uid("el$");
+                result.declarations.push(Declaration {
+                    name: marker_id.clone(),
+                    init: child_accessor(parent_id, *node_index),
+                });
+
+                result.exprs.push(Expr {
+                    code: format!("_$insert({}, {}, {})", parent_id, value, marker_id),
+                });
+
+                *node_index += 1;
+            }
+        }
+    }
+
+    /// The last child that puts nodes in the template. An element there may
+    /// leave its closing tag to the HTML parser; an insert only does when a
+    /// marker goes in after it.
+    fn last_template_child(children: &[JSXChild], hydratable: bool) -> Option<usize> {
+        (0..children.len())
+            .rev()
+            .find(|&index| match &children[index] {
+                JSXChild::Text(text) => !trim_whitespace(&text.value).is_empty(),
+                JSXChild::Element(element) if is_component(&get_tag_name(element)) => {
+                    hydratable || next_child(children, index).is_some()
+                }
+                JSXChild::ExpressionContainer(container) => {
+                    match container.expression.as_expression() {
+                        Some(expr) if constant_text(expr).is_none() => {
+                            hydratable || next_child(children, index).is_some()
+                        }
+                        Some(_) => true,
+                        None => false,
+                    }
+                }
+                JSXChild::Spread(_) => hydratable || next_child(children, index).is_some(),
+                _ => true,
+            })
+    }
+
+    /// An insert waiting for the id of the element it goes before
+    struct PendingInsert {
+        index: usize,
+        parent_id: String,
+        value: String,
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn transform_children_list<'a, 'b>(
         children: &[oxc_ast::ast::JSXChild<'a>],
         result: &mut TransformResult,
         info: &TransformInfo,
-        context: &BlockContext,
+        context: &mut BlockContext,
         options: &TransformOptions<'a>,
         transform_child: ChildTransformer<'a, 'b>,
         node_index: &mut usize,
         last_was_text: &mut bool,
         single_dynamic: bool,
+        at_end: bool,
+        last: Option<usize>,
     ) {
-        for child in children {
+        let minify = options.minify_whitespace && !context.preserves_whitespace();
+        let mut pending: Option<PendingInsert> = None;
+        // Children up to this index were merged into an earlier insert
+        let mut merged_until = 0;
+        for (index, child) in children.iter().enumerate() {
+            if index < merged_until {
+                continue;
+            }
             match child {
                 oxc_ast::ast::JSXChild::Text(text) => {
-                    let content = common::expression::trim_whitespace(&text.value);
+                    let mut content = common::expression::trim_whitespace(&text.value);
+                    if minify {
+                        content = trim_around_blocks(&content, children, index);
+                    }
                     if !content.is_empty() {
                         let escaped = escape_html(&content, false);
                         result.template.push_str(&escaped);
@@ -769,7 +1769,7 @@ fn transform_children<'a, 'b>(
                     if is_component(&child_tag) {
                         *last_was_text = false;
                         if let (Some(parent_id), Some(child_result)) =
-                            (result.id.as_deref(), transform_child(child))
+                            (result.id.as_deref(), transform_child(child, context))
                         {
                             if child_result.exprs.is_empty() {
                                 continue;
@@ -781,28 +1781,23 @@ fn transform_children<'a, 'b>(
                             if single_dynamic {
                                 result.exprs.push(Expr {
                                     code: format!(
-                                        "insert({}, {})",
+                                        "_$insert({}, {})",
                                         parent_id, child_result.exprs[0].code
                                     ),
                                 });
                             } else {
-                                result.template.push_str("<!>");
-                                result.template_with_closing_tags.push_str("<!>");
-
-                                let marker_id = context.generate_uid("el$");
-                                result.declarations.push(Declaration {
-                                    name: marker_id.clone(),
-                                    init: child_accessor(parent_id, *node_index),
-                                });
-
-                                result.exprs.push(Expr {
-                                    code: format!(
-                                        "insert({}, {}, {})",
-                                        parent_id, child_result.exprs[0].code, marker_id
-                                    ),
-                                });
-
-                                *node_index += 1;
+                                let parent_id = parent_id.to_string();
+                                push_insert(
+                                    result,
+                                    context,
+                                    &parent_id,
+                                    child_result.exprs[0].code.clone(),
+                                    next_child(children, index),
+                                    at_end,
+                                    node_index,
+                                    &mut pending,
+                                    options.hydratable,
+                                );
                             }
                         }
                         continue;
@@ -811,6 +1806,8 @@ fn transform_children<'a, 'b>(
                     *last_was_text = false;
                     let child_info = TransformInfo {
                         top_level: false,
+                        last_element: last == Some(index),
+                        marker: pending.is_some(),
                         path: child_path(&info.path, *node_index),
                         root_id: info.root_id.clone(),
                         ..info.clone()
@@ -835,53 +1832,153 @@ fn transform_children<'a, 'b>(
                             .template_with_closing_tags
                             .push_str(&child_result.template);
                     }
+                    if let Some(insert) = pending.take() {
+                        let marker_id = child_result
+                            .id
+                            .as_deref()
+                            .expect("Insert markers get an element id");
+                        result.exprs[insert.index].code = format!(
+                            "_$insert({}, {}, {})",
+                            insert.parent_id, insert.value, marker_id
+                        );
+                    }
                     result.declarations.extend(child_result.declarations);
                     result.exprs.extend(child_result.exprs);
                     result.dynamics.extend(child_result.dynamics);
                     result.has_custom_element |= child_result.has_custom_element;
+                    result.has_hydratable_event |= child_result.has_hydratable_event;
 
                     *node_index += 1;
                 }
                 oxc_ast::ast::JSXChild::ExpressionContainer(container) => {
+                    // Constant text is part of the template, like static text
+                    let constant = container.expression.as_expression().and_then(constant_text);
+                    if let Some(content) = constant {
+                        if !content.is_empty() {
+                            let escaped = escape_html(&content, false);
+                            result.template.push_str(&escaped);
+                            result.template_with_closing_tags.push_str(&escaped);
+                            if !*last_was_text {
+                                *node_index += 1;
+                                *last_was_text = true;
+                            }
+                        }
+                        continue;
+                    }
                     if let (Some(parent_id), Some(expr)) =
                         (result.id.as_deref(), container.expression.as_expression())
                     {
                         *last_was_text = false;
+
+                        // Adjacent reads and calls share one insert: joined when
+                        // they're all text, as an array otherwise
+                        let (exprs, end) =
+                            mergeable_run(children, index, &context.module.static_marks)
+                                .unwrap_or_else(|| (vec![expr], index + 1));
+                        merged_until = end;
+                        let text = exprs.iter().all(|expr| {
+                            is_dynamic(expr, &context.module.static_marks)
+                                && is_text_expression(expr)
+                        });
+                        let codes: Vec<String> =
+                            exprs.iter().map(|expr| expr_to_string(expr)).collect();
+                        let code = if text {
+                            codes.join(" + ")
+                        } else if exprs.len() > 1 {
+                            format!("[{}]", codes.join(", "))
+                        } else {
+                            expr_to_string(expr)
+                        };
+
+                        // A sole text child updates a placeholder text node in place
+                        if single_dynamic && text {
+                            result.template.push(' ');
+                            result.template_with_closing_tags.push(' ');
+                            result.dynamics.push(DynamicBinding {
+                                elem: format!("{}.firstChild", parent_id),
+                                key: "textContent".to_string(),
+                                value: code,
+                                is_svg: result.is_svg,
+                                is_ce: result.has_custom_element,
+                                tag_name: result.tag_name.clone().unwrap_or_default(),
+                            });
+                            continue;
+                        }
                         context.register_helper("insert");
 
-                        let expr_str = expr_to_string(expr);
-                        let insert_value = if is_dynamic(expr) {
-                            format!("() => {}", expr_str)
+                        // With `wrapConditionals`, a branch is only recreated when
+                        // its test flips
+                        let memoized = if options.wrap_conditionals && exprs.len() == 1 {
+                            memo_condition_insert(expr, context)
+                        } else {
+                            None
+                        };
+                        let insert_value = if let Some(code) = memoized {
+                            code
+                        } else if exprs
+                            .iter()
+                            .any(|expr| is_dynamic(expr, &context.module.static_marks))
+                        {
+                            format!("() => {}", code)
                         } else {
-                            expr_str
+                            code
                         };
 
                         // Single dynamic child: no marker needed
                         if single_dynamic {
                             result.exprs.push(Expr {
-                                code: format!("insert({}, {})", parent_id, insert_value),
+                                code: format!("_$insert({}, {})", parent_id, insert_value),
                             });
                         } else {
-                            result.template.push_str("<!>");
-                            result.template_with_closing_tags.push_str("<!>");
-
-                            let marker_id = context.generate_uid("el$");
-                            result.declarations.push(Declaration {
-                                name: marker_id.clone(),
-                                init: child_accessor(parent_id, *node_index),
-                            });
-
-                            result.exprs.push(Expr {
-                                code: format!(
-                                    "insert({}, {}, {})",
-                                    parent_id, insert_value, marker_id
-                                ),
-                            });
-
-                            *node_index += 1;
+                            let parent_id = parent_id.to_string();
+                            push_insert(
+                                result,
+                                context,
+                                &parent_id,
+                                insert_value,
+                                next_child(children, end - 1),
+                                at_end,
+                                node_index,
+                                &mut pending,
+                                options.hydratable,
+                            );
                         }
                     }
                 }
+                oxc_ast::ast::JSXChild::Spread(spread) => {
+                    let Some(parent_id) = result.id.as_deref() else {
+                        continue;
+                    };
+                    *last_was_text = false;
+                    context.register_helper("insert");
+
+                    // `{...items}` inserts the array like any other child
+                    let code = expr_to_string(&spread.expression);
+                    let insert_value =
+                        if is_dynamic(&spread.expression, &context.module.static_marks) {
+                            format!("() => {}", code)
+                        } else {
+                            code
+                        };
+                    if single_dynamic {
+                        result.exprs.push(Expr {
+                            code: format!("_$insert({}, {})", parent_id, insert_value),
+                        });
+                    } else {
+                        let parent_id = parent_id.to_string();
+                        push_insert(
+                            result,
+                            context,
+                            &parent_id,
+                            insert_value,
+                            next_child(children, index),
+                            at_end,
+                            node_index,
+                            &mut pending,
+                            options.hydratable,
+                        );
+                    }
+                }
                 oxc_ast::ast::JSXChild::Fragment(fragment) => {
                     transform_children_list(
                         &fragment.children,
@@ -893,16 +1990,17 @@ fn transform_children<'a, 'b>(
                         node_index,
                         last_was_text,
                         single_dynamic,
+                        at_end && next_child(children, index).is_none(),
+                        None,
                     );
                 }
-                _ => {}
             }
         }
     }
 
     let mut node_index = 0usize;
     let mut last_was_text = false;
-    let single_dynamic = is_single_dynamic_child(&element.children);
+    let single_dynamic = is_single_dynamic_child(&element.children, &context.module.static_marks);
     transform_children_list(
         &element.children,
         result,
@@ -913,5 +2011,7 @@ fn transform_children<'a, 'b>(
         &mut node_index,
         &mut last_was_text,
         single_dynamic,
+        true,
+        last_template_child(&element.children, options.hydratable),
     );
 }
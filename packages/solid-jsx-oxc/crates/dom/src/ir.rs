@@ -2,12 +2,13 @@
 //! This IR is used to collect information during traversal
 //! and then generate code in a second pass.
 
+use common::{json_array, json_object, json_string, ModuleState, TransformContext};
 use indexmap::IndexSet;
 use oxc_ast::ast::JSXChild;
-use std::cell::RefCell;
 
 /// Function type for transforming child JSX elements
-pub type ChildTransformer<'a, 'b> = &'b dyn Fn(&JSXChild<'a>) -> Option<TransformResult>;
+pub type ChildTransformer<'a, 'b> =
+    &'b dyn Fn(&JSXChild<'a>, &mut BlockContext) -> Option<TransformResult>;
 
 /// The result of transforming a JSX node
 #[derive(Default)]
@@ -45,7 +46,7 @@ pub struct TransformResult {
     /// The generated element ID
     pub id: Option<String>,
 
-    /// Whether this result is just text
+    /// Whether this result is just text, held unescaped in `template`
     pub text: bool,
 
     /// Whether this result needs memo() wrapping (for fragment expressions)
@@ -53,51 +54,146 @@ pub struct TransformResult {
 
     /// Individual child codes for fragments (when children need to be in an array)
     pub child_codes: Vec<String>,
+
+    /// Whether a delegated event handler is set, which hydration has to replay
+    pub has_hydratable_event: bool,
 }
 
 /// A variable declaration
 pub struct Declaration {
+    /// The variable name (e.g. `_el$2`)
     pub name: String,
+    /// The initializer code (e.g. `_el$1.firstChild`)
     pub init: String,
 }
 
 /// An expression to generate
 pub struct Expr {
+    /// The expression code
     pub code: String,
 }
 
 /// A dynamic attribute binding that needs effect wrapping
+#[derive(Clone)]
 pub struct DynamicBinding {
+    /// The element variable the binding targets
     pub elem: String,
+    /// The attribute or property name as written in JSX
     pub key: String,
+    /// The value expression code
     pub value: String,
+    /// Whether the element is an SVG element
     pub is_svg: bool,
+    /// Whether the element is a custom element
     pub is_ce: bool,
+    /// The element's tag name
     pub tag_name: String,
 }
 
+impl DynamicBinding {
+    /// Whether two bindings compute the same value.
+    ///
+    /// Values are compared as printed code, so formatting differences in the
+    /// source don't matter.
+    pub fn same_value(&self, other: &DynamicBinding) -> bool {
+        self.value == other.value
+    }
+}
+
+impl TransformResult {
+    /// Whether the result is only its template, so a clone of it is all the code needed
+    pub fn is_static(&self) -> bool {
+        self.declarations.is_empty()
+            && self.exprs.is_empty()
+            && self.dynamics.is_empty()
+            && self.post_exprs.is_empty()
+    }
+
+    /// The result as JSON, for the IR dump
+    pub fn to_json(&self) -> String {
+        let exprs = |exprs: &[Expr]| json_array(exprs.iter().map(|expr| json_string(&expr.code)));
+        json_object([
+            ("template", json_string(&self.template)),
+            (
+                "declarations",
+                json_array(self.declarations.iter().map(|decl| {
+                    json_object([
+                        ("name", json_string(&decl.name)),
+                        ("init", json_string(&decl.init)),
+                    ])
+                })),
+            ),
+            ("exprs", exprs(&self.exprs)),
+            (
+                "dynamics",
+                json_array(self.dynamics.iter().map(|binding| {
+                    json_object([
+                        ("elem", json_string(&binding.elem)),
+                        ("key", json_string(&binding.key)),
+                        ("value", json_string(&binding.value)),
+                    ])
+                })),
+            ),
+            ("postExprs", exprs(&self.post_exprs)),
+            ("isSvg", self.is_svg.to_string()),
+        ])
+    }
+
+    /// Values driving more than one dynamic binding, in order of first use.
+    ///
+    /// Plain identifiers are left out, as reading them costs nothing.
+    pub fn shared_values(&self) -> Vec<&str> {
+        let mut shared: Vec<&str> = vec![];
+        for (index, binding) in self.dynamics.iter().enumerate() {
+            let is_identifier = binding
+                .value
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+            if is_identifier || shared.contains(&binding.value.as_str()) {
+                continue;
+            }
+            if self.dynamics[index + 1..]
+                .iter()
+                .any(|other| other.same_value(binding))
+            {
+                shared.push(&binding.value);
+            }
+        }
+        shared
+    }
+}
+
 /// Context for the current block being transformed
+///
+/// Owned by a single transform and passed down as `&mut`, so independent
+/// transforms never share state and can run on separate threads.
 #[derive(Default)]
 pub struct BlockContext {
     /// Current template string being built
-    pub template: RefCell<String>,
+    pub template: String,
 
     /// Templates collected at the file level
-    pub templates: RefCell<Vec<TemplateInfo>>,
+    pub templates: Vec<TemplateInfo>,
 
-    /// Helper imports needed
-    pub helpers: RefCell<IndexSet<String>>,
+    /// Helper imports, generated names, hoisted constants and diagnostics
+    pub module: ModuleState,
 
     /// Delegated events
-    pub delegates: RefCell<IndexSet<String>>,
+    pub delegates: IndexSet<String>,
 
-    /// Variable counter for unique names
-    pub var_counter: RefCell<usize>,
+    /// Names of the `use:` directives used
+    pub directives: IndexSet<String>,
 }
 
+/// A template registered for hoisting to module scope
 pub struct TemplateInfo {
+    /// The template HTML
     pub content: String,
+    /// Whether the template must be created in the SVG namespace
     pub is_svg: bool,
+    /// Whether the template contains custom elements, which must be imported
+    /// into the document rather than cloned
+    pub is_ce: bool,
 }
 
 impl BlockContext {
@@ -105,29 +201,60 @@ impl BlockContext {
         Self::default()
     }
 
-    /// Generate a unique variable name
-    pub fn generate_uid(&self, prefix: &str) -> String {
-        let mut counter = self.var_counter.borrow_mut();
-        *counter += 1;
-        format!("_{}{}", prefix, *counter)
+    /// Register a delegated event
+    pub fn register_delegate(&mut self, event: &str) {
+        self.delegates.insert(event.to_string());
     }
 
-    /// Register a helper import
-    pub fn register_helper(&self, name: &str) {
-        self.helpers.borrow_mut().insert(name.to_string());
+    /// Register a `use:` directive
+    pub fn register_directive(&mut self, name: &str) {
+        self.directives.insert(name.to_string());
     }
 
-    /// Register a delegated event
-    pub fn register_delegate(&self, event: &str) {
-        self.delegates.borrow_mut().insert(event.to_string());
+    /// The file-level state as JSON, for the IR dump
+    pub fn to_json(&self) -> String {
+        let strings = |set: &IndexSet<String>| json_array(set.iter().map(|s| json_string(s)));
+        json_object([
+            (
+                "templates",
+                json_array(self.templates.iter().map(|tmpl| {
+                    json_object([
+                        ("content", json_string(&tmpl.content)),
+                        ("isSvg", tmpl.is_svg.to_string()),
+                        ("isCE", tmpl.is_ce.to_string()),
+                    ])
+                })),
+            ),
+            ("helpers", strings(&self.module.helpers)),
+            ("delegatedEvents", strings(&self.delegates)),
+        ])
     }
 
-    /// Push a template and return its index
-    pub fn push_template(&self, content: String, is_svg: bool) -> usize {
+    /// Push a template and return its index. Identical templates (same HTML,
+    /// namespace and custom-element flag) share one module-level declaration.
+    pub fn push_template(&mut self, content: String, is_svg: bool, is_ce: bool) -> usize {
         self.register_helper("template");
-        let mut templates = self.templates.borrow_mut();
-        let index = templates.len();
-        templates.push(TemplateInfo { content, is_svg });
+        if let Some(index) = self.templates.iter().position(|tmpl| {
+            tmpl.content == content && tmpl.is_svg == is_svg && tmpl.is_ce == is_ce
+        }) {
+            return index;
+        }
+        let index = self.templates.len();
+        self.templates.push(TemplateInfo {
+            content,
+            is_svg,
+            is_ce,
+        });
         index
     }
 }
+
+impl TransformContext for BlockContext {
+    fn module(&self) -> &ModuleState {
+        &self.module
+    }
+
+    fn module_mut(&mut self) -> &mut ModuleState {
+        &mut self.module
+    }
+}
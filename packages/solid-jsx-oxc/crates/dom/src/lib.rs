@@ -1,3 +1,5 @@
+#![forbid(unsafe_code)]
+
 pub mod component;
 pub mod element;
 pub mod ir;
@@ -1,8 +1,8 @@
 //! Template generation
 //! Creates the _tmpl$ declarations and cloneNode calls
 
-use crate::ir::{BlockContext, TransformResult};
-use common::TransformOptions;
+use crate::ir::{BlockContext, Declaration, TransformResult};
+use common::{TransformContext, TransformOptions};
 
 /// Generate template declaration
 /// _tmpl$ = _template("<div>...</div>")
@@ -10,7 +10,7 @@ pub fn create_template_declaration(
     index: usize,
     content: &str,
     is_svg: bool,
-    context: &BlockContext,
+    context: &mut BlockContext,
 ) -> String {
     context.register_helper("template");
 
@@ -30,17 +30,79 @@ pub fn create_template_clone(index: usize) -> String {
     format!("_tmpl${}.cloneNode(true)", index)
 }
 
+/// Chain the walks to a template's nodes the way the Babel plugin does: each
+/// node is reached from the closest one already declared, so
+/// `_el$1.firstChild.nextSibling` becomes `_el$2.nextSibling` once `_el$2` is
+/// `_el$1.firstChild`. `root` is the template clone the walks start from.
+///
+/// In hydratable output `[_el$4, _co$5] = _$getNextMarker(start)` finds the
+/// closing marker of server-rendered content, so later walks continue from it.
+pub fn chain_walks(root: &str, declarations: &[Declaration]) -> Vec<Declaration> {
+    let mut known: Vec<(&str, Vec<&str>)> = vec![(root, vec![])];
+    let mut chained = Vec::with_capacity(declarations.len());
+    for decl in declarations {
+        let marker = decl
+            .init
+            .strip_prefix("_$getNextMarker(")
+            .and_then(|start| start.strip_suffix(')'));
+        let walk = marker.unwrap_or(&decl.init);
+
+        let mut steps = walk.split('.');
+        let base = steps.next().unwrap_or_default();
+        let Some((_, base_path)) = known.iter().find(|(name, _)| *name == base) else {
+            // Not a walk from this template
+            chained.push(Declaration {
+                name: decl.name.clone(),
+                init: decl.init.clone(),
+            });
+            continue;
+        };
+        let path: Vec<&str> = base_path.iter().copied().chain(steps).collect();
+
+        let (from, from_path) = known
+            .iter()
+            .filter(|(_, known_path)| path.starts_with(known_path))
+            .max_by_key(|(_, known_path)| known_path.len())
+            .expect("the root starts every walk");
+        let init = std::iter::once(*from)
+            .chain(path[from_path.len()..].iter().copied())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        if marker.is_some() {
+            chained.push(Declaration {
+                name: decl.name.clone(),
+                init: format!("_$getNextMarker({})", init),
+            });
+            // `[end, nodes]`: the end marker stands where the template has it
+            let end = decl.name.trim_start_matches('[').split(',').next();
+            known.extend(end.map(|end| (end.trim(), path)));
+        } else {
+            chained.push(Declaration {
+                name: decl.name.clone(),
+                init,
+            });
+            known.push((&decl.name, path));
+        }
+    }
+    chained
+}
+
 /// Generate the full template creation code from a transform result
 pub fn generate_template_code(
     result: &TransformResult,
-    context: &BlockContext,
+    context: &mut BlockContext,
     _options: &TransformOptions,
 ) -> String {
     let mut code = String::new();
 
     // If we have a template, create the declaration
     if !result.template.is_empty() && !result.skip_template {
-        let template_index = context.push_template(result.template.clone(), result.is_svg);
+        let template_index = context.push_template(
+            result.template.clone(),
+            result.is_svg,
+            result.has_custom_element,
+        );
 
         // Generate variable declarations
         if let Some(id) = &result.id {
@@ -62,10 +124,7 @@ pub fn generate_template_code(
         context.register_helper("effect");
 
         for binding in &result.dynamics {
-            code.push_str(&format!(
-                "_effect(() => {});\n",
-                generate_set_attr(&binding)
-            ));
+            code.push_str(&format!("_effect(() => {});\n", generate_set_attr(binding)));
         }
     }
 
@@ -91,7 +150,11 @@ pub fn generate_template_code(
 
 /// Generate attribute setter expression
 pub fn generate_set_attr(binding: &crate::ir::DynamicBinding) -> String {
-    let key = &binding.key;
+    // `htmlFor` is the `for` attribute
+    let key = match binding.key.as_str() {
+        "htmlFor" => "for",
+        key => key,
+    };
     let elem = &binding.elem;
     let value = &binding.value;
 
@@ -103,12 +166,32 @@ pub fn generate_set_attr(binding: &crate::ir::DynamicBinding) -> String {
             format!("{}.className = {}", elem, value)
         }
     } else if key == "style" {
-        format!("style({}, {})", elem, value)
+        format!("_$style({}, {})", elem, value)
     } else if key == "classList" {
-        format!("classList({}, {})", elem, value)
+        format!("_$classList({}, {})", elem, value)
     } else if key == "textContent" || key == "innerText" {
         format!("{}.data = {}", elem, value)
-    } else if common::constants::PROPERTIES.contains(key.as_str()) {
+    } else if let Some(namespace) = common::constants::attribute_namespace(key) {
+        format!(
+            "_$setAttributeNS({}, \"{}\", \"{}\", {})",
+            elem, namespace, key, value
+        )
+    } else if key.starts_with("aria-") {
+        // The `setAttribute` helper removes the attribute for null/undefined and
+        // stringifies everything else, so `false` stays "false"
+        format!("_$setAttribute({}, \"{}\", {})", elem, key, value)
+    } else if common::is_enumerated_attr(key) {
+        // `false` must be written as "false"; removing the attribute means "inherit"
+        format!("{}.setAttribute(\"{}\", String({}))", elem, key, value)
+    } else if binding.is_ce && !binding.is_svg && !key.starts_with("data-") {
+        // Custom elements take their inputs as properties
+        format!("{}.{} = {}", elem, kebab_to_camel(key), value)
+    } else if let Some(property) =
+        common::constants::prop_alias(key, &binding.tag_name).filter(|_| !binding.is_svg)
+    {
+        // Per-tag aliases, e.g. `readonly` is `readOnly` on inputs
+        format!("{}.{} = {}", elem, property, value)
+    } else if !binding.is_svg && common::constants::PROPERTIES.contains(key) {
         format!("{}.{} = {}", elem, key, value)
     } else if binding.is_svg {
         format!("{}.setAttribute(\"{}\", {})", elem, key, value)
@@ -117,3 +200,31 @@ pub fn generate_set_attr(binding: &crate::ir::DynamicBinding) -> String {
         format!("{}.setAttribute(\"{}\", {})", elem, key, value)
     }
 }
+
+/// The runtime helper the setter from `generate_set_attr` calls, if any
+pub fn set_attr_helper(key: &str) -> Option<&'static str> {
+    match key {
+        "style" => Some("style"),
+        "classList" => Some("classList"),
+        key if common::constants::attribute_namespace(key).is_some() => Some("setAttributeNS"),
+        key if key.starts_with("aria-") => Some("setAttribute"),
+        _ => None,
+    }
+}
+
+/// Convert a kebab-case attribute name to its camelCase property
+fn kebab_to_camel(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '-' {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
@@ -1,27 +1,41 @@
 //! Main JSX transform logic
 //! This implements the Traverse trait to walk the AST and transform JSX
 
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
 use oxc_allocator::{Allocator, CloneIn};
 use oxc_ast::ast::{
-    Expression, ImportDeclarationSpecifier, ImportOrExportKind, JSXChild, JSXElement,
-    JSXExpressionContainer, JSXFragment, JSXText, ModuleExportName, Program, Statement,
+    Argument, ArrayExpressionElement, Expression, IdentifierReference, JSXAttribute,
+    JSXAttributeItem, JSXAttributeName, JSXAttributeValue, JSXChild, JSXElement,
+    JSXExpressionContainer, JSXFragment, JSXOpeningElement, JSXText, Program, Statement,
 };
+use oxc_ast::AstBuilder;
 use oxc_parser::Parser;
 use oxc_semantic::SemanticBuilder;
-use oxc_span::{SourceType, Span};
-use oxc_traverse::{traverse_mut, Traverse, TraverseCtx};
-
-use common::{expr_to_string, get_tag_name, is_component, TransformOptions};
+use oxc_span::{GetSpan, SourceType, Span};
+use oxc_traverse::{traverse_mut, Ancestor, Traverse, TraverseCtx};
+
+use common::{
+    check_runtime_exports, decode_entities, expr_to_string, expression_child, fold_jsx_branches,
+    fold_show, get_tag_name, import_helpers, is_component, json_array, json_object, json_string,
+    substitute_constants, wrap_element_value, Diagnostic, IrDump, JsxIgnore, StaticMarks,
+    TransformContext, TransformOptions,
+};
 
 use crate::component::transform_component;
 use crate::element::transform_element;
-use crate::ir::{BlockContext, TransformResult};
+use crate::ir::{BlockContext, DynamicBinding, TransformResult};
+use crate::template::chain_walks;
 
 /// The main Solid JSX transformer
 pub struct SolidTransform<'a> {
     allocator: &'a Allocator,
     options: &'a TransformOptions<'a>,
     context: BlockContext,
+    jsx_ignore: JsxIgnore,
+    /// The IR of each JSX root as JSON, when dumping it
+    ir_roots: Option<Vec<String>>,
 }
 
 impl<'a> SolidTransform<'a> {
@@ -30,48 +44,68 @@ impl<'a> SolidTransform<'a> {
             allocator,
             options,
             context: BlockContext::new(),
+            jsx_ignore: JsxIgnore::default(),
+            ir_roots: None,
         }
     }
 
-    /// Run the transform on a program
-    pub fn transform(mut self, program: &mut Program<'a>) {
-        // SAFETY: We convert the allocator reference to a raw pointer and back to a reference
-        // to satisfy oxc_traverse's API which requires `&Allocator` while we hold `&mut self`.
-        // This is safe because:
-        // 1. The allocator lives for 'a which outlives this entire transform operation
-        // 2. oxc_traverse only uses the allocator for read-only arena access
-        // 3. We don't mutate the allocator through any path during traversal
-        // 4. The pointer is never escaped or stored beyond this call
-        let allocator = self.allocator as *const Allocator;
-        traverse_mut(
-            &mut self,
-            unsafe { &*allocator },
-            program,
-            SemanticBuilder::new()
-                .build(program)
-                .semantic
-                .into_scoping(),
-            (),
-        );
+    /// Run the transform on a program, returning any diagnostics it reported
+    pub fn transform(mut self, program: &mut Program<'a>) -> Vec<Diagnostic> {
+        self.run(program);
+        self.context.module.diagnostics
+    }
+
+    /// Run the transform and also dump the IR of every JSX root
+    pub fn transform_with_ir(mut self, program: &mut Program<'a>) -> (Vec<Diagnostic>, IrDump) {
+        self.ir_roots = Some(vec![]);
+        self.run(program);
+        let roots = self.ir_roots.take().unwrap_or_default();
+        let ir = IrDump {
+            json: json_object([
+                ("backend", json_string("dom")),
+                ("roots", json_array(roots)),
+                ("file", self.context.to_json()),
+            ]),
+            templates: self
+                .context
+                .templates
+                .iter()
+                .map(|tmpl| tmpl.content.clone())
+                .collect(),
+        };
+        (self.context.module.diagnostics, ir)
+    }
+
+    fn run(&mut self, program: &mut Program<'a>) {
+        self.jsx_ignore = JsxIgnore::from_program(program);
+        self.context.module.static_marks =
+            StaticMarks::from_program(program, self.options.static_marker);
+        // Copy the allocator reference out so it isn't tied to the `&mut self` borrow
+        let allocator = self.allocator;
+        let scoping = SemanticBuilder::new()
+            .build(program)
+            .semantic
+            .into_scoping();
+        traverse_mut(self, allocator, program, scoping, ());
     }
 
     /// Transform a JSX node and return the result
-    fn transform_node(&self, node: &JSXChild<'a>, info: &TransformInfo) -> Option<TransformResult> {
+    fn transform_node(
+        &self,
+        node: &JSXChild<'a>,
+        info: &TransformInfo,
+        context: &mut BlockContext,
+    ) -> Option<TransformResult> {
         match node {
-            JSXChild::Element(element) => Some(self.transform_jsx_element(element, info)),
-            JSXChild::Fragment(fragment) => Some(self.transform_fragment(fragment, info)),
+            JSXChild::Element(element) => Some(self.transform_jsx_element(element, info, context)),
+            JSXChild::Fragment(fragment) => Some(self.transform_fragment(fragment, info, context)),
             JSXChild::Text(text) => self.transform_text(text),
             JSXChild::ExpressionContainer(container) => {
-                self.transform_expression_container(container, info)
+                self.transform_expression_container(container, info, context)
             }
-            JSXChild::Spread(_spread) => {
-                // Spread children are rare, treat as dynamic
-                Some(TransformResult {
-                    exprs: vec![crate::ir::Expr {
-                        code: format!("/* spread child */"),
-                    }],
-                    ..Default::default()
-                })
+            // `{...items}` inserts the array like any other child
+            JSXChild::Spread(spread) => {
+                Some(self.transform_child_value(&spread.expression, context))
             }
         }
     }
@@ -81,27 +115,41 @@ impl<'a> SolidTransform<'a> {
         &self,
         element: &JSXElement<'a>,
         info: &TransformInfo,
+        context: &mut BlockContext,
     ) -> TransformResult {
         let tag_name = get_tag_name(element);
 
         // Create child transformer closure that can recursively transform children
         let child_transformer =
-            |child: &JSXChild<'a>| -> Option<TransformResult> { self.transform_node(child, info) };
+            |child: &JSXChild<'a>, context: &mut BlockContext| -> Option<TransformResult> {
+                self.transform_node(child, info, context)
+            };
 
         if is_component(&tag_name) {
-            transform_component(
-                element,
-                &tag_name,
-                &self.context,
-                self.options,
-                &child_transformer,
-            )
+            // Each JSX child of a component is a root of its own, compiled to the
+            // expression that creates it
+            let component_child =
+                |child: &JSXChild<'a>, context: &mut BlockContext| -> Option<TransformResult> {
+                    let info = TransformInfo {
+                        top_level: true,
+                        last_element: true,
+                        component_child: true,
+                        ..Default::default()
+                    };
+                    let result = self.transform_node(child, &info, context)?;
+                    let code = self.build_dom_output(&result, context);
+                    Some(TransformResult {
+                        exprs: vec![crate::ir::Expr { code }],
+                        ..Default::default()
+                    })
+                };
+            transform_component(element, &tag_name, context, self.options, &component_child)
         } else {
             transform_element(
                 element,
                 &tag_name,
                 info,
-                &self.context,
+                context,
                 self.options,
                 &child_transformer,
             )
@@ -113,6 +161,7 @@ impl<'a> SolidTransform<'a> {
         &self,
         fragment: &JSXFragment<'a>,
         info: &TransformInfo,
+        context: &mut BlockContext,
     ) -> TransformResult {
         let mut result = TransformResult::default();
         let mut has_expression_child = false;
@@ -120,11 +169,18 @@ impl<'a> SolidTransform<'a> {
 
         for child in &fragment.children {
             // Track if we have expression container children (need memo)
-            if matches!(child, JSXChild::ExpressionContainer(_)) {
-                has_expression_child = true;
-            }
+            let dynamic = match child {
+                JSXChild::ExpressionContainer(container) => {
+                    // `{/* comments */}` are not expressions
+                    let expr = container.expression.as_expression();
+                    has_expression_child |= expr.is_some();
+                    expr.is_some_and(|expr| common::is_dynamic(expr, &context.module.static_marks))
+                }
+                _ => false,
+            };
 
-            if let Some(child_result) = self.transform_node(child, info) {
+            if let Some(mut child_result) = self.transform_node(child, info, context) {
+                child_result.needs_memo |= dynamic;
                 child_results.push(child_result);
             }
         }
@@ -135,63 +191,31 @@ impl<'a> SolidTransform<'a> {
             return result;
         }
 
+        // A single child is the fragment's value
         if child_results.len() == 1 {
             let mut single_result = child_results.pop().unwrap();
             // For single expression child, check if we need memo
-            if single_result.template.is_empty() && !single_result.exprs.is_empty() && has_expression_child {
+            if single_result.template.is_empty()
+                && !single_result.exprs.is_empty()
+                && has_expression_child
+            {
                 single_result.needs_memo = true;
             }
             return single_result;
         }
 
-        // Multiple children - check if we need array output
-        let has_text_child = child_results.iter().any(|r| r.text);
-        let has_element_child = child_results.iter().any(|r| !r.template.is_empty() && !r.text);
-        let has_component_child = child_results.iter().any(|r| r.template.is_empty() && !r.exprs.is_empty());
-
-        // Use array output when mixing different types of children
-        let needs_array = (has_text_child && has_element_child)
-            || (has_text_child && has_component_child)
-            || (has_element_child && has_component_child)
-            || has_component_child;
-
-        if needs_array {
-            // Mixed children: need array output
-            // Generate code for each child independently
-            for child_result in &child_results {
-                if child_result.text {
-                    // Text children become string literals
-                    result.child_codes.push(format!("\"{}\"", child_result.template));
-                } else {
-                    let code = self.build_dom_output(child_result);
-                    if !code.is_empty() {
-                        result.child_codes.push(code);
-                    }
+        // Several children become an array. Each element gets its own template,
+        // as a template has a single root, and reactive expressions are memoized
+        for child_result in &child_results {
+            if child_result.text {
+                // Text children become string literals
+                result.child_codes.push(json_string(&child_result.template));
+            } else {
+                let code = self.build_dom_output(child_result, context);
+                if !code.is_empty() {
+                    result.child_codes.push(code);
                 }
             }
-        } else if has_element_child {
-            // All native element children - merge templates
-            for child_result in child_results {
-                result.template.push_str(&child_result.template);
-                result.declarations.extend(child_result.declarations);
-                result.exprs.extend(child_result.exprs);
-                result.dynamics.extend(child_result.dynamics);
-            }
-        } else if has_text_child {
-            // All text children - merge templates
-            for child_result in child_results {
-                result.template.push_str(&child_result.template);
-            }
-        } else {
-            // All expression children (non-component expressions like {x()})
-            for child_result in child_results {
-                result.exprs.extend(child_result.exprs);
-            }
-
-            // Wrap in memo for expression children
-            if info.top_level && has_expression_child {
-                result.needs_memo = true;
-            }
         }
 
         result
@@ -204,8 +228,9 @@ impl<'a> SolidTransform<'a> {
             return None;
         }
 
+        // Text outside an element becomes a text node, so it stays unescaped
         Some(TransformResult {
-            template: common::expression::escape_html(&content, false),
+            template: content,
             text: true,
             ..Default::default()
         })
@@ -216,33 +241,37 @@ impl<'a> SolidTransform<'a> {
         &self,
         container: &JSXExpressionContainer<'a>,
         _info: &TransformInfo,
+        context: &BlockContext,
     ) -> Option<TransformResult> {
         // Use as_expression() to get the expression if it exists
-        if let Some(expr) = container.expression.as_expression() {
-            let expr_str = expr_to_string(expr);
-            if common::is_dynamic(expr) {
-                // Wrap in arrow function for reactivity
-                Some(TransformResult {
-                    exprs: vec![crate::ir::Expr {
-                        code: format!("() => {}", expr_str),
-                    }],
-                    ..Default::default()
-                })
-            } else {
-                // Static expression
-                Some(TransformResult {
-                    exprs: vec![crate::ir::Expr { code: expr_str }],
-                    ..Default::default()
-                })
-            }
+        container
+            .expression
+            .as_expression()
+            .map(|expr| self.transform_child_value(expr, context))
+    }
+
+    /// The value of an expression or spread child
+    fn transform_child_value(
+        &self,
+        expr: &Expression<'a>,
+        context: &BlockContext,
+    ) -> TransformResult {
+        let expr_str = expr_to_string(expr);
+        let code = if common::is_dynamic(expr, &context.module.static_marks) {
+            // Wrap in arrow function for reactivity
+            format!("() => {}", expr_str)
         } else {
-            // Empty expression
-            None
+            // Static expression
+            expr_str
+        };
+        TransformResult {
+            exprs: vec![crate::ir::Expr { code }],
+            ..Default::default()
         }
     }
 
     /// Build DOM output code from transform result
-    fn build_dom_output(&self, result: &TransformResult) -> String {
+    fn build_dom_output(&self, result: &TransformResult, context: &mut BlockContext) -> String {
         let mut code = String::new();
 
         // Handle fragment with mixed children (array output)
@@ -253,54 +282,102 @@ impl<'a> SolidTransform<'a> {
 
         // Handle text-only result - just return the string literal
         if result.text && !result.template.is_empty() {
-            // The template already contains escaped HTML, just wrap in quotes
-            return format!("\"{}\"", result.template);
+            return json_string(&result.template);
         }
 
         // If there's a template, we need to clone it
         if !result.template.is_empty() && !result.skip_template {
             // Register template helper
-            self.context.register_helper("template");
+            context.register_helper("template");
 
             // Push template and get variable name
-            let tmpl_idx = self
-                .context
-                .push_template(result.template.clone(), result.is_svg);
+            let tmpl_idx = context.push_template(
+                result.template.clone(),
+                result.is_svg,
+                result.has_custom_element,
+            );
             let tmpl_var = format!("_tmpl${}", tmpl_idx + 1);
 
+            // Hydration adopts the server-rendered node instead of a fresh clone
+            let root = if self.options.hydratable {
+                context.register_helper("getNextElement");
+                format!("_$getNextElement({})", tmpl_var)
+            } else {
+                format!("{}.cloneNode(true)", tmpl_var)
+            };
+
+            // Nothing to wire up: the clone is the whole expression, so children
+            // and fallbacks don't build a closure on every access
+            if result.is_static() {
+                return root;
+            }
+
             // Generate element variable
             let elem_var = result.id.clone().unwrap_or_else(|| "_el$".to_string());
 
-            // Build IIFE
+            // Build IIFE, declaring the clone and the walks to its nested nodes
+            // together: const _el$1 = _tmpl$1.cloneNode(true), _el$2 = _el$1.firstChild;
             code.push_str("(() => {\n");
-            code.push_str(&format!(
-                "  const {} = {}.cloneNode(true);\n",
-                elem_var, tmpl_var
-            ));
-
-            // Add declarations (element walking for nested elements)
-            for decl in &result.declarations {
-                code.push_str(&format!("  const {} = {};\n", decl.name, decl.init));
+            code.push_str(&format!("  const {} = {}", elem_var, root));
+            for decl in chain_walks(&elem_var, &result.declarations) {
+                code.push_str(&format!(",\n    {} = {}", decl.name, decl.init));
             }
+            code.push_str(";\n");
 
             // Add expressions (effects, inserts, etc.)
             for expr in &result.exprs {
                 code.push_str(&format!("  {};\n", expr.code));
             }
 
+            // A value driving several bindings is computed once and shared
+            let mut memos: Vec<(&str, String)> = vec![];
+            for value in result.shared_values() {
+                context.register_helper("memo");
+                let name = context.generate_uid("v$");
+                code.push_str(&format!("  const {} = _$memo(() => {});\n", name, value));
+                memos.push((value, name));
+            }
+
             // Add dynamic bindings
             for binding in &result.dynamics {
-                self.context.register_helper("effect");
+                context.register_helper("effect");
                 // Register the appropriate helper based on binding key
                 if binding.key == "style" {
-                    self.context.register_helper("style");
+                    context.register_helper("style");
                 } else if binding.key == "classList" {
-                    self.context.register_helper("classList");
+                    context.register_helper("classList");
+                } else if common::constants::attribute_namespace(&binding.key).is_some() {
+                    context.register_helper("setAttributeNS");
+                } else if matches!(binding.key.as_str(), "textContent" | "innerText") {
+                    // Text is written to the text node's `data`
                 } else {
-                    self.context.register_helper("setAttribute");
+                    context.register_helper("setAttribute");
                 }
-                let setter = crate::template::generate_set_attr(binding);
-                code.push_str(&format!("  effect(() => {});\n", setter));
+                let value = match memos.iter().find(|(value, _)| *value == binding.value) {
+                    Some((_, name)) => format!("{}()", name),
+                    None => binding.value.clone(),
+                };
+                // `style` and `classList` diff against the value they were last given
+                let effect = match binding.key.as_str() {
+                    key @ ("style" | "classList") => {
+                        self.options.effect_prev_code(&value, |value, prev| {
+                            format!("_${}({}, {}, {})", key, binding.elem, value, prev)
+                        })
+                    }
+                    _ => self.options.effect_code(&value, |value| {
+                        crate::template::generate_set_attr(&DynamicBinding {
+                            value: value.to_string(),
+                            ..binding.clone()
+                        })
+                    }),
+                };
+                code.push_str(&format!("  {};\n", effect));
+            }
+
+            // Delegated events fired before hydration finished are replayed
+            if self.options.hydratable && result.has_hydratable_event {
+                context.register_helper("runHydrationEvents");
+                code.push_str("  _$runHydrationEvents();\n");
             }
 
             code.push_str(&format!("  return {};\n", elem_var));
@@ -316,8 +393,8 @@ impl<'a> SolidTransform<'a> {
 
             // Fragment expressions need memo wrapping for reactivity
             if result.needs_memo {
-                self.context.register_helper("memo");
-                code = format!("memo({})", expr_code);
+                context.register_helper("memo");
+                code = format!("_$memo({})", expr_code);
             } else {
                 code = expr_code;
             }
@@ -333,18 +410,121 @@ pub struct TransformInfo {
     pub top_level: bool,
     pub last_element: bool,
     pub skip_id: bool,
+    /// A dynamic sibling is inserted before this element, so it needs an id
+    pub marker: bool,
+    /// Whether the parent element is in the SVG namespace; `None` at the top,
+    /// where the tag decides
+    pub in_svg: Option<bool>,
     pub component_child: bool,
     pub fragment_child: bool,
     /// Path from root element to this element (e.g., ["firstChild", "nextSibling"])
     pub path: Vec<String>,
     /// The root element variable name (e.g., "_el$1")
     pub root_id: Option<String>,
+    /// Tags that keep their closing tag in the subtree; `None` until an ancestor
+    /// keeps its own
+    pub to_be_closed: Option<HashSet<String>>,
 }
 
 impl<'a> Traverse<'a, ()> for SolidTransform<'a> {
+    fn enter_expression(&mut self, node: &mut Expression<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        let scoping = ctx.scoping();
+        let is_global = |ident: &IdentifierReference| match ident.reference_id.get() {
+            Some(id) => scoping.get_reference(id).symbol_id().is_none(),
+            None => true,
+        };
+        substitute_constants(node, self.options, ctx.ast, &is_global);
+
+        // Only the branch a literal condition takes is compiled
+        if !self.jsx_ignore.contains(node.span()) {
+            fold_jsx_branches(node, ctx.ast);
+            if let Expression::JSXElement(element) = node {
+                if let Some(folded) = fold_show(element, ctx.ast) {
+                    *node = folded;
+                    fold_jsx_branches(node, ctx.ast);
+                }
+            }
+        }
+
+        if matches!(node, Expression::JSXElement(_) | Expression::JSXFragment(_)) {
+            self.jsx_ignore.enter(node.span());
+        }
+    }
+
+    fn enter_jsx_child(&mut self, node: &mut JSXChild<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if let JSXChild::Element(element) = node {
+            if self.jsx_ignore.contains(element.span) {
+                return;
+            }
+            if let Some(folded) = fold_show(element, ctx.ast) {
+                *node = expression_child(folded, ctx.ast);
+            }
+        }
+    }
+
+    // Character references are decoded before any text or string attribute is
+    // read, then escaped again where they end up in markup
+    fn enter_jsx_text(&mut self, node: &mut JSXText<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if node.value.contains('&') && !self.jsx_ignore.contains(node.span) {
+            node.value = ctx.ast.atom(&decode_entities(&node.value));
+        }
+    }
+
+    fn enter_jsx_attribute(&mut self, node: &mut JSXAttribute<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if !self.jsx_ignore.contains(node.span) {
+            wrap_element_value(&mut node.value, ctx.ast);
+        }
+        if let Some(JSXAttributeValue::StringLiteral(lit)) = &mut node.value {
+            if lit.value.contains('&') && !self.jsx_ignore.contains(lit.span) {
+                lit.value = ctx.ast.atom(&decode_entities(&lit.value));
+            }
+        }
+    }
+
+    // `use:` directives are resolved here, while the traversal still tracks the
+    // scope the element is written in
+    fn enter_jsx_opening_element(
+        &mut self,
+        node: &mut JSXOpeningElement<'a>,
+        ctx: &mut TraverseCtx<'a, ()>,
+    ) {
+        if self.jsx_ignore.contains(node.span) {
+            return;
+        }
+        for item in &node.attributes {
+            let JSXAttributeItem::Attribute(attr) = item else {
+                continue;
+            };
+            let JSXAttributeName::NamespacedName(name) = &attr.name else {
+                continue;
+            };
+            if name.namespace.name != "use" {
+                continue;
+            }
+            let directive = name.name.name.as_str();
+            let bound = ctx
+                .scoping()
+                .find_binding(ctx.current_scope_id(), directive)
+                .is_some();
+            if !bound && self.options.directive_module(directive).is_none() {
+                self.context.report(Diagnostic::error(
+                    format!("`use:{}` has no `{}` in scope", directive, directive),
+                    attr.span,
+                ));
+            }
+        }
+    }
+
     // Use exit_expression instead of enter_expression to avoid
     // oxc_traverse walking into our newly created nodes (which lack scope info)
     fn exit_expression(&mut self, node: &mut Expression<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        // `@jsx-ignore` subtrees are left as written
+        if self.jsx_ignore.contains(node.span()) {
+            return;
+        }
+
+        // Take the context out so it can be passed down mutably alongside `&self`
+        let mut context = std::mem::take(&mut self.context);
         let new_expr = match node {
             Expression::JSXElement(element) => {
                 let result = self.transform_jsx_element(
@@ -354,118 +534,144 @@ impl<'a> Traverse<'a, ()> for SolidTransform<'a> {
                         last_element: true,
                         ..Default::default()
                     },
+                    &mut context,
                 );
-                Some(self.build_dom_expression(&result, ctx))
+                self.record_ir(&result);
+                Some(self.build_dom_expression(&result, &mut context, ctx))
             }
             Expression::JSXFragment(fragment) => {
                 let result = self.transform_fragment(
                     fragment,
                     &TransformInfo {
                         top_level: true,
+                        last_element: true,
                         ..Default::default()
                     },
+                    &mut context,
                 );
-                Some(self.build_dom_expression(&result, ctx))
+                self.record_ir(&result);
+                Some(self.build_dom_expression(&result, &mut context, ctx))
             }
             _ => None,
         };
+        self.context = context;
 
-        if let Some(expr) = new_expr {
+        if let Some(mut expr) = new_expr {
+            annotate_module_level_call(&mut expr, ctx);
             *node = expr;
         }
     }
 
     fn exit_program(&mut self, program: &mut Program<'a>, ctx: &mut TraverseCtx<'a, ()>) {
-        let helpers = self.context.helpers.borrow();
-        let templates = self.context.templates.borrow();
-        let delegates = self.context.delegates.borrow();
-
-        if helpers.is_empty() && templates.is_empty() {
+        if self.context.module.helpers.is_empty() && self.context.templates.is_empty() {
             return;
         }
 
         let ast = ctx.ast;
-        let span = Span::default();
 
-        // Insert template declarations
-        // const _tmpl$ = template(`<div></div>`);
-        for (i, tmpl) in templates.iter().enumerate() {
+        // Insert hoisted constants, below the templates they may clone
+        for (name, init) in self.context.module.hoisted.iter().rev() {
+            let decl_code = format!("const {} = {};", name, init);
+            if let Some(stmt) = self.parse_statement(&decl_code, ctx) {
+                program.body.insert(0, stmt);
+            }
+        }
+
+        // Insert template declarations, in the order they were created
+        // const _tmpl$ = /*@__PURE__*/_$template(`<div></div>`);
+        for (i, tmpl) in self.context.templates.iter().enumerate().rev() {
             let tmpl_var = format!("_tmpl${}", i + 1);
-            let call_code = if tmpl.is_svg {
-                format!("template(`{}`, true)", tmpl.content)
+            // The runtime's `template(html, isImportNode, isSVG)`
+            let call_code = if tmpl.is_svg || tmpl.is_ce {
+                format!(
+                    "_$template(`{}`, {}, {})",
+                    tmpl.content, tmpl.is_ce, tmpl.is_svg
+                )
             } else {
-                format!("template(`{}`)", tmpl.content)
+                format!("_$template(`{}`)", tmpl.content)
             };
 
             // Parse and build the declaration
             let decl_code = format!("const {} = {};", tmpl_var, call_code);
-            if let Some(stmt) = self.parse_statement(&decl_code, ctx) {
+            if let Some(mut stmt) = self.parse_statement(&decl_code, ctx) {
+                // Unused templates can be dropped by bundlers
+                if let Statement::VariableDeclaration(decl) = &mut stmt {
+                    if let Some(Expression::CallExpression(call)) = &mut decl.declarations[0].init {
+                        call.pure = true;
+                    }
+                }
                 program.body.insert(0, stmt);
             }
         }
 
-        // Insert delegateEvents call if needed
-        if !delegates.is_empty() {
-            let events: Vec<&str> = delegates.iter().map(|s| s.as_str()).collect();
-            let delegate_code = format!("delegateEvents([\"{}\"])", events.join("\", \""));
-            if let Some(stmt) = self.parse_statement(&format!("{};", delegate_code), ctx) {
-                program.body.push(stmt);
+        // Insert delegateEvents call if needed, sorted so the footer is stable.
+        // A file transformed again (e.g. on HMR) already has one: extend it
+        // rather than delegating twice.
+        if !self.context.delegates.is_empty() {
+            let mut events: Vec<&str> = self.context.delegates.iter().map(|s| s.as_str()).collect();
+            events.sort_unstable();
+            if !extend_delegate_events(program, &events, ast) {
+                let delegate_code = format!("_$delegateEvents([\"{}\"])", events.join("\", \""));
+                if let Some(stmt) = self.parse_statement(&format!("{};", delegate_code), ctx) {
+                    program.body.push(stmt);
+                }
+                // Register helper
+                self.context.register_helper("delegateEvents");
+            }
+        }
+
+        // Import directives with a configured module unless the file declares them
+        let mut directive_imports: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for name in &self.context.directives {
+            let Some(module) = self.options.directive_module(name) else {
+                continue;
+            };
+            if ctx.scoping().get_root_binding(name).is_none() {
+                directive_imports
+                    .entry(module)
+                    .or_default()
+                    .push(name.as_str());
+            }
+        }
+        for (module, names) in directive_imports.iter().rev() {
+            let import_code = format!("import {{ {} }} from \"{}\";", names.join(", "), module);
+            if let Some(stmt) = self.parse_statement(&import_code, ctx) {
+                program.body.insert(0, stmt);
             }
-            // Register helper
-            drop(helpers); // Release borrow
-            self.context.register_helper("delegateEvents");
         }
 
-        // Re-borrow helpers after potential modification
-        let helpers = self.context.helpers.borrow();
+        let helpers = &self.context.module.helpers;
+        let missing = check_runtime_exports(self.options, helpers);
+        self.context.module.diagnostics.extend(missing);
 
-        // Build import statement: import { template, effect, ... } from 'solid-js/web';
-        // NOTE: This import building logic is duplicated with SSR transform.
-        // Extraction is non-trivial due to OXC's lifetime requirements.
+        // import { template as _$template, effect as _$effect, ... } from 'solid-js/web';
+        // Generated code only calls helpers by their `_$` alias, so user bindings
+        // with the same names (a `style` variable, an `effect` function) can't clash.
         if !helpers.is_empty() {
-            let module_name = self.options.module_name;
-
-            // Build specifiers
-            let mut specifiers = ast.vec();
-            for helper in helpers.iter() {
-                let helper_str = ast.allocator.alloc_str(helper);
-                let imported =
-                    ModuleExportName::IdentifierName(ast.identifier_name(span, helper_str));
-                let local = ast.binding_identifier(span, helper_str);
-                let specifier =
-                    ast.import_specifier(span, imported, local, ImportOrExportKind::Value);
-                specifiers.push(ImportDeclarationSpecifier::ImportSpecifier(
-                    ast.alloc(specifier),
-                ));
-            }
-
-            // Build source string literal
-            let source = ast.string_literal(span, module_name, None);
-
-            // Build import declaration
-            let import_decl = ast.import_declaration(
-                span,
-                Some(specifiers),
-                source,
-                None,                                 // phase
-                None::<oxc_ast::ast::WithClause<'a>>, // with_clause
-                ImportOrExportKind::Value,
+            import_helpers(
+                program,
+                helpers,
+                self.options.module_name,
+                ctx.scoping(),
+                ast,
             );
-
-            // Create the statement
-            let import_stmt = Statement::ImportDeclaration(ast.alloc(import_decl));
-
-            // Insert at the beginning of the program
-            program.body.insert(0, import_stmt);
         }
     }
 }
 
 impl<'a> SolidTransform<'a> {
+    /// Keep the IR of a JSX root when dumping it
+    fn record_ir(&mut self, result: &TransformResult) {
+        if let Some(roots) = &mut self.ir_roots {
+            roots.push(result.to_json());
+        }
+    }
+
     /// Build DOM expression from transform result
     fn build_dom_expression(
         &self,
         result: &TransformResult,
+        context: &mut BlockContext,
         ctx: &mut TraverseCtx<'a, ()>,
     ) -> Expression<'a> {
         let ast = ctx.ast;
@@ -478,7 +684,7 @@ impl<'a> SolidTransform<'a> {
         }
 
         // Generate the DOM code string
-        let dom_code = self.build_dom_output(result);
+        let dom_code = self.build_dom_output(result, context);
 
         // Parse the code into an expression
         let allocator = ast.allocator;
@@ -486,10 +692,8 @@ impl<'a> SolidTransform<'a> {
         let parse_result = Parser::new(allocator, &dom_code, source_type).parse();
 
         // Try to extract the expression from the parsed program
-        if let Some(stmt) = parse_result.program.body.first() {
-            if let Statement::ExpressionStatement(expr_stmt) = stmt {
-                return expr_stmt.expression.clone_in(allocator);
-            }
+        if let Some(Statement::ExpressionStatement(expr_stmt)) = parse_result.program.body.first() {
+            return expr_stmt.expression.clone_in(allocator);
         }
 
         // Fallback: create a string literal with the code (for debugging)
@@ -511,3 +715,75 @@ impl<'a> SolidTransform<'a> {
             .map(|stmt| stmt.clone_in(allocator))
     }
 }
+
+/// Add events to a top-level `delegateEvents([...])` call of the program, keeping
+/// its events sorted and unique. Returns false when there is no such call.
+fn extend_delegate_events<'a>(
+    program: &mut Program<'a>,
+    events: &[&str],
+    ast: AstBuilder<'a>,
+) -> bool {
+    for stmt in program.body.iter_mut() {
+        let Statement::ExpressionStatement(stmt) = stmt else {
+            continue;
+        };
+        let Expression::CallExpression(call) = &mut stmt.expression else {
+            continue;
+        };
+        let is_delegate = matches!(&call.callee, Expression::Identifier(ident)
+            if matches!(ident.name.as_str(), "delegateEvents" | "_$delegateEvents"));
+        if !is_delegate {
+            continue;
+        }
+        let Some(Argument::ArrayExpression(array)) = call.arguments.first_mut() else {
+            continue;
+        };
+
+        let mut names: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+        let mut others = ast.vec();
+        for element in std::mem::replace(&mut array.elements, ast.vec()) {
+            match element {
+                ArrayExpressionElement::StringLiteral(lit) => names.push(lit.value.to_string()),
+                element => others.push(element),
+            }
+        }
+        names.sort_unstable();
+        names.dedup();
+        for name in names {
+            let value = ast.atom(&name);
+            others.push(ArrayExpressionElement::StringLiteral(
+                ast.alloc_string_literal(Span::default(), value, None),
+            ));
+        }
+        array.elements = others;
+        return true;
+    }
+    false
+}
+
+/// Mark a JSX root's component call or IIFE `/* @__PURE__ */` when it initializes
+/// a module-level binding or default export, so bundlers can drop it with an
+/// unused export
+fn annotate_module_level_call(expr: &mut Expression, ctx: &TraverseCtx<'_, ()>) {
+    if ctx.current_scope_id() != ctx.scoping().root_scope_id()
+        || !matches!(
+            ctx.parent(),
+            Ancestor::VariableDeclaratorInit(_) | Ancestor::ExportDefaultDeclarationDeclaration(_)
+        )
+    {
+        return;
+    }
+    if let Expression::CallExpression(call) = expr {
+        let is_component = matches!(
+            &call.callee,
+            Expression::Identifier(ident) if ident.name == "_$createComponent"
+        );
+        let is_iife = matches!(
+            call.callee.without_parentheses(),
+            Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_)
+        );
+        if is_component || is_iife {
+            call.pure = true;
+        }
+    }
+}
@@ -3,52 +3,26 @@
 //! Components in SSR are rendered the same way as DOM - using createComponent.
 //! The component itself decides whether to render for server or client.
 
-use oxc_ast::ast::{JSXAttributeItem, JSXAttributeName, JSXAttributeValue, JSXChild, JSXElement};
+use oxc_ast::ast::{Expression, JSXAttributeItem, JSXAttributeValue, JSXElement};
 
 use common::{
-    expr_to_string, find_prop_value, get_children_callback, is_built_in, is_dynamic,
+    child_values, expr_to_string, find_prop, find_prop_value, get_attr_name, get_children_callback,
+    is_built_in, is_constant_children, is_event_attr, ComponentProps, TransformContext,
     TransformOptions,
 };
 
-use crate::ir::{SSRChildTransformer, SSRContext, SSRResult};
+use crate::element::transform_dynamic_element;
+use crate::ir::{EscapeContext, SSRChildTransformer, SSRContext, SSRResult};
 
 // find_prop_value and get_children_callback moved to common module
 
 /// Get children as SSR expression with recursive transformation
 fn get_children_ssr<'a, 'b>(
     element: &JSXElement<'a>,
+    context: &mut SSRContext,
     transform_child: SSRChildTransformer<'a, 'b>,
 ) -> String {
-    let mut children: Vec<String> = vec![];
-
-    for child in &element.children {
-        match child {
-            JSXChild::Text(text) => {
-                let content = common::expression::trim_whitespace(&text.value);
-                if !content.is_empty() {
-                    children.push(format!(
-                        "\"{}\"",
-                        common::expression::escape_html(&content, false)
-                    ));
-                }
-            }
-            JSXChild::ExpressionContainer(container) => {
-                if let Some(expr) = container.expression.as_expression() {
-                    children.push(expr_to_string(expr));
-                }
-            }
-            JSXChild::Element(_) | JSXChild::Fragment(_) => {
-                // Transform the child JSX element/fragment
-                if let Some(result) = transform_child(child) {
-                    children.push(result.to_ssr_call());
-                }
-            }
-            JSXChild::Spread(spread) => {
-                children.push(expr_to_string(&spread.expression));
-            }
-        }
-    }
-
+    let mut children = children_values_ssr(element, context, transform_child);
     if children.len() == 1 {
         format!("() => {}", children.pop().unwrap_or_default())
     } else if children.is_empty() {
@@ -58,11 +32,27 @@ fn get_children_ssr<'a, 'b>(
     }
 }
 
+/// The code for each of an element's children
+fn children_values_ssr<'a, 'b>(
+    element: &JSXElement<'a>,
+    context: &mut SSRContext,
+    transform_child: SSRChildTransformer<'a, 'b>,
+) -> Vec<String> {
+    // Component children render their own templates, so their elements are
+    // hydration roots again
+    let in_element = std::mem::replace(&mut context.in_element, false);
+    let values = child_values(element, context, |child, context| {
+        transform_child(child, context).map(|result| result.to_ssr_call(context))
+    });
+    context.in_element = in_element;
+    values
+}
+
 /// Transform a component for SSR
 pub fn transform_component<'a, 'b>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &SSRContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
     transform_child: SSRChildTransformer<'a, 'b>,
 ) -> SSRResult {
@@ -70,22 +60,32 @@ pub fn transform_component<'a, 'b>(
 
     // Check if this is a built-in (For, Show, etc.)
     if is_built_in(tag_name) {
-        return transform_builtin(element, tag_name, context, transform_child);
+        return transform_builtin(element, tag_name, context, options, transform_child);
     }
 
     context.register_helper("createComponent");
-    context.register_helper("escape");
 
     // Build props
     let props = build_props(element, context, options, transform_child);
 
+    // Boundary comments let selective hydration and HTML-diff tooling find the component
+    let boundary = options
+        .component_boundaries
+        .then(|| format!("c:{}:{}", tag_name, context.next_component_id()));
+    if let Some(boundary) = &boundary {
+        result.push_static(&format!("<!--{}-->", boundary));
+    }
+
     // Generate createComponent call - will be escaped by parent
     result.push_dynamic(
-        format!("createComponent({}, {})", tag_name, props),
-        false,
-        false, // Components return escaped content
+        format!("_$createComponent({}, {})", tag_name, props),
+        EscapeContext::Text, // Components return escaped content
     );
 
+    if let Some(boundary) = &boundary {
+        result.push_static(&format!("<!--/{}-->", boundary));
+    }
+
     result
 }
 
@@ -93,13 +93,13 @@ pub fn transform_component<'a, 'b>(
 fn transform_builtin<'a, 'b>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &SSRContext,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
     transform_child: SSRChildTransformer<'a, 'b>,
 ) -> SSRResult {
     let mut result = SSRResult::new();
 
     context.register_helper("createComponent");
-    context.register_helper("escape");
 
     // Note: Built-in components (For, Show, Switch, Match, Index, Suspense, Portal, Dynamic, ErrorBoundary)
     // are expected to be imported by the user from solid-js or solid-js/web.
@@ -111,47 +111,43 @@ fn transform_builtin<'a, 'b>(
             let children = get_children_callback(element);
             result.push_dynamic(
                 format!(
-                    "createComponent(For, {{ each: {}, children: {} }})",
+                    "_$createComponent(For, {{ each: {}, children: {} }})",
                     each, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
         "Show" => {
             let when = find_prop_value(element, "when").unwrap_or("false".to_string());
             let fallback = find_prop_value(element, "fallback").unwrap_or("undefined".to_string());
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
                 format!(
-                    "createComponent(Show, {{ when: {}, fallback: {}, children: {} }})",
+                    "_$createComponent(Show, {{ when: {}, fallback: {}, children: {} }})",
                     when, fallback, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
         "Switch" => {
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
-                format!("createComponent(Switch, {{ children: {} }})", children),
-                false,
-                false,
+                format!("_$createComponent(Switch, {{ children: {} }})", children),
+                EscapeContext::Text,
             );
         }
 
         "Match" => {
             let when = find_prop_value(element, "when").unwrap_or("false".to_string());
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
                 format!(
-                    "createComponent(Match, {{ when: {}, children: {} }})",
+                    "_$createComponent(Match, {{ when: {}, children: {} }})",
                     when, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
@@ -160,158 +156,133 @@ fn transform_builtin<'a, 'b>(
             let children = get_children_callback(element);
             result.push_dynamic(
                 format!(
-                    "createComponent(Index, {{ each: {}, children: {} }})",
+                    "_$createComponent(Index, {{ each: {}, children: {} }})",
                     each, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
         "Suspense" => {
             let fallback = find_prop_value(element, "fallback").unwrap_or("undefined".to_string());
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
                 format!(
-                    "createComponent(Suspense, {{ fallback: {}, children: {} }})",
+                    "_$createComponent(Suspense, {{ fallback: {}, children: {} }})",
                     fallback, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
         "Portal" => {
             // Portal in SSR just renders children (no mount target on server)
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
-                format!("createComponent(Portal, {{ children: {} }})", children),
-                false,
-                false,
+                format!("_$createComponent(Portal, {{ children: {} }})", children),
+                EscapeContext::Text,
             );
         }
 
         "Dynamic" => {
-            let component =
-                find_prop_value(element, "component").unwrap_or("undefined".to_string());
+            // A tag written in place is rendered here, anything else is left to
+            // the runtime `Dynamic`, which takes `component` as an ordinary prop
+            if let Some(tag) = static_tag(element) {
+                return transform_dynamic_element(element, &tag, context, options);
+            }
+            let props = build_props(element, context, options, transform_child);
             result.push_dynamic(
-                format!("createComponent(Dynamic, {{ component: {} }})", component),
-                false,
-                false,
+                format!("_$createComponent(Dynamic, {})", props),
+                EscapeContext::Text,
             );
         }
 
         "ErrorBoundary" => {
             let fallback = find_prop_value(element, "fallback").unwrap_or("undefined".to_string());
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
                 format!(
-                    "createComponent(ErrorBoundary, {{ fallback: {}, children: {} }})",
+                    "_$createComponent(ErrorBoundary, {{ fallback: {}, children: {} }})",
                     fallback, children
                 ),
-                false,
-                false,
+                EscapeContext::Text,
             );
         }
 
         "NoHydration" => {
             // Special SSR component - renders children without hydration markers
             context.register_helper("NoHydration");
-            let children = get_children_ssr(element, transform_child);
+            let children = get_children_ssr(element, context, transform_child);
             result.push_dynamic(
-                format!("createComponent(NoHydration, {{ children: {} }})", children),
-                false,
-                true, // Don't escape - it handles its own output
+                format!(
+                    "_$createComponent(NoHydration, {{ children: {} }})",
+                    children
+                ),
+                EscapeContext::Raw, // Don't escape - it handles its own output
             );
         }
 
         _ => {
             // Unknown built-in, treat as regular component
-            result.push_dynamic(format!("createComponent({}, {{}})", tag_name), false, false);
+            result.push_dynamic(
+                format!("_$createComponent({}, {{}})", tag_name),
+                EscapeContext::Text,
+            );
         }
     }
 
     result
 }
 
+/// The tag of a `<Dynamic>` whose `component` is a string literal
+fn static_tag(element: &JSXElement) -> Option<String> {
+    match &find_prop(element, "component")?.value {
+        Some(JSXAttributeValue::StringLiteral(lit)) => Some(lit.value.to_string()),
+        Some(JSXAttributeValue::ExpressionContainer(container)) => {
+            match container.expression.as_expression()? {
+                Expression::StringLiteral(lit) => Some(lit.value.to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Build props object for a component
 fn build_props<'a, 'b>(
     element: &JSXElement<'a>,
-    context: &SSRContext,
-    _options: &TransformOptions<'a>,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
     transform_child: SSRChildTransformer<'a, 'b>,
 ) -> String {
-    let mut static_props: Vec<String> = vec![];
-    let mut dynamic_props: Vec<String> = vec![];
-    let mut spreads: Vec<String> = vec![];
+    let mut props = ComponentProps::new();
 
     for attr in &element.opening_element.attributes {
         match attr {
             JSXAttributeItem::Attribute(attr) => {
-                let key = match &attr.name {
-                    JSXAttributeName::Identifier(id) => id.name.to_string(),
-                    JSXAttributeName::NamespacedName(ns) => {
-                        format!("{}:{}", ns.namespace.name, ns.name.name)
-                    }
-                };
+                let key = get_attr_name(&attr.name);
 
                 // Skip event handlers and refs in SSR
-                if key.starts_with("on") || key == "ref" || key.starts_with("use:") {
+                if is_event_attr(&key) || key == "ref" || key.starts_with("use:") {
                     continue;
                 }
 
-                match &attr.value {
-                    Some(JSXAttributeValue::StringLiteral(lit)) => {
-                        static_props.push(format!("{}: \"{}\"", key, lit.value));
-                    }
-                    Some(JSXAttributeValue::ExpressionContainer(container)) => {
-                        if let Some(expr) = container.expression.as_expression() {
-                            let expr_str = expr_to_string(expr);
-                            if is_dynamic(expr) {
-                                dynamic_props
-                                    .push(format!("get {}() {{ return {}; }}", key, expr_str));
-                            } else {
-                                static_props.push(format!("{}: {}", key, expr_str));
-                            }
-                        }
-                    }
-                    None => {
-                        static_props.push(format!("{}: true", key));
-                    }
-                    _ => {}
-                }
+                props.push_attribute(&key, attr, context, options);
             }
             JSXAttributeItem::SpreadAttribute(spread) => {
-                spreads.push(expr_to_string(&spread.argument));
+                props.push_spread(expr_to_string(&spread.argument));
             }
         }
     }
 
     // Handle children
-    if !element.children.is_empty() {
-        let children = get_children_ssr(element, transform_child);
-        dynamic_props.push(format!("get children() {{ return {}; }}", children));
-    }
-
-    // Combine all props
-    let all_props = static_props
-        .into_iter()
-        .chain(dynamic_props)
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    // Combine props
-    if !spreads.is_empty() {
-        context.register_helper("mergeProps");
-        let spread_list = spreads.join(", ");
-        if all_props.is_empty() {
-            format!("mergeProps({})", spread_list)
-        } else {
-            format!("mergeProps({}, {{ {} }})", spread_list, all_props)
-        }
-    } else if all_props.is_empty() {
-        "{}".to_string()
+    if is_constant_children(&element.children) {
+        let children = children_values_ssr(element, context, transform_child);
+        props.push_children(children, true);
     } else {
-        format!("{{ {} }}", all_props)
+        let children = get_children_ssr(element, context, transform_child);
+        props.push_dynamic(format!("get children() {{ return {}; }}", children));
     }
+
+    props.finish(context, options)
 }
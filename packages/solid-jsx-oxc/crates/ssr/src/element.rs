@@ -4,256 +4,300 @@
 //! Unlike DOM, we don't create DOM nodes - we build strings.
 
 use oxc_ast::ast::{
-    JSXAttribute, JSXAttributeItem, JSXAttributeName, JSXAttributeValue, JSXElement,
+    Expression, JSXAttribute, JSXAttributeItem, JSXAttributeValue, JSXChild, JSXElement,
 };
 
 use common::{
-    constants::{ALIASES, CHILD_PROPERTIES, PROPERTIES, VOID_ELEMENTS},
+    attribute_value_code, child_property_conflict,
+    constants::{ALIASES, CHILD_PROPERTIES},
     expr_to_string,
-    expression::escape_html,
-    get_attr_name, is_svg_element, TransformOptions,
+    expression::{
+        class_list_conditions, class_template_literal, constant_text, escape_html,
+        object_to_style_string, trim_around_blocks,
+    },
+    find_child_property, get_attr_name, is_class_key, is_enumerated_attr, is_event_attr,
+    json_string, may_be_nullish, nesting_diagnostics, preserves_whitespace, ComponentProps,
+    Diagnostic, TransformContext, TransformOptions,
 };
 
-use crate::ir::{SSRContext, SSRResult};
+use crate::ir::{EscapeContext, SSRContext, SSRResult};
 
 /// Transform a native HTML/SVG element for SSR
 pub fn transform_element<'a>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &SSRContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
 ) -> SSRResult {
-    let is_void = VOID_ELEMENTS.contains(tag_name);
-    let is_script_or_style = tag_name == "script" || tag_name == "style";
+    let is_void = options.is_void_element(tag_name);
 
     let mut result = SSRResult::new();
     result.tag_name = Some(tag_name.to_string());
-    result.skip_escape = is_script_or_style;
+    result.content = content_escape(tag_name);
 
-    // Check for spread attributes - need different handling
-    let has_spread = element
+    if options.validate && !options.is_svg_element(tag_name) {
+        for diagnostic in nesting_diagnostics(element, tag_name) {
+            context.report(diagnostic);
+        }
+    }
+
+    result.has_spread = element
         .opening_element
         .attributes
         .iter()
         .any(|a| matches!(a, JSXAttributeItem::SpreadAttribute(_)));
 
-    if has_spread {
-        return transform_element_with_spread(element, tag_name, context, options);
-    }
-
     // Start the tag
     result.push_static(&format!("<{}", tag_name));
 
-    // Add hydration key if needed
-    if context.hydratable && options.hydratable {
+    // Only the root element of a template takes a hydration key
+    if !context.in_element && context.hydratable && options.hydratable {
         context.register_helper("ssrHydrationKey");
-        result.push_dynamic("ssrHydrationKey()".to_string(), false, true);
+        result.push_dynamic("_$ssrHydrationKey()".to_string(), EscapeContext::Raw);
     }
 
     // Transform attributes
-    transform_attributes(element, &mut result, context, options);
+    if result.has_spread {
+        transform_spread_attributes(element, &mut result, context, options);
+    } else {
+        transform_attributes(element, &mut result, context, options);
+    }
 
     // Close opening tag
     result.push_static(">");
 
     // Transform children (if not void element)
     if !is_void {
+        let preserve = options.minify_whitespace && preserves_whitespace(element, tag_name);
+        if preserve {
+            context.module.preserve_whitespace += 1;
+        }
+        // `<noscript>` content is rendered as markup, but the client never hydrates
+        // it, so it must not take hydration keys
+        let hydratable = context.hydratable;
+        if tag_name == "noscript" {
+            context.hydratable = false;
+        }
+        let in_element = std::mem::replace(&mut context.in_element, true);
         transform_children(element, &mut result, context, options);
+        context.in_element = in_element;
+        context.hydratable = hydratable;
+        if preserve {
+            context.module.preserve_whitespace -= 1;
+        }
         result.push_static(&format!("</{}>", tag_name));
     }
 
     result
 }
 
-/// Transform element with spread attributes using ssrElement()
-fn transform_element_with_spread<'a>(
+/// Render the attributes of an element with a spread through `ssrSpread`, which
+/// writes them out at runtime. Attributes after a spread override its values.
+fn transform_spread_attributes<'a>(
+    element: &JSXElement<'a>,
+    result: &mut SSRResult,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
+) {
+    let tag_name = result.tag_name.as_deref().unwrap_or("");
+    let is_svg = options.is_svg_element(tag_name);
+    let props = runtime_props(element, tag_name, None, context, options);
+
+    // Spread `children` and `innerHTML` only render when the element has none
+    let has_children =
+        element.children.iter().any(renders_content) || find_child_property(element).is_some();
+    context.register_helper("ssrSpread");
+    result.push_dynamic(
+        format!("_$ssrSpread({}, {}, {})", props, is_svg, has_children),
+        EscapeContext::Raw,
+    );
+}
+
+/// Render `<Dynamic component="h1">` through `ssrElement`, which writes the tag
+/// and its props out at runtime. The children are compiled like those of a
+/// native element.
+pub fn transform_dynamic_element<'a>(
     element: &JSXElement<'a>,
     tag_name: &str,
-    context: &SSRContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
 ) -> SSRResult {
-    context.register_helper("ssrElement");
-    context.register_helper("escape");
+    let needs_id = !context.in_element && context.hydratable && options.hydratable;
+    let props = runtime_props(element, tag_name, Some("component"), context, options);
+
+    let mut children = SSRResult::new();
+    children.content = content_escape(tag_name);
+    if !options.is_void_element(tag_name) {
+        let in_element = std::mem::replace(&mut context.in_element, true);
+        transform_children(element, &mut children, context, options);
+        context.in_element = in_element;
+    }
+    let is_markup = children.template_parts.iter().any(|part| !part.is_empty());
+    let children = match children.template_values.as_slice() {
+        [] if !is_markup => "undefined".to_string(),
+        // Static markup is passed as a string literal
+        [] => children.to_ssr_call(context),
+        // A lone value is otherwise passed as is, and the runtime doesn't escape it
+        [value] if !is_markup && value.context != EscapeContext::Raw => {
+            context.register_helper("escape");
+            format!("() => {}", value.context.escape(&value.expr))
+        }
+        _ => format!("() => {}", children.to_ssr_call(context)),
+    };
 
+    context.register_helper("ssrElement");
     let mut result = SSRResult::new();
-    result.has_spread = true;
+    result.push_dynamic(
+        format!(
+            "_$ssrElement({}, {}, {}, {})",
+            json_string(tag_name),
+            props,
+            children,
+            needs_id
+        ),
+        EscapeContext::Raw,
+    );
+    result
+}
 
-    // Build props object - merge spreads with regular attributes
-    let mut props_parts: Vec<String> = vec![];
-    let is_svg = is_svg_element(tag_name);
+/// The attributes of an element as a props object, for the runtime to write
+/// out. `exclude` names an attribute that belongs to the JSX tag itself.
+fn runtime_props<'a>(
+    element: &JSXElement<'a>,
+    tag_name: &str,
+    exclude: Option<&str>,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
+) -> String {
+    let is_svg = options.is_svg_element(tag_name);
 
+    let mut props = ComponentProps::new();
     for attr in &element.opening_element.attributes {
         match attr {
             JSXAttributeItem::SpreadAttribute(spread) => {
-                props_parts.push(format!("...{}", expr_to_string(&spread.argument)));
+                props.push_spread(expr_to_string(&spread.argument));
             }
             JSXAttributeItem::Attribute(attr) => {
                 let key = get_attr_name(&attr.name);
-                // Skip client-only attributes
-                if key == "ref"
-                    || key.starts_with("on")
-                    || key.starts_with("use:")
-                    || key.starts_with("prop:")
+                if exclude == Some(key.as_str())
+                    || is_client_only_attr(attr, &key, tag_name, context, options)
+                    || CHILD_PROPERTIES.contains(key.as_str())
                 {
                     continue;
                 }
-
                 let attr_name = if is_svg {
-                    key.clone()
+                    key.as_str()
                 } else {
-                    ALIASES
-                        .get(key.as_str())
-                        .copied()
-                        .unwrap_or(&key)
-                        .to_string()
+                    ALIASES.get(key.as_str()).copied().unwrap_or(&key)
                 };
-
-                match &attr.value {
-                    Some(JSXAttributeValue::StringLiteral(lit)) => {
-                        props_parts.push(format!(
-                            "\"{}\": \"{}\"",
-                            attr_name,
-                            escape_html(&lit.value, true)
-                        ));
-                    }
-                    Some(JSXAttributeValue::ExpressionContainer(container)) => {
-                        if let Some(expr) = container.expression.as_expression() {
-                            props_parts.push(format!(
-                                "\"{}\": {}",
-                                attr_name,
-                                expr_to_string(expr)
-                            ));
-                        }
-                    }
-                    None => {
-                        props_parts.push(format!("\"{}\": true", attr_name));
-                    }
-                    _ => {}
-                }
+                props.push_attribute(&json_string(attr_name), attr, context, options);
             }
         }
     }
-
-    let props_str = if props_parts.is_empty() {
-        "{}".to_string()
-    } else {
-        format!("{{ {} }}", props_parts.join(", "))
-    };
-
-    // Build children
-    let children_str = if element.children.is_empty() {
-        "null".to_string()
-    } else {
-        let mut children: Vec<String> = vec![];
-        for child in &element.children {
-            match child {
-                oxc_ast::ast::JSXChild::Text(text) => {
-                    let content = common::expression::trim_whitespace(&text.value);
-                    if !content.is_empty() {
-                        children.push(format!("\"{}\"", escape_html(&content, false)));
-                    }
-                }
-                oxc_ast::ast::JSXChild::ExpressionContainer(container) => {
-                    if let Some(expr) = container.expression.as_expression() {
-                        context.register_helper("escape");
-                        children.push(format!("escape({})", expr_to_string(expr)));
-                    }
-                }
-                oxc_ast::ast::JSXChild::Element(child_elem) => {
-                    // Recursively transform child element - check if component or native
-                    let child_tag = common::get_tag_name(child_elem);
-                    let child_result = if common::is_component(&child_tag) {
-                        // Component - use component transformer
-                        let child_transformer =
-                            |child: &oxc_ast::ast::JSXChild<'a>| -> Option<SSRResult> {
-                                match child {
-                                    oxc_ast::ast::JSXChild::Element(el) => {
-                                        let tag = common::get_tag_name(el);
-                                        Some(if common::is_component(&tag) {
-                                            let mut r = SSRResult::new();
-                                            r.push_dynamic(
-                                                format!("createComponent({}, {{}})", tag),
-                                                false,
-                                                false,
-                                            );
-                                            r
-                                        } else {
-                                            transform_element(el, &tag, context, options)
-                                        })
-                                    }
-                                    _ => None,
-                                }
-                            };
-                        crate::component::transform_component(
-                            child_elem,
-                            &child_tag,
-                            context,
-                            options,
-                            &child_transformer,
-                        )
-                    } else {
-                        transform_element(child_elem, &child_tag, context, options)
-                    };
-                    children.push(child_result.to_ssr_call_with_hydration(context.hydratable));
-                }
-                _ => {}
-            }
-        }
-        if children.len() == 1 {
-            children.pop().unwrap_or("null".to_string())
-        } else if children.is_empty() {
-            "null".to_string()
-        } else {
-            format!("[{}]", children.join(", "))
-        }
-    };
-
-    // For spread, we generate: ssrElement("tag", props, children, needsHydrationKey)
-    result.push_dynamic(
-        format!(
-            "ssrElement(\"{}\", {}, {}, {})",
-            tag_name,
-            props_str,
-            children_str,
-            context.hydratable && options.hydratable
-        ),
-        false,
-        true,
-    );
-
-    result
+    props.finish(context, options)
 }
 
 /// Transform element attributes for SSR
 fn transform_attributes<'a>(
     element: &JSXElement<'a>,
     result: &mut SSRResult,
-    context: &SSRContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
 ) {
     let tag_name = result.tag_name.as_deref().unwrap_or("");
-    let is_svg = is_svg_element(tag_name);
+    let is_svg = options.is_svg_element(tag_name);
+
+    // Several class attributes render as one `class`
+    let class_attrs: Vec<&JSXAttribute> = element
+        .opening_element
+        .attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            JSXAttributeItem::Attribute(attr) if is_class_key(&get_attr_name(&attr.name)) => {
+                Some(&**attr)
+            }
+            _ => None,
+        })
+        .collect();
+    let merge_classes = class_attrs.len() > 1;
+    if merge_classes {
+        transform_merged_class(&class_attrs, result, context);
+    }
 
     for attr in &element.opening_element.attributes {
         if let JSXAttributeItem::Attribute(attr) = attr {
+            if merge_classes && is_class_key(&get_attr_name(&attr.name)) {
+                continue;
+            }
             transform_attribute(attr, result, context, options, is_svg);
         }
     }
 }
 
+/// Render `class`, `className` and `classList` of one element as a single
+/// `class` attribute, with all dynamic classes in one interpolation
+fn transform_merged_class(
+    attrs: &[&JSXAttribute],
+    result: &mut SSRResult,
+    context: &mut SSRContext,
+) {
+    let mut static_classes = vec![];
+    let mut dynamic = vec![];
+    for attr in attrs {
+        match &attr.value {
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                let classes = lit.value.trim();
+                if !classes.is_empty() {
+                    static_classes.push(classes.to_string());
+                }
+            }
+            Some(JSXAttributeValue::ExpressionContainer(container)) => {
+                let Some(expr) = container.expression.as_expression() else {
+                    continue;
+                };
+                if get_attr_name(&attr.name) != "classList" {
+                    dynamic.push(format!("{} || \"\"", expr_to_string(expr)));
+                } else if let Some(conditions) = class_list_conditions(expr) {
+                    dynamic.extend(conditions);
+                } else {
+                    context.register_helper("ssrClassList");
+                    dynamic.push(format!("_$ssrClassList({})", expr_to_string(expr)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let static_classes = escape_html(&static_classes.join(" "), true);
+    if dynamic.is_empty() {
+        if !static_classes.is_empty() {
+            result.push_static(&format!(" class=\"{}\"", static_classes));
+        }
+        return;
+    }
+    result.push_static(&format!(" class=\"{}", static_classes));
+    if !static_classes.is_empty() {
+        result.push_static(" ");
+    }
+    result.push_dynamic(
+        class_template_literal("", &dynamic),
+        EscapeContext::Attribute,
+    );
+    result.push_static("\"");
+}
+
 /// Transform a single attribute for SSR
 fn transform_attribute<'a>(
     attr: &JSXAttribute<'a>,
     result: &mut SSRResult,
-    context: &SSRContext,
-    _options: &TransformOptions<'a>,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
     is_svg: bool,
 ) {
     let key = get_attr_name(&attr.name);
 
-    // Skip client-only attributes
-    if key == "ref" || key.starts_with("on") || key.starts_with("use:") || key.starts_with("prop:")
-    {
+    let tag_name = result.tag_name.as_deref().unwrap_or("");
+    if is_client_only_attr(attr, &key, tag_name, context, options) {
         return;
     }
 
@@ -284,41 +328,106 @@ fn transform_attribute<'a>(
         // Dynamic value
         Some(JSXAttributeValue::ExpressionContainer(container)) => {
             if let Some(expr) = container.expression.as_expression() {
-                let expr_str = expr_to_string(expr);
-                context.register_helper("escape");
+                // Folded at build time, e.g. `{"btn-" + "primary"}`. Style objects
+                // and boolean attributes keep their runtime handling.
+                let constant = constant_text(expr).filter(|_| {
+                    key != "style"
+                        && key != "classList"
+                        && !options.is_boolean_attribute(&attr_name)
+                });
+                if let Some(value) = constant {
+                    let escaped = escape_html(&value, true);
+                    result.push_static(&format!(" {}=\"{}\"", attr_name, escaped));
+                    return;
+                }
+                // A literal boolean is settled too: boolean attributes are present
+                // or left out, any other attribute gets "true" or "false"
+                if let Expression::BooleanLiteral(lit) = expr.without_parentheses() {
+                    if !options.is_boolean_attribute(&attr_name) {
+                        result.push_static(&format!(" {}=\"{}\"", attr_name, lit.value));
+                    } else if lit.value {
+                        result.push_static(&format!(" {}", attr_name));
+                    }
+                    return;
+                }
+                // A style object of literals is serialized at build time
+                let static_style = match expr {
+                    Expression::ObjectExpression(obj) if key == "style" => {
+                        object_to_style_string(obj)
+                    }
+                    _ => None,
+                };
+                if let Some(style) = static_style {
+                    let escaped = escape_html(&style, true);
+                    result.push_static(&format!(" {}=\"{}\"", attr_name, escaped));
+                    return;
+                }
+                let expr_str = attribute_value_code(expr, context, options);
 
                 // Handle special attributes
                 if key == "style" {
                     context.register_helper("ssrStyle");
                     result.push_static(&format!(" {}=\"", attr_name));
-                    result.push_dynamic(format!("ssrStyle({})", expr_str), false, true);
+                    result.push_dynamic(format!("_$ssrStyle({})", expr_str), EscapeContext::Raw);
                     result.push_static("\"");
                 } else if key == "class" || key == "className" {
                     result.push_static(&format!(" {}=\"", attr_name));
-                    result.push_dynamic(expr_str, true, false);
+                    result.push_dynamic(expr_str, EscapeContext::Attribute);
                     result.push_static("\"");
                 } else if key == "classList" {
                     context.register_helper("ssrClassList");
                     result.push_static(" class=\"");
-                    result.push_dynamic(format!("ssrClassList({})", expr_str), false, true);
+                    result
+                        .push_dynamic(format!("_$ssrClassList({})", expr_str), EscapeContext::Raw);
                     result.push_static("\"");
-                } else if PROPERTIES.contains(key.as_str()) {
+                } else if attr_name.starts_with("aria-") {
+                    // Rendered as "true"/"false", and left out for null/undefined
+                    context.register_helper("ssrAttribute");
+                    context.register_helper("escape");
+                    result.push_dynamic(
+                        format!(
+                            "_$ssrAttribute(\"{}\", _$escape({}, true), false)",
+                            attr_name, expr_str
+                        ),
+                        EscapeContext::Raw,
+                    );
+                } else if is_enumerated_attr(&attr_name) {
+                    // Booleans are written out, `false` must not drop the attribute
+                    result.push_static(&format!(" {}=\"", attr_name));
+                    result.push_dynamic(format!("String({})", expr_str), EscapeContext::Attribute);
+                    result.push_static("\"");
+                } else if options.is_boolean_attribute(&attr_name) {
                     // Boolean attributes
                     context.register_helper("ssrAttribute");
                     result.push_dynamic(
-                        format!("ssrAttribute(\"{}\", {}, true)", attr_name, expr_str),
-                        false,
-                        true,
+                        format!("_$ssrAttribute(\"{}\", {}, true)", attr_name, expr_str),
+                        EscapeContext::Raw,
+                    );
+                } else if may_be_nullish(expr) {
+                    // `{active ? "page" : undefined}` leaves the attribute out
+                    context.register_helper("ssrAttribute");
+                    context.register_helper("escape");
+                    result.push_dynamic(
+                        format!(
+                            "_$ssrAttribute(\"{}\", _$escape({}, true), false)",
+                            attr_name, expr_str
+                        ),
+                        EscapeContext::Raw,
                     );
                 } else {
                     // Regular attribute
                     result.push_static(&format!(" {}=\"", attr_name));
-                    result.push_dynamic(expr_str, true, false);
+                    result.push_dynamic(expr_str, EscapeContext::Attribute);
                     result.push_static("\"");
                 }
             }
         }
 
+        // Enumerated and ARIA attributes have no empty state meaning true
+        None if is_enumerated_attr(&attr_name) || attr_name.starts_with("aria-") => {
+            result.push_static(&format!(" {}=\"true\"", attr_name));
+        }
+
         // Boolean attribute (no value)
         None => {
             result.push_static(&format!(" {}", attr_name));
@@ -328,44 +437,80 @@ fn transform_attribute<'a>(
     }
 }
 
+/// Check whether an attribute only has meaning on the client and must be left out of the HTML.
+///
+/// Event handlers are reported when `ssr_event_diagnostics` is enabled, since they
+/// only attach once the page hydrates.
+fn is_client_only_attr(
+    attr: &JSXAttribute<'_>,
+    key: &str,
+    tag_name: &str,
+    context: &mut SSRContext,
+    options: &TransformOptions<'_>,
+) -> bool {
+    // There is no element to hand out on the server, so refs compile to nothing.
+    // The value is dropped without being evaluated, keeping SSR side-effect free.
+    if key == "ref" {
+        return true;
+    }
+
+    if is_event_attr(key) {
+        if options.ssr_event_diagnostics {
+            context.report(Diagnostic::warning(
+                format!(
+                    "`{}` on <{}> will only attach after hydration",
+                    key, tag_name
+                ),
+                attr.span,
+            ));
+        }
+        return true;
+    }
+
+    key.starts_with("use:") || key.starts_with("prop:")
+}
+
 /// Transform element children for SSR
 fn transform_children<'a>(
     element: &JSXElement<'a>,
     result: &mut SSRResult,
-    context: &SSRContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
 ) {
-    // Check for innerHTML/textContent in attributes first
-    for attr in &element.opening_element.attributes {
-        if let JSXAttributeItem::Attribute(attr) = attr {
-            let key = match &attr.name {
-                JSXAttributeName::Identifier(id) => id.name.as_str(),
-                _ => continue,
-            };
-
-            if key == "innerHTML" {
-                if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
-                    if let Some(expr) = container.expression.as_expression() {
-                        // innerHTML - don't escape
-                        result.push_dynamic(expr_to_string(expr), false, true);
-                        return;
-                    }
-                }
-            } else if key == "textContent" || key == "innerText" {
-                if let Some(JSXAttributeValue::ExpressionContainer(container)) = &attr.value {
-                    if let Some(expr) = container.expression.as_expression() {
-                        context.register_helper("escape");
-                        result.push_dynamic(expr_to_string(expr), false, false);
-                        return;
-                    }
+    // `innerHTML`, `textContent` and `innerText` replace the children
+    if let Some(property) = find_child_property(element) {
+        if let Some(diagnostic) = child_property_conflict(element, property) {
+            context.report(diagnostic);
+        }
+        let is_html = get_attr_name(&property.name) == "innerHTML";
+        match &property.value {
+            Some(JSXAttributeValue::StringLiteral(lit)) if is_html => {
+                result.push_static(&lit.value);
+            }
+            Some(JSXAttributeValue::StringLiteral(lit)) => {
+                result.push_static(&escape_html(&lit.value, false));
+            }
+            Some(JSXAttributeValue::ExpressionContainer(container)) => {
+                if let Some(expr) = container.expression.as_expression() {
+                    // innerHTML is written as is, text is escaped
+                    result.push_dynamic(
+                        expr_to_string(expr),
+                        if is_html {
+                            EscapeContext::Raw
+                        } else {
+                            EscapeContext::Text
+                        },
+                    );
                 }
             }
+            _ => {}
         }
+        return;
     }
 
     // Process children
-    let skip_escape = result.skip_escape;
-    process_jsx_children(&element.children, result, skip_escape, context, options);
+    let content = result.content;
+    process_jsx_children(&element.children, result, content, context, options);
 }
 
 /// Process a list of JSX children, appending to the result.
@@ -373,82 +518,150 @@ fn transform_children<'a>(
 fn process_jsx_children<'a>(
     children: &oxc_allocator::Vec<'a, oxc_ast::ast::JSXChild<'a>>,
     result: &mut SSRResult,
-    skip_escape: bool,
-    context: &SSRContext,
+    content: EscapeContext,
+    context: &mut SSRContext,
     options: &TransformOptions<'a>,
 ) {
-    for child in children {
+    let minify = options.minify_whitespace && !context.preserves_whitespace();
+    // Among several children the hydrator finds each dynamic one between
+    // `<!--$-->` and `<!--/-->` comments
+    let markers = context.hydratable
+        && options.hydratable
+        && content != EscapeContext::Raw
+        && children
+            .iter()
+            .filter(|child| renders_content(child))
+            .count()
+            > 1;
+
+    for (index, child) in children.iter().enumerate() {
         match child {
             oxc_ast::ast::JSXChild::Text(text) => {
-                let content = common::expression::trim_whitespace(&text.value);
-                if !content.is_empty() {
-                    if skip_escape {
-                        result.push_static(&content);
-                    } else {
-                        result.push_static(&escape_html(&content, false));
-                    }
+                let mut text = common::expression::trim_whitespace(&text.value);
+                if minify {
+                    text = trim_around_blocks(&text, children, index);
+                }
+                if !text.is_empty() {
+                    push_text(result, &text, content);
                 }
             }
 
             oxc_ast::ast::JSXChild::Element(child_elem) => {
-                let child_tag = common::get_tag_name(child_elem);
-                let child_result = if common::is_component(&child_tag) {
-                    // Create a child transformer for nested components
-                    let child_transformer =
-                        |child: &oxc_ast::ast::JSXChild<'a>| -> Option<SSRResult> {
-                            match child {
-                                oxc_ast::ast::JSXChild::Element(el) => {
-                                    let tag = common::get_tag_name(el);
-                                    Some(if common::is_component(&tag) {
-                                        // For deeply nested components, use simple fallback
-                                        let mut r = SSRResult::new();
-                                        r.push_dynamic(
-                                            format!("createComponent({}, {{}})", tag),
-                                            false,
-                                            false,
-                                        );
-                                        r
-                                    } else {
-                                        transform_element(el, &tag, context, options)
-                                    })
-                                }
-                                _ => None,
-                            }
-                        };
-                    crate::component::transform_component(
-                        child_elem,
-                        &child_tag,
-                        context,
-                        options,
-                        &child_transformer,
-                    )
-                } else {
-                    transform_element(child_elem, &child_tag, context, options)
-                };
-                result.merge(child_result);
+                result.merge(transform_child_element(child_elem, context, options));
             }
 
             oxc_ast::ast::JSXChild::ExpressionContainer(container) => {
                 if let Some(expr) = container.expression.as_expression() {
-                    let expr_str = expr_to_string(expr);
-                    context.register_helper("escape");
-
-                    if skip_escape {
-                        // Inside script/style - don't escape
-                        result.push_dynamic(expr_str, false, true);
+                    // Constant text is part of the markup, like static text
+                    if let Some(text) = constant_text(expr) {
+                        push_text(result, &text, content);
+                        continue;
+                    }
+                    // Booleans render nothing
+                    if is_boolean_literal(expr) {
+                        continue;
+                    }
+                    // Nested JSX is markup already compiled by the `ssr` tag
+                    let escape = if is_compiled_markup(expr) {
+                        EscapeContext::Raw
                     } else {
-                        // Normal content - escape
-                        result.push_dynamic(expr_str, false, false);
+                        content
+                    };
+                    if markers {
+                        result.push_static("<!--$-->");
+                        result.push_dynamic(expr_to_string(expr), escape);
+                        result.push_static("<!--/-->");
+                    } else {
+                        result.push_dynamic(expr_to_string(expr), escape);
                     }
                 }
             }
 
             oxc_ast::ast::JSXChild::Fragment(fragment) => {
                 // Recursively process fragment children with same escape settings
-                process_jsx_children(&fragment.children, result, skip_escape, context, options);
+                process_jsx_children(&fragment.children, result, content, context, options);
             }
 
             _ => {}
         }
     }
 }
+
+/// Transform an element among the children of an element or component. A
+/// component recurses with a transformer for its own children, so components
+/// nested at any depth keep their props and children.
+fn transform_child_element<'a>(
+    element: &JSXElement<'a>,
+    context: &mut SSRContext,
+    options: &TransformOptions<'a>,
+) -> SSRResult {
+    let tag_name = common::get_tag_name(element);
+    if !common::is_component(&tag_name) {
+        return transform_element(element, &tag_name, context, options);
+    }
+    let child_transformer = |child: &JSXChild<'a>, context: &mut SSRContext| -> Option<SSRResult> {
+        match child {
+            JSXChild::Element(element) => Some(transform_child_element(element, context, options)),
+            JSXChild::Fragment(fragment) => {
+                let mut result = SSRResult::new();
+                process_jsx_children(
+                    &fragment.children,
+                    &mut result,
+                    EscapeContext::Text,
+                    context,
+                    options,
+                );
+                Some(result)
+            }
+            _ => None,
+        }
+    };
+    crate::component::transform_component(element, &tag_name, context, options, &child_transformer)
+}
+
+/// Whether a child puts anything in the output, skipping whitespace-only text,
+/// empty `{}` containers and literal booleans
+fn renders_content(child: &JSXChild) -> bool {
+    match child {
+        JSXChild::Text(text) => !common::expression::trim_whitespace(&text.value).is_empty(),
+        JSXChild::ExpressionContainer(container) => container
+            .expression
+            .as_expression()
+            .is_some_and(|expr| !is_boolean_literal(expr)),
+        _ => true,
+    }
+}
+
+/// Whether an expression is `true` or `false`
+fn is_boolean_literal(expr: &Expression) -> bool {
+    matches!(expr.without_parentheses(), Expression::BooleanLiteral(_))
+}
+
+/// How the content of an element is escaped. Scripts and styles are raw text
+/// elements: entities aren't decoded there, so their content is written as is.
+fn content_escape(tag_name: &str) -> EscapeContext {
+    if tag_name == "script" || tag_name == "style" {
+        EscapeContext::Raw
+    } else {
+        EscapeContext::Text
+    }
+}
+
+/// Append static text, escaped for the content it is part of
+fn push_text(result: &mut SSRResult, text: &str, content: EscapeContext) {
+    if content == EscapeContext::Raw {
+        result.push_static(text);
+    } else {
+        result.push_static(&escape_html(text, false));
+    }
+}
+
+/// Whether an expression is a call of the `ssr` tag, as nested JSX compiles to
+pub(crate) fn is_compiled_markup(expr: &Expression) -> bool {
+    match expr.without_parentheses() {
+        Expression::CallExpression(call) => {
+            matches!(&call.callee, Expression::Identifier(ident) if ident.name == "_$ssr")
+        }
+        _ => false,
+    }
+}
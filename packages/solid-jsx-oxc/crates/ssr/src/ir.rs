@@ -2,12 +2,12 @@
 //!
 //! SSR uses a simpler IR than DOM since we're just building template strings.
 
-use indexmap::IndexSet;
+use common::{json_array, json_object, json_string, ModuleState, TransformContext};
 use oxc_ast::ast::JSXChild;
-use std::cell::RefCell;
 
 /// Function type for transforming child JSX elements
-pub type SSRChildTransformer<'a, 'b> = &'b dyn Fn(&JSXChild<'a>) -> Option<SSRResult>;
+pub type SSRChildTransformer<'a, 'b> =
+    &'b dyn Fn(&JSXChild<'a>, &mut SSRContext) -> Option<SSRResult>;
 
 /// The result of transforming a JSX node for SSR
 #[derive(Default)]
@@ -21,8 +21,8 @@ pub struct SSRResult {
     /// Whether this needs a hydration key
     pub needs_hydration_key: bool,
 
-    /// Whether to skip escaping (for innerHTML, script, style)
-    pub skip_escape: bool,
+    /// How the children are escaped: raw for script and style
+    pub content: EscapeContext,
 
     /// Whether this contains a spread attribute
     pub has_spread: bool,
@@ -36,14 +36,40 @@ pub struct TemplateValue {
     /// The expression code
     pub expr: String,
 
-    /// Whether this is an attribute value (uses different escaping)
-    pub is_attr: bool,
+    /// Where the value lands in the markup, which decides how it is escaped
+    pub context: EscapeContext,
+}
+
+/// The position of a dynamic value in the markup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EscapeContext {
+    /// Element content, where `<` and `&` are escaped: `_$escape(value)`
+    #[default]
+    Text,
+    /// A quoted attribute value, where `"` is escaped too: `_$escape(value, true)`
+    Attribute,
+    /// Written as is: raw text elements, `innerHTML`, and helpers that render
+    /// their own markup
+    Raw,
+}
 
-    /// Whether to skip escaping entirely
-    pub skip_escape: bool,
+impl EscapeContext {
+    /// The code writing `expr` in this position
+    pub fn escape(self, expr: &str) -> String {
+        match self {
+            EscapeContext::Text => format!("_$escape({})", expr),
+            EscapeContext::Attribute => format!("_$escape({}, true)", expr),
+            EscapeContext::Raw => expr.to_string(),
+        }
+    }
 
-    /// Whether this needs hydration markers (for dynamic children)
-    pub needs_hydration_marker: bool,
+    fn as_str(self) -> &'static str {
+        match self {
+            EscapeContext::Text => "text",
+            EscapeContext::Attribute => "attribute",
+            EscapeContext::Raw => "raw",
+        }
+    }
 }
 
 impl SSRResult {
@@ -52,6 +78,26 @@ impl SSRResult {
         Self::default()
     }
 
+    /// The result as JSON, for the IR dump
+    pub fn to_json(&self) -> String {
+        json_object([
+            (
+                "templateParts",
+                json_array(self.template_parts.iter().map(|part| json_string(part))),
+            ),
+            (
+                "values",
+                json_array(self.template_values.iter().map(|value| {
+                    json_object([
+                        ("expr", json_string(&value.expr)),
+                        ("escape", json_string(value.context.as_str())),
+                    ])
+                })),
+            ),
+            ("needsHydrationKey", self.needs_hydration_key.to_string()),
+        ])
+    }
+
     /// Append static text to the template
     pub fn push_static(&mut self, text: &str) {
         if self.template_parts.is_empty() {
@@ -62,29 +108,13 @@ impl SSRResult {
         }
     }
 
-    /// Append a dynamic value
-    pub fn push_dynamic(&mut self, expr: String, is_attr: bool, skip_escape: bool) {
-        self.push_dynamic_with_marker(expr, is_attr, skip_escape, !is_attr)
-    }
-
-    /// Append a dynamic value with explicit hydration marker control
-    pub fn push_dynamic_with_marker(
-        &mut self,
-        expr: String,
-        is_attr: bool,
-        skip_escape: bool,
-        needs_marker: bool,
-    ) {
+    /// Append a dynamic value, escaped for the position it lands in
+    pub fn push_dynamic(&mut self, expr: String, context: EscapeContext) {
         // Ensure we have a template part before this value
         if self.template_parts.len() == self.template_values.len() {
             self.template_parts.push(String::new());
         }
-        self.template_values.push(TemplateValue {
-            expr,
-            is_attr,
-            skip_escape,
-            needs_hydration_marker: needs_marker,
-        });
+        self.template_values.push(TemplateValue { expr, context });
         // Add empty part for after this value
         self.template_parts.push(String::new());
     }
@@ -102,84 +132,92 @@ impl SSRResult {
         self.template_values.extend(other.template_values);
     }
 
-    /// Generate the final ssr tagged template call
-    pub fn to_ssr_call(&self) -> String {
-        self.to_ssr_call_with_hydration(false)
-    }
-
-    /// Generate the final ssr tagged template call with optional hydration markers
-    pub fn to_ssr_call_with_hydration(&self, hydratable: bool) -> String {
+    /// Generate the final `ssr` call.
+    ///
+    /// The static parts are hoisted to a module-level `_tmpl$` array, shared by
+    /// every template with the same parts, and the values follow it as
+    /// arguments: `_$ssr(_tmpl$1, _$escape(count()))`.
+    pub fn to_ssr_call(&self, context: &mut impl TransformContext) -> String {
         if self.template_values.is_empty() {
             // No dynamic values, just return static string
-            format!("\"{}\"", self.template_parts.join(""))
-        } else {
-            // Build ssr`...` tagged template
-            let mut result = String::from("ssr`");
-
-            for (i, part) in self.template_parts.iter().enumerate() {
-                result.push_str(part);
-                if i < self.template_values.len() {
-                    let val = &self.template_values[i];
-
-                    // Add hydration marker before dynamic content (not for attributes)
-                    if hydratable && !val.is_attr && val.needs_hydration_marker {
-                        result.push_str("<!--#-->");
-                    }
-
-                    result.push_str("${");
-                    if val.skip_escape {
-                        result.push_str(&val.expr);
-                    } else if val.is_attr {
-                        result.push_str(&format!("escape({}, true)", val.expr));
-                    } else {
-                        result.push_str(&format!("escape({})", val.expr));
-                    }
-                    result.push('}');
-
-                    // Add closing hydration marker
-                    if hydratable && !val.is_attr && val.needs_hydration_marker {
-                        result.push_str("<!--/-->");
-                    }
-                }
+            return json_string(&self.template_parts.concat());
+        }
+        // A component or expression without markup around it is the value itself
+        if let [value] = self.template_values.as_slice() {
+            if self.template_parts.iter().all(String::is_empty) {
+                return value.expr.clone();
             }
+        }
 
-            result.push('`');
-            result
+        let mut values = vec![];
+        for val in &self.template_values {
+            if val.context != EscapeContext::Raw {
+                context.register_helper("escape");
+            }
+            values.push(val.context.escape(&val.expr));
         }
+
+        context.register_helper("ssr");
+        let template = context.hoist(
+            "tmpl$",
+            json_array(self.template_parts.iter().map(|part| json_string(part))),
+        );
+        format!("_$ssr({}, {})", template, values.join(", "))
     }
 }
 
 /// Context for SSR block transformation
+///
+/// Like the DOM `BlockContext`, this is owned by one transform and passed
+/// down as `&mut` rather than shared.
 #[derive(Default)]
 pub struct SSRContext {
-    /// Helper imports needed
-    pub helpers: RefCell<IndexSet<String>>,
+    /// Helper imports, generated names, hoisted constants and diagnostics
+    pub module: ModuleState,
 
-    /// Variable counter for unique names
-    pub var_counter: RefCell<usize>,
+    /// Counter for component boundary ids
+    pub component_counter: usize,
 
     /// Whether we're in hydratable mode
     pub hydratable: bool,
+
+    /// Whether the element being transformed sits inside another element's
+    /// template, so it isn't a hydration root
+    pub in_element: bool,
 }
 
 impl SSRContext {
     pub fn new(hydratable: bool) -> Self {
         Self {
-            helpers: RefCell::new(IndexSet::new()),
-            var_counter: RefCell::new(0),
             hydratable,
+            ..Default::default()
         }
     }
 
-    /// Generate a unique variable name
-    pub fn generate_uid(&self, prefix: &str) -> String {
-        let mut counter = self.var_counter.borrow_mut();
-        *counter += 1;
-        format!("_{}{}", prefix, *counter)
+    /// The file-level state as JSON, for the IR dump
+    pub fn to_json(&self) -> String {
+        json_object([
+            (
+                "helpers",
+                json_array(self.module.helpers.iter().map(|s| json_string(s))),
+            ),
+            ("hydratable", self.hydratable.to_string()),
+        ])
+    }
+
+    /// Generate the id for the next component boundary
+    pub fn next_component_id(&mut self) -> usize {
+        self.component_counter += 1;
+        self.component_counter
+    }
+}
+
+impl TransformContext for SSRContext {
+    fn module(&self) -> &ModuleState {
+        &self.module
     }
 
-    /// Register a helper import
-    pub fn register_helper(&self, name: &str) {
-        self.helpers.borrow_mut().insert(name.to_string());
+    fn module_mut(&mut self) -> &mut ModuleState {
+        &mut self.module
     }
 }
@@ -1,7 +1,7 @@
 //! SSR (Server-Side Rendering) transform for SolidJS
 //!
-//! This crate generates SSR output that uses template strings and
-//! escape() calls instead of DOM operations.
+//! This crate generates SSR output that joins hoisted arrays of static markup
+//! with escaped values instead of performing DOM operations.
 //!
 //! ## Output Format
 //!
@@ -10,9 +10,12 @@
 //! <div class={style()}>{count()}</div>
 //!
 //! // SSR Output
-//! ssr`<div${ssrHydrationKey()} class="${escape(style(), true)}">${escape(count())}</div>`
+//! const _tmpl$1 = ["<div", " class=\"", "\">", "</div>"];
+//! _$ssr(_tmpl$1, _$ssrHydrationKey(), _$escape(style(), true), _$escape(count()))
 //! ```
 
+#![forbid(unsafe_code)]
+
 pub mod component;
 pub mod element;
 pub mod ir;
@@ -1,18 +1,18 @@
 //! SSR template generation
 //!
-//! Unlike DOM which uses template() + cloneNode(), SSR uses
-//! the ssr`` tagged template literal.
+//! Unlike DOM which uses template() + cloneNode(), SSR passes hoisted arrays of
+//! static markup and the values between them to `ssr()`.
 
 use crate::ir::{SSRContext, SSRResult};
 
 /// Generate the final SSR output code from a result
-pub fn generate_ssr_code(result: &SSRResult, context: &SSRContext) -> String {
+pub fn generate_ssr_code(result: &SSRResult, context: &mut SSRContext) -> String {
     let mut code = String::new();
+    let call = result.to_ssr_call(context);
 
     // Generate helper imports
-    let helpers = context.helpers.borrow();
-    if !helpers.is_empty() {
-        let helper_list: Vec<&String> = helpers.iter().collect();
+    if !context.module.helpers.is_empty() {
+        let helper_list: Vec<&String> = context.module.helpers.iter().collect();
         code.push_str(&format!(
             "import {{ {} }} from \"solid-js/web\";\n\n",
             helper_list
@@ -23,8 +23,11 @@ pub fn generate_ssr_code(result: &SSRResult, context: &SSRContext) -> String {
         ));
     }
 
-    // Generate the ssr call
-    code.push_str(&result.to_ssr_call());
+    // Generate the template arrays and the ssr call
+    for (name, init) in &context.module.hoisted {
+        code.push_str(&format!("const {} = {};\n", name, init));
+    }
+    code.push_str(&call);
 
     code
 }
@@ -32,16 +35,16 @@ pub fn generate_ssr_code(result: &SSRResult, context: &SSRContext) -> String {
 /// Wrap a value in escape() call if needed
 pub fn escape_value(expr: &str, is_attr: bool) -> String {
     if is_attr {
-        format!("escape({}, true)", expr)
+        format!("_$escape({}, true)", expr)
     } else {
-        format!("escape({})", expr)
+        format!("_$escape({})", expr)
     }
 }
 
 /// Generate ssrAttribute call for dynamic boolean attributes
 pub fn ssr_attribute(name: &str, expr: &str, is_boolean: bool) -> String {
     format!(
-        "ssrAttribute(\"{}\", {}, {})",
+        "_$ssrAttribute(\"{}\", {}, {})",
         name,
         expr,
         if is_boolean { "true" } else { "false" }
@@ -50,15 +53,15 @@ pub fn ssr_attribute(name: &str, expr: &str, is_boolean: bool) -> String {
 
 /// Generate ssrStyle call
 pub fn ssr_style(expr: &str) -> String {
-    format!("ssrStyle({})", expr)
+    format!("_$ssrStyle({})", expr)
 }
 
 /// Generate ssrClassList call
 pub fn ssr_class_list(expr: &str) -> String {
-    format!("ssrClassList({})", expr)
+    format!("_$ssrClassList({})", expr)
 }
 
 /// Generate ssrHydrationKey call
 pub fn ssr_hydration_key() -> &'static str {
-    "ssrHydrationKey()"
+    "_$ssrHydrationKey()"
 }
@@ -4,26 +4,37 @@
 
 use oxc_allocator::{Allocator, CloneIn};
 use oxc_ast::ast::{
-    Expression, ImportDeclarationSpecifier, ImportOrExportKind, JSXChild, JSXElement,
-    JSXExpressionContainer, JSXFragment, JSXText, ModuleExportName, Program, Statement,
-    TemplateElementValue,
+    Declaration, Expression, IdentifierReference, JSXAttribute, JSXAttributeValue, JSXChild,
+    JSXElement, JSXExpressionContainer, JSXFragment, JSXText, Program, Statement,
 };
 use oxc_parser::Parser;
 use oxc_semantic::SemanticBuilder;
-use oxc_span::{SourceType, Span};
-use oxc_traverse::{traverse_mut, Traverse, TraverseCtx};
-
-use common::{get_tag_name, is_component, TransformOptions};
+use oxc_span::{GetSpan, SourceType, Span};
+use oxc_traverse::{traverse_mut, Ancestor, Traverse, TraverseCtx};
+
+use common::{
+    check_runtime_exports, decode_entities, expr_to_string, expression_child, fold_jsx_branches,
+    fold_show, get_tag_name, import_helpers, is_component, is_static_element, is_static_markup,
+    json_array, json_object, json_string, substitute_constants, wrap_element_value, Diagnostic,
+    IrDump, JsxIgnore, StaticMarks, TransformOptions,
+};
 
 use crate::component::transform_component;
-use crate::element::transform_element;
-use crate::ir::{SSRContext, SSRResult};
+use crate::element::{is_compiled_markup, transform_element};
+use crate::ir::{EscapeContext, SSRContext, SSRResult};
 
 /// The main SSR JSX transformer
 pub struct SSRTransform<'a> {
     allocator: &'a Allocator,
     options: &'a TransformOptions<'a>,
     context: SSRContext,
+    jsx_ignore: JsxIgnore,
+    /// The IR of each JSX root as JSON, when dumping it
+    ir_roots: Option<Vec<String>>,
+    /// The template of each JSX root, when dumping the IR
+    ir_templates: Vec<String>,
+    /// Roots compiled to plain HTML strings, which keep their source span
+    static_roots: Vec<Span>,
 }
 
 impl<'a> SSRTransform<'a> {
@@ -32,76 +43,109 @@ impl<'a> SSRTransform<'a> {
             allocator,
             options,
             context: SSRContext::new(options.hydratable),
+            jsx_ignore: JsxIgnore::default(),
+            ir_roots: None,
+            ir_templates: vec![],
+            static_roots: vec![],
         }
     }
 
-    /// Run the transform on a program
-    pub fn transform(mut self, program: &mut Program<'a>) {
-        // SAFETY: We convert the allocator reference to a raw pointer and back to a reference
-        // to satisfy oxc_traverse's API which requires `&Allocator` while we hold `&mut self`.
-        // This is safe because:
-        // 1. The allocator lives for 'a which outlives this entire transform operation
-        // 2. oxc_traverse only uses the allocator for read-only arena access
-        // 3. We don't mutate the allocator through any path during traversal
-        // 4. The pointer is never escaped or stored beyond this call
-        let allocator = self.allocator as *const Allocator;
-        traverse_mut(
-            &mut self,
-            unsafe { &*allocator },
-            program,
-            SemanticBuilder::new()
-                .build(program)
-                .semantic
-                .into_scoping(),
-            (),
-        );
+    /// Run the transform on a program, returning any diagnostics it reported
+    pub fn transform(mut self, program: &mut Program<'a>) -> Vec<Diagnostic> {
+        self.run(program);
+        self.context.module.diagnostics
+    }
+
+    /// Run the transform and also dump the IR of every JSX root
+    pub fn transform_with_ir(mut self, program: &mut Program<'a>) -> (Vec<Diagnostic>, IrDump) {
+        self.ir_roots = Some(vec![]);
+        self.run(program);
+        let roots = self.ir_roots.take().unwrap_or_default();
+        let ir = IrDump {
+            json: json_object([
+                ("backend", json_string("ssr")),
+                ("roots", json_array(roots)),
+                ("file", self.context.to_json()),
+            ]),
+            templates: std::mem::take(&mut self.ir_templates),
+        };
+        (self.context.module.diagnostics, ir)
+    }
+
+    fn run(&mut self, program: &mut Program<'a>) {
+        self.jsx_ignore = JsxIgnore::from_program(program);
+        self.context.module.static_marks =
+            StaticMarks::from_program(program, self.options.static_marker);
+        // Copy the allocator reference out so it isn't tied to the `&mut self` borrow
+        let allocator = self.allocator;
+        let scoping = SemanticBuilder::new()
+            .build(program)
+            .semantic
+            .into_scoping();
+        traverse_mut(self, allocator, program, scoping, ());
+    }
+
+    /// Keep the IR of a JSX root when dumping it
+    fn record_ir(&mut self, result: &SSRResult) {
+        if let Some(roots) = &mut self.ir_roots {
+            roots.push(result.to_json());
+            self.ir_templates.push(result.template_parts.join("${}"));
+        }
     }
 
     /// Transform a JSX node and return the SSR result
-    fn transform_node(&self, node: &JSXChild<'a>) -> Option<SSRResult> {
+    fn transform_node(&self, node: &JSXChild<'a>, context: &mut SSRContext) -> Option<SSRResult> {
         match node {
-            JSXChild::Element(element) => Some(self.transform_jsx_element(element)),
-            JSXChild::Fragment(fragment) => Some(self.transform_fragment(fragment)),
+            JSXChild::Element(element) => Some(self.transform_jsx_element(element, context)),
+            JSXChild::Fragment(fragment) => Some(self.transform_fragment(fragment, context)),
             JSXChild::Text(text) => self.transform_text(text),
             JSXChild::ExpressionContainer(container) => {
                 self.transform_expression_container(container)
             }
-            JSXChild::Spread(_) => {
-                // Spread children - treat as dynamic
+            JSXChild::Spread(spread) => {
                 let mut result = SSRResult::new();
-                self.context.register_helper("escape");
-                result.push_dynamic("/* spread */".to_string(), false, false);
+                result.push_dynamic(expr_to_string(&spread.expression), EscapeContext::Text);
                 Some(result)
             }
         }
     }
 
     /// Transform a JSX element
-    fn transform_jsx_element(&self, element: &JSXElement<'a>) -> SSRResult {
+    fn transform_jsx_element(
+        &self,
+        element: &JSXElement<'a>,
+        context: &mut SSRContext,
+    ) -> SSRResult {
         let tag_name = get_tag_name(element);
 
         if is_component(&tag_name) {
             // Create child transformer closure that can recursively transform children
             let child_transformer =
-                |child: &JSXChild<'a>| -> Option<SSRResult> { self.transform_node(child) };
+                |child: &JSXChild<'a>, context: &mut SSRContext| -> Option<SSRResult> {
+                    self.transform_node(child, context)
+                };
             transform_component(
                 element,
                 &tag_name,
-                &self.context,
+                context,
                 self.options,
                 &child_transformer,
             )
         } else {
-            transform_element(element, &tag_name, &self.context, self.options)
+            transform_element(element, &tag_name, context, self.options)
         }
     }
 
     /// Transform a JSX fragment
-    fn transform_fragment(&self, fragment: &JSXFragment<'a>) -> SSRResult {
+    fn transform_fragment(
+        &self,
+        fragment: &JSXFragment<'a>,
+        context: &mut SSRContext,
+    ) -> SSRResult {
         let mut result = SSRResult::new();
 
         for child in &fragment.children {
-            if let Some(child_result) = self.transform_node(child) {
+            if let Some(child_result) = self.transform_node(child, context) {
                 result.merge(child_result);
             }
         }
@@ -126,91 +170,210 @@ impl<'a> SSRTransform<'a> {
         &self,
         container: &JSXExpressionContainer<'a>,
     ) -> Option<SSRResult> {
-        if let Some(_expr) = container.expression.as_expression() {
-            self.context.register_helper("escape");
-            let mut result = SSRResult::new();
-            result.push_dynamic("/* expr */".to_string(), false, false);
-            Some(result)
+        let expr = container.expression.as_expression()?;
+        // Nested JSX is markup already compiled by the `ssr` tag
+        let escape = if is_compiled_markup(expr) {
+            EscapeContext::Raw
         } else {
-            None
-        }
+            EscapeContext::Text
+        };
+        let mut result = SSRResult::new();
+        result.push_dynamic(expr_to_string(expr), escape);
+        Some(result)
     }
 }
 
 impl<'a> Traverse<'a, ()> for SSRTransform<'a> {
+    fn enter_expression(&mut self, node: &mut Expression<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        let scoping = ctx.scoping();
+        let is_global = |ident: &IdentifierReference| match ident.reference_id.get() {
+            Some(id) => scoping.get_reference(id).symbol_id().is_none(),
+            None => true,
+        };
+        substitute_constants(node, self.options, ctx.ast, &is_global);
+
+        // Only the branch a literal condition takes is compiled
+        if !self.jsx_ignore.contains(node.span()) {
+            fold_jsx_branches(node, ctx.ast);
+            if let Expression::JSXElement(element) = node {
+                if let Some(folded) = fold_show(element, ctx.ast) {
+                    *node = folded;
+                    fold_jsx_branches(node, ctx.ast);
+                }
+            }
+        }
+
+        if matches!(node, Expression::JSXElement(_) | Expression::JSXFragment(_)) {
+            self.jsx_ignore.enter(node.span());
+        }
+    }
+
+    fn enter_jsx_child(&mut self, node: &mut JSXChild<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if let JSXChild::Element(element) = node {
+            if self.jsx_ignore.contains(element.span) {
+                return;
+            }
+            if let Some(folded) = fold_show(element, ctx.ast) {
+                *node = expression_child(folded, ctx.ast);
+            }
+        }
+    }
+
+    // Character references are decoded before any text or string attribute is
+    // read, then escaped again where they end up in markup
+    fn enter_jsx_text(&mut self, node: &mut JSXText<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if node.value.contains('&') && !self.jsx_ignore.contains(node.span) {
+            node.value = ctx.ast.atom(&decode_entities(&node.value));
+        }
+    }
+
+    fn enter_jsx_attribute(&mut self, node: &mut JSXAttribute<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if !self.jsx_ignore.contains(node.span) {
+            wrap_element_value(&mut node.value, ctx.ast);
+        }
+        if let Some(JSXAttributeValue::StringLiteral(lit)) = &mut node.value {
+            if lit.value.contains('&') && !self.jsx_ignore.contains(lit.span) {
+                lit.value = ctx.ast.atom(&decode_entities(&lit.value));
+            }
+        }
+    }
+
     // Use exit_expression instead of enter_expression to avoid
     // oxc_traverse walking into our newly created nodes (which lack scope info)
     fn exit_expression(&mut self, node: &mut Expression<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        // `@jsx-ignore` subtrees are left as written
+        if self.jsx_ignore.contains(node.span()) {
+            return;
+        }
+
+        if self.options.static_html {
+            let is_static = match node {
+                Expression::JSXElement(element) => is_static_element(element),
+                Expression::JSXFragment(fragment) => is_static_markup(&fragment.children),
+                _ => false,
+            };
+            if is_static {
+                self.transform_static_root(node, ctx);
+                return;
+            }
+        }
+
+        // Take the context out so it can be passed down mutably alongside `&self`
+        let mut context = std::mem::take(&mut self.context);
         let new_expr = match node {
             Expression::JSXElement(element) => {
-                let result = self.transform_jsx_element(element);
-                Some(self.build_ssr_expression(&result, ctx))
+                let result = self.transform_jsx_element(element, &mut context);
+                self.record_ir(&result);
+                Some(self.build_ssr_expression(&result, &mut context, ctx))
             }
             Expression::JSXFragment(fragment) => {
-                let result = self.transform_fragment(fragment);
-                Some(self.build_ssr_expression(&result, ctx))
+                let result = self.transform_fragment(fragment, &mut context);
+                self.record_ir(&result);
+                Some(self.build_ssr_expression(&result, &mut context, ctx))
             }
             _ => None,
         };
+        self.context = context;
 
-        if let Some(expr) = new_expr {
+        if let Some(mut expr) = new_expr {
+            annotate_module_level_call(&mut expr, ctx);
             *node = expr;
         }
     }
 
     fn exit_program(&mut self, program: &mut Program<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        if !self.static_roots.is_empty() {
+            self.export_static_html(program, ctx);
+        }
+
+        // Insert hoisted constants
+        for (name, init) in self.context.module.hoisted.iter().rev() {
+            let decl_code = format!("const {} = {};", name, init);
+            if let Some(stmt) = self.parse_statement(&decl_code, ctx) {
+                program.body.insert(0, stmt);
+            }
+        }
+
         // Get the helpers that were used
-        let helpers = self.context.helpers.borrow();
+        let helpers = &self.context.module.helpers;
 
         if helpers.is_empty() {
             return;
         }
 
-        // Build import statement: import { ssr, escape, ... } from 'solid-js/web';
-        // NOTE: This import building logic is duplicated with DOM transform.
-        // Extraction is non-trivial due to OXC's lifetime requirements.
-        let ast = ctx.ast;
-        let span = Span::default();
-        let module_name = self.options.module_name;
-
-        // Build specifiers
-        let mut specifiers = ast.vec();
-        for helper in helpers.iter() {
-            let helper_str = ast.allocator.alloc_str(helper);
-            let imported = ModuleExportName::IdentifierName(ast.identifier_name(span, helper_str));
-            let local = ast.binding_identifier(span, helper_str);
-            let specifier = ast.import_specifier(span, imported, local, ImportOrExportKind::Value);
-            specifiers.push(ImportDeclarationSpecifier::ImportSpecifier(
-                ast.alloc(specifier),
-            ));
-        }
+        let missing = check_runtime_exports(self.options, helpers);
+        self.context.module.diagnostics.extend(missing);
 
-        // Build source string literal
-        let source = ast.string_literal(span, module_name, None);
-
-        // Build import declaration
-        let import_decl = ast.import_declaration(
-            span,
-            Some(specifiers),
-            source,
-            None,                                 // phase
-            None::<oxc_ast::ast::WithClause<'a>>, // with_clause
-            ImportOrExportKind::Value,
+        // import { ssr as _$ssr, escape as _$escape, ... } from 'solid-js/web';
+        // Helpers are aliased so user bindings with the same names can't clash.
+        import_helpers(
+            program,
+            helpers,
+            self.options.module_name,
+            ctx.scoping(),
+            ctx.ast,
         );
-
-        // Create the statement
-        let import_stmt = Statement::ImportDeclaration(ast.alloc(import_decl));
-
-        // Insert at the beginning of the program
-        program.body.insert(0, import_stmt);
     }
 }
 
 impl<'a> SSRTransform<'a> {
+    /// Replace JSX that renders the same markup every time with its HTML as a
+    /// plain string. Static sites inline it, so it takes no hydration keys.
+    fn transform_static_root(&mut self, node: &mut Expression<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        let mut context = std::mem::take(&mut self.context);
+        let hydratable = context.hydratable;
+        context.hydratable = false;
+        let result = match node {
+            Expression::JSXElement(element) => {
+                Some(self.transform_jsx_element(element, &mut context))
+            }
+            Expression::JSXFragment(fragment) => {
+                Some(self.transform_fragment(fragment, &mut context))
+            }
+            _ => None,
+        };
+        context.hydratable = hydratable;
+        self.context = context;
+        let Some(result) = result else {
+            return;
+        };
+        self.record_ir(&result);
+
+        let span = node.span();
+        let html = ctx.ast.allocator.alloc_str(&result.template_parts.concat());
+        self.static_roots.push(span);
+        *node = ctx.ast.expression_string_literal(span, html, None);
+    }
+
+    /// Export `<Name>Html` next to each exported component that only returns
+    /// static markup, so static site generators can import the string itself
+    fn export_static_html(&self, program: &mut Program<'a>, ctx: &mut TraverseCtx<'a, ()>) {
+        let mut index = 0;
+        while index < program.body.len() {
+            index += 1;
+            let Statement::ExportNamedDeclaration(export) = &program.body[index - 1] else {
+                continue;
+            };
+            let Some((name, html)) = export
+                .declaration
+                .as_ref()
+                .and_then(|decl| static_component(decl, &self.static_roots))
+            else {
+                continue;
+            };
+            let code = format!("export const {}Html = {};", name, json_string(&html));
+            if let Some(stmt) = self.parse_statement(&code, ctx) {
+                program.body.insert(index, stmt);
+                index += 1;
+            }
+        }
+    }
+
     /// Build the SSR expression from the transform result
     fn build_ssr_expression(
         &self,
         result: &SSRResult,
+        context: &mut SSRContext,
         ctx: &mut TraverseCtx<'a, ()>,
     ) -> Expression<'a> {
         let ast = ctx.ast;
@@ -223,69 +386,21 @@ impl<'a> SSRTransform<'a> {
             return ast.expression_string_literal(span, allocated_str, None);
         }
 
-        // Build a proper TaggedTemplateExpression: ssr`...${expr}...`
-        self.context.register_helper("ssr");
-
-        // Build quasis (static template parts)
-        let mut quasis = ast.vec();
-        for (i, part) in result.template_parts.iter().enumerate() {
-            let is_tail = i == result.template_parts.len() - 1;
-            let part_str = ast.allocator.alloc_str(part);
-            let value = TemplateElementValue {
-                raw: ast.atom(part_str),
-                cooked: Some(ast.atom(part_str)),
-            };
-            let element = ast.template_element(span, value, is_tail);
-            quasis.push(element);
-        }
-
-        // Build expressions (dynamic parts)
-        let mut expressions = ast.vec();
-        for val in &result.template_values {
-            let expr = self.parse_and_wrap_expression(&val.expr, val.is_attr, val.skip_escape, ctx);
-            expressions.push(expr);
-        }
-
-        // Build the template literal
-        let template = ast.template_literal(span, quasis, expressions);
-
-        // Build the tag (ssr identifier)
-        let tag = ast.expression_identifier(span, "ssr");
-
-        // Build the tagged template expression
-        // Args: span, tag, type_arguments, quasi (template)
-        ast.expression_tagged_template(
-            span,
-            tag,
-            None::<oxc_ast::ast::TSTypeParameterInstantiation<'a>>,
-            template,
-        )
+        // ssr(_tmpl$1, escape(a()), ...), with the static parts hoisted
+        let code = result.to_ssr_call(context);
+        self.parse_expression(&code, ctx)
     }
 
-    /// Parse an expression string and wrap it appropriately
-    fn parse_and_wrap_expression(
-        &self,
-        expr_str: &str,
-        is_attr: bool,
-        skip_escape: bool,
-        ctx: &mut TraverseCtx<'a, ()>,
-    ) -> Expression<'a> {
-        let _ast = ctx.ast;
-        let _span = Span::default();
-
-        // Try to parse the expression
-        let parsed_expr = self.parse_expression(expr_str, ctx);
-
-        if skip_escape {
-            // Don't wrap in escape()
-            parsed_expr
-        } else if is_attr {
-            // Wrap in escape(expr, true)
-            self.build_escape_call(parsed_expr, true, ctx)
-        } else {
-            // Wrap in escape(expr)
-            self.build_escape_call(parsed_expr, false, ctx)
-        }
+    /// Parse a statement string into a Statement
+    fn parse_statement(&self, code: &str, ctx: &mut TraverseCtx<'a, ()>) -> Option<Statement<'a>> {
+        let allocator = ctx.ast.allocator;
+        let parse_result = Parser::new(allocator, code, SourceType::tsx()).parse();
+
+        parse_result
+            .program
+            .body
+            .first()
+            .map(|stmt| stmt.clone_in(allocator))
     }
 
     /// Parse an expression string into an AST Expression
@@ -301,12 +416,10 @@ impl<'a> SSRTransform<'a> {
         let parse_result = Parser::new(allocator, expr_str, source_type).parse();
 
         // Try to extract the expression from the parsed program
-        if let Some(stmt) = parse_result.program.body.first() {
-            if let Statement::ExpressionStatement(expr_stmt) = stmt {
-                // Clone the expression into our allocator
-                // Note: This is a simplified approach - ideally we'd transfer ownership
-                return expr_stmt.expression.clone_in(allocator);
-            }
+        if let Some(Statement::ExpressionStatement(expr_stmt)) = parse_result.program.body.first() {
+            // Clone the expression into our allocator
+            // Note: This is a simplified approach - ideally we'd transfer ownership
+            return expr_stmt.expression.clone_in(allocator);
         }
 
         // Fallback: create an identifier from the expression string
@@ -314,37 +427,65 @@ impl<'a> SSRTransform<'a> {
         let expr_alloc = ast.allocator.alloc_str(expr_str);
         ast.expression_identifier(span, expr_alloc)
     }
+}
 
-    /// Build an escape() call expression
-    fn build_escape_call(
-        &self,
-        expr: Expression<'a>,
-        is_attr: bool,
-        ctx: &mut TraverseCtx<'a, ()>,
-    ) -> Expression<'a> {
-        let ast = ctx.ast;
-        let span = Span::default();
-
-        // Create: escape(expr) or escape(expr, true)
-        let callee = ast.expression_identifier(span, "escape");
-
-        let mut args = ast.vec();
-
-        // First argument: the expression
-        args.push(oxc_ast::ast::Argument::from(expr));
-
-        if is_attr {
-            // Second argument: true (for attribute escaping)
-            let true_lit = ast.expression_boolean_literal(span, true);
-            args.push(oxc_ast::ast::Argument::from(true_lit));
+/// The name and markup of a component declaration whose body only returns a
+/// root compiled to plain HTML
+fn static_component(decl: &Declaration, static_roots: &[Span]) -> Option<(String, String)> {
+    let (name, body) = match decl {
+        Declaration::FunctionDeclaration(func) => (func.id.as_ref()?.name, func.body.as_ref()?),
+        Declaration::VariableDeclaration(var) => {
+            let [declarator] = var.declarations.as_slice() else {
+                return None;
+            };
+            let name = declarator.id.get_binding_identifier()?.name;
+            match declarator.init.as_ref()? {
+                Expression::ArrowFunctionExpression(arrow) => (name, &arrow.body),
+                Expression::FunctionExpression(func) => (name, func.body.as_ref()?),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    if !is_component(&name) {
+        return None;
+    }
+    let returned = match body.statements.as_slice() {
+        [Statement::ReturnStatement(ret)] => ret.argument.as_ref()?,
+        [Statement::ExpressionStatement(stmt)] => &stmt.expression,
+        _ => return None,
+    };
+    match returned.without_parentheses() {
+        Expression::StringLiteral(lit) if static_roots.contains(&lit.span) => {
+            Some((name.to_string(), lit.value.to_string()))
         }
+        _ => None,
+    }
+}
 
-        ast.expression_call(
-            span,
-            callee,
-            None::<oxc_ast::ast::TSTypeParameterInstantiation<'a>>,
-            args,
-            false,
+/// Mark a JSX root's component call or IIFE `/* @__PURE__ */` when it initializes
+/// a module-level binding or default export, so bundlers can drop it with an
+/// unused export
+fn annotate_module_level_call(expr: &mut Expression, ctx: &TraverseCtx<'_, ()>) {
+    if ctx.current_scope_id() != ctx.scoping().root_scope_id()
+        || !matches!(
+            ctx.parent(),
+            Ancestor::VariableDeclaratorInit(_) | Ancestor::ExportDefaultDeclarationDeclaration(_)
         )
+    {
+        return;
+    }
+    if let Expression::CallExpression(call) = expr {
+        let is_component = matches!(
+            &call.callee,
+            Expression::Identifier(ident) if ident.name == "_$createComponent"
+        );
+        let is_iife = matches!(
+            call.callee.without_parentheses(),
+            Expression::ArrowFunctionExpression(_) | Expression::FunctionExpression(_)
+        );
+        if is_component || is_iife {
+            call.pure = true;
+        }
     }
 }
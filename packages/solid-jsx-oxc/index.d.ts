@@ -9,6 +9,12 @@ export interface TransformOptions {
    */
   moduleName?: string;
 
+  /**
+   * Exports available from `moduleName`. When set, a diagnostic is reported for
+   * every helper the output would import that isn't in this list.
+   */
+  runtimeExports?: string[];
+
   /**
    * Generate mode: "dom", "ssr", or "universal"
    * @default "dom"
@@ -27,12 +33,50 @@ export interface TransformOptions {
    */
   delegateEvents?: boolean;
 
+  /**
+   * Extra events to delegate
+   * @default []
+   */
+  delegatedEvents?: string[];
+
+  /**
+   * Whether `delegatedEvents` replaces the built-in table instead of extending it.
+   * Pass an empty `delegatedEvents` to turn delegation off for every event.
+   * @default false
+   */
+  replaceDelegatedEvents?: boolean;
+
   /**
    * Whether to wrap conditionals
    * @default true
    */
   wrapConditionals?: boolean;
 
+  /**
+   * Text of the leading block comment that marks an expression to be read
+   * once rather than tracked
+   * @default "@once"
+   */
+  staticMarker?: string;
+
+  /**
+   * Compile-time constants. Each key (an identifier or member chain such as
+   * `import.meta.env.SSR`) is replaced with its value's source before JSX is
+   * analyzed, and branches on the result are folded.
+   * @example { "__DEV__": "false", "import.meta.env.SSR": "true" }
+   * @default {}
+   */
+  define?: Record<string, string>;
+
+  /**
+   * Modules to import `use:` directives from. `use:tooltip` then gets
+   * `import { tooltip } from "my-directives"` unless the file declares `tooltip`
+   * at the top level.
+   * @example { "tooltip": "my-directives" }
+   * @default {}
+   */
+  directiveImports?: Record<string, string>;
+
   /**
    * Whether to pass context to custom elements
    * @default true
@@ -51,10 +95,155 @@ export interface TransformOptions {
    */
   sourceMap?: boolean;
 
+  /**
+   * Dump the intermediate representation of every JSX root (templates,
+   * declarations, dynamic bindings, helpers) as JSON in `ir`, for debugging
+   * why a JSX shape compiled the way it did.
+   * @default false
+   */
+  emitIr?: boolean;
+
+  /**
+   * Parse every file with JSX enabled, regardless of its extension.
+   * Useful for legacy codebases that keep JSX in `.js` or `.ts` files.
+   * @default false
+   */
+  jsx?: boolean;
+
+  /**
+   * Syntax level of the generated code. With "es5", arrow functions become
+   * function expressions, `const` becomes `var` and template literals become
+   * string concatenation.
+   * @default "es2015"
+   */
+  target?: 'es2015' | 'es5';
+
+  /**
+   * The Solid major version the output runs against. "solid-2" is an opt-in
+   * preview of the next runtime: effects get separate compute and apply
+   * functions, and `mergeProps` becomes `merge`.
+   * @default "solid-1"
+   */
+  runtime?: 'solid-1' | 'solid-2';
+
+  /**
+   * Whose output to match. "babel" emits babel-plugin-jsx-dom-expressions'
+   * naming (`_$template` imports, `_tmpl$`, `_el$`, `_el$2`), one import per
+   * helper and `/*#__PURE__*\/` templates, so output from the Babel plugin and
+   * this compiler can be diffed down to whitespace while migrating.
+   * @default "none"
+   */
+  compat?: 'none' | 'babel';
+
+  /**
+   * Quote strings and space anonymous functions the way Prettier does, so running
+   * a formatter over the output doesn't churn. Long lines are not re-wrapped.
+   * @default false
+   */
+  prettier?: boolean;
+
   /**
    * Built-in components that receive special handling
    */
   builtIns?: string[];
+
+  /**
+   * Whether SSR warns about event handlers that only attach after hydration
+   * @default false
+   */
+  ssrEventDiagnostics?: boolean;
+
+  /**
+   * Whether SSR wraps each component's output in `<!--c:Name:id-->` boundary comments
+   * @default false
+   */
+  componentBoundaries?: boolean;
+
+  /**
+   * Whether SSR compiles JSX that renders the same markup every time (no
+   * components, expressions or spreads) to a plain HTML string, with no `ssr`
+   * tag, escapes or hydration keys. Exported components returning only such
+   * JSX also get an exported `<Name>Html` string, for static site generators.
+   * @default false
+   */
+  staticHtml?: boolean;
+
+  /**
+   * Whether DOM and SSR templates drop whitespace next to block-level tags.
+   * Whitespace inside `pre`, `textarea`, and elements with `whitespace-*` classes is kept.
+   * Both outputs apply the same rules, so hydration still finds its text nodes.
+   * @default false
+   */
+  minifyWhitespace?: boolean;
+
+  /**
+   * Whether component props made only of literals are hoisted to module-level
+   * constants, shared by every render instead of allocated each time. Constant
+   * object and array props (`options={{ dense: true }}`) are hoisted too.
+   * Leave off if components mutate their props object or compare it by identity.
+   * @default false
+   */
+  hoistStaticProps?: boolean;
+
+  /**
+   * Whether templates also drop the closing tag of a last child inside an
+   * element that keeps its own, where the HTML parser closes it anyway.
+   * Only takes effect with `omitLastClosingTag`.
+   * @default false
+   */
+  omitNestedClosingTags?: boolean;
+
+  /**
+   * Whether templates drop the closing tags at their end
+   * (`<div><span>a` instead of `<div><span>a</span></div>`).
+   * @default false
+   */
+  omitLastClosingTag?: boolean;
+
+  /**
+   * Whether template attribute values go unquoted when HTML allows it
+   * (`class=btn` instead of `class="btn"`).
+   * @default false
+   */
+  omitQuotes?: boolean;
+
+  /**
+   * Warn about HTML nesting the browser's parser would rearrange, such as
+   * `<div>` inside `<p>` or `<td>` outside `<tr>`.
+   * @default false
+   */
+  validate?: boolean;
+
+  /**
+   * Extra void elements (rendered without children or a closing tag).
+   * Prefix an entry with `!` to remove a built-in one.
+   * @default []
+   */
+  voidElements?: string[];
+
+  /**
+   * Extra SVG elements (created in the SVG namespace).
+   * Prefix an entry with `!` to remove a built-in one.
+   * @default []
+   */
+  svgElements?: string[];
+
+  /**
+   * Extra boolean attributes (present when truthy, omitted otherwise).
+   * Prefix an entry with `!` to remove a built-in one.
+   * @default []
+   */
+  booleanAttributes?: string[];
+}
+
+export interface Diagnostic {
+  /** "warning" or "error" */
+  severity: string;
+  message: string;
+  /** Byte offset where the offending source starts */
+  start: number;
+  /** Byte offset where the offending source ends */
+  end: number;
 }
 
 export interface TransformResult {
@@ -62,6 +251,10 @@ export interface TransformResult {
   code: string;
   /** Source map (if enabled) */
   map?: string;
+  /** The intermediate representation as JSON (if `emitIr` is set) */
+  ir?: string;
+  /** Diagnostics reported during the transform */
+  diagnostics: Diagnostic[];
 }
 
 /**
@@ -72,21 +265,126 @@ export interface TransformResult {
  */
 export function transform(source: string, options?: TransformOptions): TransformResult;
 
+export interface ExploreResult {
+  /**
+   * Outline of the source JSX as JSON: each node's `kind` ("element",
+   * "component", "fragment", "text", "expression" or "spread"), tag,
+   * attributes, children and `start`/`end` offsets
+   */
+  jsx: string;
+  /** The intermediate representation as JSON, as with `emitIr` */
+  ir: string;
+  /** The templates the output creates, in order */
+  templates: string[];
+  /** The transformed code */
+  code: string;
+  /** Diagnostics reported during the transform */
+  diagnostics: Diagnostic[];
+}
+
+/**
+ * Compile JSX source code and return every stage along the way (the source JSX,
+ * the IR, the templates and the final code), for explorer UIs
+ * @param source - The source code to compile
+ * @param options - Transform options
+ */
+export function explore(source: string, options?: TransformOptions): ExploreResult;
+
+export interface FileStats {
+  /** Component elements, built-ins included */
+  components: number;
+  /** Native (and custom) elements */
+  elements: number;
+  /**
+   * Reactive attributes, spreads and children of native elements, each one an
+   * effect or insert at runtime
+   */
+  dynamicBindings: number;
+  /** Events handled through delegation, in order of first use */
+  delegatedEvents: string[];
+  /** Total length of the templates the output creates */
+  templateBytes: number;
+}
+
+/**
+ * Count what a file's JSX costs at runtime, for auditing SSR-readiness and
+ * bundle impact across a codebase. The template sizes come from running the
+ * full transform, so an analysis costs about as much as `transform`
+ * @param source - The source code to analyze
+ * @param options - Transform options
+ */
+export function analyze(source: string, options?: TransformOptions): FileStats;
+
+/**
+ * Report problems in JSX source code: parse errors, then everything the
+ * transform would report, with HTML nesting validation always on. The transform
+ * runs in full and only printing the output is skipped, so a check costs about
+ * as much as `transform`.
+ * @param source - The source code to check
+ * @param options - Transform options
+ */
+export function check(source: string, options?: TransformOptions): Diagnostic[];
+
 /**
  * Low-level transform function from the native binding.
  * Prefers snake_case option names.
  */
 export function transformJsx(source: string, options?: {
   module_name?: string;
+  runtime_exports?: string[];
   generate?: string;
   hydratable?: boolean;
   delegate_events?: boolean;
+  delegated_events?: string[];
+  replace_delegated_events?: boolean;
   wrap_conditionals?: boolean;
+  static_marker?: string;
+  define?: Record<string, string>;
+  directive_imports?: Record<string, string>;
   context_to_custom_elements?: boolean;
   filename?: string;
   source_map?: boolean;
+  emit_ir?: boolean;
+  jsx?: boolean;
+  target?: string;
+  runtime?: string;
+  compat?: string;
+  prettier?: boolean;
+  ssr_event_diagnostics?: boolean;
+  component_boundaries?: boolean;
+  static_html?: boolean;
+  minify_whitespace?: boolean;
+  hoist_static_props?: boolean;
+  omit_nested_closing_tags?: boolean;
+  omit_last_closing_tag?: boolean;
+  omit_quotes?: boolean;
+  validate?: boolean;
+  void_elements?: string[];
+  svg_elements?: string[];
+  boolean_attributes?: string[];
 } | null): TransformResult;
 
+export interface RuntimeHelper {
+  /** The export name (e.g. "insert") */
+  name: string;
+  /** The first solid-js version providing it */
+  since: string;
+}
+
+export interface RuntimeReport {
+  /** Helpers the compiled output may import from `moduleName` */
+  helpers: RuntimeHelper[];
+  /** The lowest solid-js version providing every helper */
+  minimumVersion: string;
+}
+
+/**
+ * List the runtime helpers output compiled with these options may import,
+ * so a custom runtime build can be checked for coverage
+ * @param options - Transform options
+ */
+export function runtimeHelpers(options?: TransformOptions): RuntimeReport;
+
 export interface PresetResult {
   options: TransformOptions;
   transform: (source: string) => TransformResult;
@@ -103,10 +401,14 @@ export function preset(context: unknown, options?: TransformOptions): PresetResu
 /**
  * Default options matching babel-preset-solid
  */
-export const defaultOptions: Required<Omit<TransformOptions, 'filename'>>;
+export const defaultOptions: Required<Omit<TransformOptions, 'filename' | 'runtimeExports'>>;
 
 declare const _default: {
   transform: typeof transform;
+  explore: typeof explore;
+  analyze: typeof analyze;
+  check: typeof check;
+  runtimeHelpers: typeof runtimeHelpers;
   preset: typeof preset;
   defaultOptions: typeof defaultOptions;
   transformJsx: typeof transformJsx;
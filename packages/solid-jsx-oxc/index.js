@@ -58,10 +58,33 @@ const defaultOptions = {
   ],
   contextToCustomElements: true,
   wrapConditionals: true,
+  staticMarker: '@once',
+  define: {},
+  directiveImports: {},
   generate: 'dom', // 'dom' | 'ssr' | 'universal'
   hydratable: false,
   delegateEvents: true,
+  delegatedEvents: [],
+  replaceDelegatedEvents: false,
   sourceMap: false,
+  emitIr: false,
+  jsx: false,
+  target: 'es2015', // 'es2015' | 'es5'
+  runtime: 'solid-1', // 'solid-1' | 'solid-2'
+  compat: 'none', // 'none' | 'babel'
+  prettier: false,
+  ssrEventDiagnostics: false,
+  componentBoundaries: false,
+  staticHtml: false,
+  minifyWhitespace: false,
+  hoistStaticProps: false,
+  omitNestedClosingTags: false,
+  omitLastClosingTag: false,
+  omitQuotes: false,
+  validate: false,
+  voidElements: [],
+  svgElements: [],
+  booleanAttributes: [],
 };
 
 /**
@@ -82,6 +105,63 @@ function transform(source, options = {}) {
   return nativeBinding.transformJsx(source, mergedOptions);
 }
 
+/**
+ * Compile JSX source code and return every stage along the way, for explorer UIs
+ * @param {string} source - The source code to compile
+ * @param {object} options - Transform options
+ * @returns {{ jsx: string, ir: string, templates: string[], code: string, diagnostics: object[] }}
+ */
+function explore(source, options = {}) {
+  if (!nativeBinding) {
+    throw new Error('solid-jsx-oxc: Native module not loaded. Ensure it is built for your platform.');
+  }
+
+  return nativeBinding.exploreJsx(source, { ...defaultOptions, ...options });
+}
+
+/**
+ * Count what a file's JSX costs at runtime. Runs the full transform to size the templates,
+ * so it costs about as much as `transform`
+ * @param {string} source - The source code to analyze
+ * @param {object} options - Transform options
+ * @returns {{ components: number, elements: number, dynamicBindings: number, delegatedEvents: string[], templateBytes: number }}
+ */
+function analyze(source, options = {}) {
+  if (!nativeBinding) {
+    throw new Error('solid-jsx-oxc: Native module not loaded. Ensure it is built for your platform.');
+  }
+
+  return nativeBinding.analyzeJsx(source, { ...defaultOptions, ...options });
+}
+
+/**
+ * Report problems in JSX source code, nesting validation included, for editor and CI lint passes.
+ * Runs the full transform, only the output isn't printed
+ * @param {string} source - The source code to check
+ * @param {object} options - Transform options
+ * @returns {{ severity: string, message: string, start: number, end: number }[]}
+ */
+function check(source, options = {}) {
+  if (!nativeBinding) {
+    throw new Error('solid-jsx-oxc: Native module not loaded. Ensure it is built for your platform.');
+  }
+
+  return nativeBinding.checkJsx(source, { ...defaultOptions, ...options });
+}
+
+/**
+ * List the runtime helpers output compiled with these options may import
+ * @param {object} options - Transform options
+ * @returns {{ helpers: { name: string, since: string }[], minimumVersion: string }}
+ */
+function runtimeHelpers(options = {}) {
+  if (!nativeBinding) {
+    throw new Error('solid-jsx-oxc: Native module not loaded. Ensure it is built for your platform.');
+  }
+
+  return nativeBinding.runtimeHelpers({ ...defaultOptions, ...options });
+}
+
 /**
  * Create a preset configuration (for compatibility with babel-preset-solid interface)
  * @param {object} context - Babel context (ignored, for compatibility)
@@ -102,6 +182,10 @@ function preset(context, options = {}) {
 
 module.exports = {
   transform,
+  explore,
+  analyze,
+  check,
+  runtimeHelpers,
   preset,
   defaultOptions,
   // Also export the raw binding for advanced usage
@@ -1,4 +1,4 @@
 // ESM wrapper for solid-jsx-oxc
 import mod from './index.js';
-export const { transform, preset, defaultOptions, transformJsx } = mod;
+export const { transform, explore, analyze, check, runtimeHelpers, preset, defaultOptions, transformJsx } = mod;
 export default mod;
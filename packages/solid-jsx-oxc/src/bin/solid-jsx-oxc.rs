@@ -0,0 +1,96 @@
+//! Command-line entry point, mostly for debugging the transform
+//!
+//! ```sh
+//! solid-jsx-oxc [--generate=dom|ssr|universal] [--hydratable] [--emit=js|ir] [--check] [FILE]
+//! ```
+//!
+//! Reads FILE (or stdin) and prints the compiled code, or with `--emit=ir` the
+//! intermediate representation as JSON. Diagnostics go to stderr. With `--check`
+//! nothing is printed but the diagnostics, HTML nesting checks included.
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use solid_jsx_oxc::{check, transform, Diagnostic, Severity, TransformOptions};
+
+const USAGE: &str = "usage: solid-jsx-oxc [--generate=dom|ssr|universal] [--hydratable] \
+                     [--emit=js|ir] [--check] [FILE]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut options = TransformOptions::solid_defaults();
+    let mut file = None;
+    let mut check_only = false;
+
+    for arg in &args {
+        if let Some(mode) = arg.strip_prefix("--generate=") {
+            let Some(mode) = common::GenerateMode::parse(mode) else {
+                eprintln!("unknown generate mode `{}`\n{}", mode, USAGE);
+                return ExitCode::FAILURE;
+            };
+            options.generate = mode;
+        } else if arg == "--hydratable" {
+            options.hydratable = true;
+        } else if let Some(emit) = arg.strip_prefix("--emit=") {
+            match emit {
+                "js" => options.emit_ir = false,
+                "ir" => options.emit_ir = true,
+                _ => {
+                    eprintln!("unknown output `{}`\n{}", emit, USAGE);
+                    return ExitCode::FAILURE;
+                }
+            }
+        } else if arg == "--check" {
+            check_only = true;
+        } else if arg.starts_with('-') {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        } else {
+            file = Some(arg.as_str());
+        }
+    }
+
+    let source = match file {
+        Some(path) => std::fs::read_to_string(path),
+        None => {
+            let mut source = String::new();
+            std::io::stdin().read_to_string(&mut source).map(|_| source)
+        }
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(path) = file {
+        options.filename = path;
+    }
+
+    if check_only {
+        return report(&check(&source, Some(options)));
+    }
+
+    let result = transform(&source, Some(options));
+    match result.ir {
+        Some(ir) => println!("{}", ir.json),
+        None => println!("{}", result.code),
+    }
+    report(&result.diagnostics)
+}
+
+/// Print diagnostics to stderr, failing if any is an error
+fn report(diagnostics: &[Diagnostic]) -> ExitCode {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,33 @@
+//! Intermediate representation produced by the transforms
+//!
+//! This is the supported way for external tools (linters, visualizers, codemods)
+//! to consume the IR; depend on these re-exports rather than on the `dom`/`ssr`
+//! crates directly.
+//!
+//! Each JSX root is lowered to one result before code is generated:
+//!
+//! - [`dom::TransformResult`] holds the template HTML, element walk
+//!   [`dom::Declaration`]s, [`dom::Expr`]s to run, and [`dom::DynamicBinding`]s
+//!   that get wrapped in effects. Per-file state (templates, delegated events)
+//!   lives in [`dom::BlockContext`].
+//! - [`ssr::SSRResult`] holds the static template parts and the
+//!   [`ssr::TemplateValue`]s interpolated between them, each escaped for its
+//!   [`ssr::EscapeContext`]. Per-file state lives in [`ssr::SSRContext`].
+//!
+//! Both contexts carry a [`ModuleState`] (helpers, generated names, hoisted
+//! constants, diagnostics), reached through the [`TransformContext`] trait.
+
+pub use common::{ModuleState, TransformContext};
+
+/// IR for the DOM backend (`generate: "dom"`)
+pub mod dom {
+    pub use ::dom::ir::{
+        BlockContext, ChildTransformer, Declaration, DynamicBinding, Expr, TemplateInfo,
+        TransformResult,
+    };
+}
+
+/// IR for the SSR backend (`generate: "ssr"`)
+pub mod ssr {
+    pub use ::ssr::ir::{EscapeContext, SSRChildTransformer, SSRContext, SSRResult, TemplateValue};
+}
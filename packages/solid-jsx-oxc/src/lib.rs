@@ -12,20 +12,40 @@
 //! let result = transform(source, None);
 //! println!("{}", result.code);
 //! ```
+//!
+//! ## Features
+//!
+//! Each `generate` mode has a cargo feature (`dom`, `ssr`, `universal`), all on by
+//! default. Tools that only need one backend can turn off the default features and
+//! leave the others out of the build; transforming for a missing backend returns
+//! the source unchanged with an error diagnostic.
+
+pub mod ir;
 
-pub use common::TransformOptions;
+pub use common::{
+    minimum_version, required_helpers, Diagnostic, FileStats, IrDump, RuntimeHelper, Severity,
+    TransformOptions,
+};
+
+use common::Pragmas;
 
 #[cfg(feature = "napi")]
 use napi_derive::napi;
 
 use oxc_allocator::Allocator;
-use oxc_codegen::{Codegen, CodegenOptions, CodegenReturn, IndentChar};
+use oxc_ast::ast::Program;
+use oxc_ast::AstBuilder;
+use oxc_codegen::{Codegen, CodegenOptions, IndentChar};
 use oxc_parser::Parser;
-use oxc_span::SourceType;
+use oxc_span::{GetSpan, GetSpanMut, SourceType, Span};
 
+#[cfg(feature = "napi")]
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+#[cfg(any(feature = "dom", feature = "universal"))]
 use dom::SolidTransform;
+#[cfg(feature = "ssr")]
 use ssr::SSRTransform;
 
 /// Result of a transform operation
@@ -36,6 +56,96 @@ pub struct TransformResult {
     pub code: String,
     /// Source map (if enabled)
     pub map: Option<String>,
+    /// The intermediate representation as JSON (if `emitIr` is set)
+    pub ir: Option<String>,
+    /// Diagnostics reported during the transform
+    pub diagnostics: Vec<JsDiagnostic>,
+}
+
+/// A diagnostic exposed to JavaScript
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct JsDiagnostic {
+    /// "warning" or "error"
+    pub severity: String,
+    pub message: String,
+    /// Byte offset where the offending source starts
+    pub start: u32,
+    /// Byte offset where the offending source ends
+    pub end: u32,
+}
+
+#[cfg(feature = "napi")]
+impl From<Diagnostic> for JsDiagnostic {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            severity: match diagnostic.severity {
+                Severity::Warning => "warning".to_string(),
+                Severity::Error => "error".to_string(),
+            },
+            message: diagnostic.message,
+            start: diagnostic.span.start,
+            end: diagnostic.span.end,
+        }
+    }
+}
+
+/// Output of [`transform`]
+pub struct TransformOutput {
+    /// The transformed code
+    pub code: String,
+    /// Source map as JSON (if enabled)
+    pub map: Option<String>,
+    /// The intermediate representation (if `emit_ir` is set)
+    pub ir: Option<IrDump>,
+    /// Diagnostics reported during the transform
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Every stage of compiling a snippet, as returned by [`explore`]
+pub struct Exploration {
+    /// Outline of the source JSX as JSON
+    pub jsx: String,
+    /// The intermediate representation as JSON
+    pub ir: String,
+    /// The templates the output creates, in order
+    pub templates: Vec<String>,
+    /// The transformed code
+    pub code: String,
+    /// Diagnostics reported during the transform
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Every compile stage of a snippet, exposed to JavaScript
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct ExploreResult {
+    /// Outline of the source JSX as JSON
+    pub jsx: String,
+    /// The intermediate representation as JSON
+    pub ir: String,
+    /// The templates the output creates, in order
+    pub templates: Vec<String>,
+    /// The transformed code
+    pub code: String,
+    /// Diagnostics reported during the transform
+    pub diagnostics: Vec<JsDiagnostic>,
+}
+
+/// Per-file statistics exposed to JavaScript
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct JsFileStats {
+    /// Component elements, built-ins included
+    pub components: u32,
+    /// Native (and custom) elements
+    pub elements: u32,
+    /// Reactive attributes, spreads and children of native elements
+    pub dynamic_bindings: u32,
+    /// Events handled through delegation, in order of first use
+    pub delegated_events: Vec<String>,
+    /// Total length of the templates the output creates
+    pub template_bytes: u32,
 }
 
 /// Transform options exposed to JavaScript
@@ -47,6 +157,9 @@ pub struct JsTransformOptions {
     /// @default "solid-js/web"
     pub module_name: Option<String>,
 
+    /// Exports available from `module_name`; importing anything else is reported
+    pub runtime_exports: Option<Vec<String>>,
+
     /// Generate mode: "dom", "ssr", or "universal"
     /// @default "dom"
     pub generate: Option<String>,
@@ -59,10 +172,30 @@ pub struct JsTransformOptions {
     /// @default true
     pub delegate_events: Option<bool>,
 
+    /// Extra events to delegate
+    /// @default []
+    pub delegated_events: Option<Vec<String>>,
+
+    /// Whether `delegated_events` replaces the built-in table instead of extending it
+    /// @default false
+    pub replace_delegated_events: Option<bool>,
+
     /// Whether to wrap conditionals
     /// @default true
     pub wrap_conditionals: Option<bool>,
 
+    /// Comment marking an expression to be read once rather than tracked
+    /// @default "@once"
+    pub static_marker: Option<String>,
+
+    /// Compile-time constants, e.g. `{ "__DEV__": "false", "import.meta.env.SSR": "true" }`
+    /// @default {}
+    pub define: Option<HashMap<String, String>>,
+
+    /// Modules to import `use:` directives from, e.g. `{ "tooltip": "my-directives" }`
+    /// @default {}
+    pub directive_imports: Option<HashMap<String, String>>,
+
     /// Whether to pass context to custom elements
     /// @default true
     pub context_to_custom_elements: Option<bool>,
@@ -74,6 +207,78 @@ pub struct JsTransformOptions {
     /// Whether to generate source maps
     /// @default false
     pub source_map: Option<bool>,
+
+    /// Whether to dump the intermediate representation of every JSX root as JSON
+    /// @default false
+    pub emit_ir: Option<bool>,
+
+    /// Parse every file with JSX enabled, regardless of its extension
+    /// @default false
+    pub jsx: Option<bool>,
+
+    /// Syntax level of the generated code: "es2015" or "es5"
+    /// @default "es2015"
+    pub target: Option<String>,
+
+    /// The Solid major version the output runs against: "solid-1" or "solid-2"
+    /// @default "solid-1"
+    pub runtime: Option<String>,
+
+    /// Whose output to match: "none" or "babel"
+    /// @default "none"
+    pub compat: Option<String>,
+
+    /// Quote strings and space functions the way Prettier does
+    /// @default false
+    pub prettier: Option<bool>,
+
+    /// Whether SSR warns about event handlers that only attach after hydration
+    /// @default false
+    pub ssr_event_diagnostics: Option<bool>,
+
+    /// Whether SSR wraps each component's output in boundary comments
+    /// @default false
+    pub component_boundaries: Option<bool>,
+
+    /// Whether SSR compiles static JSX to plain HTML strings and exports them
+    /// @default false
+    pub static_html: Option<bool>,
+
+    /// Whether DOM and SSR templates drop whitespace next to block-level tags
+    /// @default false
+    pub minify_whitespace: Option<bool>,
+
+    /// Whether component props made only of literals are hoisted to module-level constants
+    /// @default false
+    pub hoist_static_props: Option<bool>,
+
+    /// Whether templates also drop closing tags inside elements that keep their own
+    /// @default false
+    pub omit_nested_closing_tags: Option<bool>,
+
+    /// Whether templates drop the closing tags at their end
+    /// @default false
+    pub omit_last_closing_tag: Option<bool>,
+
+    /// Whether template attribute values go unquoted when HTML allows it
+    /// @default false
+    pub omit_quotes: Option<bool>,
+
+    /// Whether to warn about HTML nesting the browser's parser would rearrange
+    /// @default false
+    pub validate: Option<bool>,
+
+    /// Extra void elements; a `!`-prefixed entry removes a built-in one
+    /// @default []
+    pub void_elements: Option<Vec<String>>,
+
+    /// Extra SVG elements; a `!`-prefixed entry removes a built-in one
+    /// @default []
+    pub svg_elements: Option<Vec<String>>,
+
+    /// Extra boolean attributes; a `!`-prefixed entry removes a built-in one
+    /// @default []
+    pub boolean_attributes: Option<Vec<String>>,
 }
 
 /// Transform JSX source code
@@ -81,75 +286,433 @@ pub struct JsTransformOptions {
 #[napi]
 pub fn transform_jsx(source: String, options: Option<JsTransformOptions>) -> TransformResult {
     let js_options = options.unwrap_or_default();
+    let options = to_transform_options(&js_options);
 
-    // Convert JS options to internal options
-    let generate = match js_options.generate.as_deref() {
-        Some("ssr") => common::GenerateMode::Ssr,
-        Some("universal") => common::GenerateMode::Universal,
-        _ => common::GenerateMode::Dom,
-    };
+    let result = transform_internal(&source, &options);
 
-    let options = TransformOptions {
+    TransformResult {
+        code: result.code,
+        map: result.map,
+        ir: result.ir.map(|ir| ir.json),
+        diagnostics: result.diagnostics.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// Compile JSX source code and return every stage along the way
+#[cfg(feature = "napi")]
+#[napi]
+pub fn explore_jsx(source: String, options: Option<JsTransformOptions>) -> ExploreResult {
+    let js_options = options.unwrap_or_default();
+    let result = explore(&source, Some(to_transform_options(&js_options)));
+
+    ExploreResult {
+        jsx: result.jsx,
+        ir: result.ir,
+        templates: result.templates,
+        code: result.code,
+        diagnostics: result.diagnostics.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// Count what a file's JSX costs at runtime. Runs the full transform
+#[cfg(feature = "napi")]
+#[napi]
+pub fn analyze_jsx(source: String, options: Option<JsTransformOptions>) -> JsFileStats {
+    let js_options = options.unwrap_or_default();
+    let stats = analyze(&source, Some(to_transform_options(&js_options)));
+
+    JsFileStats {
+        components: stats.components as u32,
+        elements: stats.elements as u32,
+        dynamic_bindings: stats.dynamic_bindings as u32,
+        delegated_events: stats.delegated_events,
+        template_bytes: stats.template_bytes as u32,
+    }
+}
+
+/// Report problems in JSX source code, without printing the output
+#[cfg(feature = "napi")]
+#[napi]
+pub fn check_jsx(source: String, options: Option<JsTransformOptions>) -> Vec<JsDiagnostic> {
+    let js_options = options.unwrap_or_default();
+    check(&source, Some(to_transform_options(&js_options)))
+        .into_iter()
+        .map(Into::into)
+        .collect()
+}
+
+/// A runtime helper exposed to JavaScript
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct JsRuntimeHelper {
+    /// The export name
+    pub name: String,
+    /// The first solid-js version providing it
+    pub since: String,
+}
+
+/// Runtime helpers the compiled output may import
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct RuntimeReport {
+    pub helpers: Vec<JsRuntimeHelper>,
+    /// The lowest solid-js version providing every helper
+    pub minimum_version: String,
+}
+
+/// List the runtime helpers output compiled with these options may import
+#[cfg(feature = "napi")]
+#[napi]
+pub fn runtime_helpers(options: Option<JsTransformOptions>) -> RuntimeReport {
+    let js_options = options.unwrap_or_default();
+    let helpers = required_helpers(&to_transform_options(&js_options));
+
+    RuntimeReport {
+        minimum_version: minimum_version(&helpers).to_string(),
+        helpers: helpers
+            .into_iter()
+            .map(|helper| JsRuntimeHelper {
+                name: helper.name.to_string(),
+                since: helper.since.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Convert JS options to internal options
+#[cfg(feature = "napi")]
+fn to_transform_options(js_options: &JsTransformOptions) -> TransformOptions<'_> {
+    let generate = js_options
+        .generate
+        .as_deref()
+        .and_then(common::GenerateMode::parse)
+        .unwrap_or_default();
+
+    TransformOptions {
+        module_name: js_options.module_name.as_deref().unwrap_or("solid-js/web"),
+        runtime_exports: js_options
+            .runtime_exports
+            .as_ref()
+            .map(|exports| exports.iter().map(String::as_str).collect()),
         generate,
         hydratable: js_options.hydratable.unwrap_or(false),
         delegate_events: js_options.delegate_events.unwrap_or(true),
+        delegated_events: string_list(&js_options.delegated_events),
+        replace_delegated_events: js_options.replace_delegated_events.unwrap_or(false),
         wrap_conditionals: js_options.wrap_conditionals.unwrap_or(true),
+        static_marker: js_options.static_marker.as_deref().unwrap_or("@once"),
+        define: js_options
+            .define
+            .iter()
+            .flatten()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect(),
+        directive_imports: js_options
+            .directive_imports
+            .iter()
+            .flatten()
+            .map(|(name, module)| (name.as_str(), module.as_str()))
+            .collect(),
         context_to_custom_elements: js_options.context_to_custom_elements.unwrap_or(true),
         filename: js_options.filename.as_deref().unwrap_or("input.jsx"),
         source_map: js_options.source_map.unwrap_or(false),
+        emit_ir: js_options.emit_ir.unwrap_or(false),
+        jsx: js_options.jsx.unwrap_or(false),
+        target: js_options
+            .target
+            .as_deref()
+            .and_then(common::OutputTarget::parse)
+            .unwrap_or_default(),
+        runtime: js_options
+            .runtime
+            .as_deref()
+            .and_then(common::RuntimeVersion::parse)
+            .unwrap_or_default(),
+        compat: js_options
+            .compat
+            .as_deref()
+            .and_then(common::Compat::parse)
+            .unwrap_or_default(),
+        prettier: js_options.prettier.unwrap_or(false),
+        ssr_event_diagnostics: js_options.ssr_event_diagnostics.unwrap_or(false),
+        component_boundaries: js_options.component_boundaries.unwrap_or(false),
+        static_html: js_options.static_html.unwrap_or(false),
+        minify_whitespace: js_options.minify_whitespace.unwrap_or(false),
+        hoist_static_props: js_options.hoist_static_props.unwrap_or(false),
+        omit_nested_closing_tags: js_options.omit_nested_closing_tags.unwrap_or(false),
+        omit_last_closing_tag: js_options.omit_last_closing_tag.unwrap_or(false),
+        omit_quotes: js_options.omit_quotes.unwrap_or(false),
+        validate: js_options.validate.unwrap_or(false),
+        void_elements: string_list(&js_options.void_elements),
+        svg_elements: string_list(&js_options.svg_elements),
+        boolean_attributes: string_list(&js_options.boolean_attributes),
         ..TransformOptions::solid_defaults()
-    };
-
-    let result = transform_internal(&source, &options);
-
-    TransformResult {
-        code: result.code,
-        map: result.map.map(|m| m.to_json_string()),
     }
 }
 
+/// Borrow an optional list of JS strings as `&str`s
+#[cfg(feature = "napi")]
+fn string_list(list: &Option<Vec<String>>) -> Vec<&str> {
+    list.iter().flatten().map(String::as_str).collect()
+}
+
 /// Internal transform function
-pub fn transform(source: &str, options: Option<TransformOptions>) -> CodegenReturn {
+pub fn transform(source: &str, options: Option<TransformOptions>) -> TransformOutput {
     let options = options.unwrap_or_else(TransformOptions::solid_defaults);
     transform_internal(source, &options)
 }
 
-fn transform_internal(source: &str, options: &TransformOptions) -> CodegenReturn {
+/// Compile a snippet and return every stage along the way: the source JSX, the
+/// IR, the templates and the final code. Powers explorer UIs in one call.
+pub fn explore(source: &str, options: Option<TransformOptions>) -> Exploration {
+    let options = TransformOptions {
+        emit_ir: true,
+        ..options.unwrap_or_else(TransformOptions::solid_defaults)
+    };
+
     let allocator = Allocator::default();
+    let program = Parser::new(&allocator, source, parse_source_type(&options))
+        .parse()
+        .program;
+    let jsx = common::jsx_summary(&program);
+
+    let output = transform_internal(source, &options);
+    // Files the transform passes through have no IR
+    let ir = output.ir.unwrap_or(IrDump {
+        json: "null".to_string(),
+        templates: vec![],
+    });
+
+    Exploration {
+        jsx,
+        ir: ir.json,
+        templates: ir.templates,
+        code: output.code,
+        diagnostics: output.diagnostics,
+    }
+}
+
+/// Count the components, native elements, dynamic bindings, delegated events and
+/// template bytes of a file. The template sizes come from running the full
+/// transform, so an analysis costs about as much as [`transform`]; only
+/// printing the output is skipped.
+pub fn analyze(source: &str, options: Option<TransformOptions>) -> FileStats {
+    let options = options.unwrap_or_else(TransformOptions::solid_defaults);
+    let allocator = Allocator::default();
+    let mut program = Parser::new(&allocator, source, parse_source_type(&options))
+        .parse()
+        .program;
+
+    let mut stats = common::jsx_stats(&program, &options);
+    let templates = generate_mode(source, &program, &options)
+        .ok()
+        .and_then(|generate| run_backend(&allocator, &mut program, &options, generate, true).ok())
+        .and_then(|(_, ir)| ir)
+        .map(|ir| ir.templates)
+        .unwrap_or_default();
+    stats.template_bytes = templates.iter().map(String::len).sum();
+    stats
+}
+
+/// How to parse the source, from its filename and the `jsx` option
+fn parse_source_type(options: &TransformOptions) -> SourceType {
     let source_type = SourceType::from_path(options.filename).unwrap_or(SourceType::tsx());
+    if options.jsx {
+        source_type.with_jsx(true)
+    } else {
+        source_type
+    }
+}
 
-    // Parse the source
-    let mut program = Parser::new(&allocator, source, source_type).parse().program;
+/// Report problems in JSX source code: parse errors, then the diagnostics of
+/// the transform for `options.generate`, with nesting validation always on.
+/// The transform runs in full and only printing the output is skipped, so this
+/// costs about as much as [`transform`].
+pub fn check(source: &str, options: Option<TransformOptions>) -> Vec<Diagnostic> {
+    let options = TransformOptions {
+        validate: true,
+        ..options.unwrap_or_else(TransformOptions::solid_defaults)
+    };
+    let allocator = Allocator::default();
+    let parsed = Parser::new(&allocator, source, parse_source_type(&options)).parse();
+
+    // The transforms can't say much about a file that doesn't parse
+    if !parsed.errors.is_empty() {
+        return parsed
+            .errors
+            .iter()
+            .map(|error| {
+                let span = error
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.first())
+                    .map_or(Span::default(), |label| {
+                        Span::new(label.offset() as u32, (label.offset() + label.len()) as u32)
+                    });
+                Diagnostic::error(error.message.to_string(), span)
+            })
+            .collect();
+    }
+
+    let mut program = parsed.program;
+    let generate = match generate_mode(source, &program, &options) {
+        Ok(generate) => generate,
+        Err(output) => return output.diagnostics,
+    };
+    match run_backend(&allocator, &mut program, &options, generate, false) {
+        Ok((diagnostics, _)) => diagnostics,
+        Err(diagnostic) => vec![diagnostic],
+    }
+}
+
+/// The mode to compile a parsed file for. Files that aren't compiled at all
+/// get their output instead: the source, unchanged.
+fn generate_mode(
+    source: &str,
+    program: &Program,
+    options: &TransformOptions,
+) -> Result<common::GenerateMode, TransformOutput> {
+    // Files written for another JSX runtime pass through untouched
+    let pragmas = Pragmas::from_comments(
+        program
+            .comments
+            .iter()
+            .map(|comment| &source[comment.span.start as usize..comment.span.end as usize]),
+    );
+    if pragmas.skips_transform(options.module_name) {
+        return Err(TransformOutput {
+            code: source.to_string(),
+            map: None,
+            ir: None,
+            diagnostics: vec![],
+        });
+    }
 
-    // Run the appropriate transform based on generate mode
-    // SAFETY: We create a raw pointer to `options` and dereference it to get a reference
-    // with an independent lifetime. This is safe because:
-    // 1. `options` is borrowed for the entire duration of this function
-    // 2. The reference is only used within this function's scope
-    // 3. The transformers don't outlive this function
-    // This pattern is used to work around Rust's borrow checker limitations with
-    // multiple mutable borrows needed during AST traversal.
-    let options_ref = unsafe { &*(options as *const TransformOptions) };
-
-    match options.generate {
+    // Compiled output fed through the transform again (e.g. a plugin registered
+    // twice in a pipeline) is returned as is rather than compiled a second time
+    if let Some(span) = common::find_compiled_marker(program, options.module_name) {
+        return Err(TransformOutput {
+            code: source.to_string(),
+            map: None,
+            ir: None,
+            diagnostics: vec![Diagnostic::warning(
+                "module is already transformed; is the JSX plugin configured twice?",
+                span,
+            )],
+        });
+    }
+
+    // A `@solid-generate` pragma overrides the configured mode for this file
+    Ok(pragmas.generate.unwrap_or(options.generate))
+}
+
+/// Run the transform for `generate` over a program, dumping the IR if asked.
+/// Fails when the backend for the mode isn't part of the build.
+fn run_backend<'a>(
+    allocator: &'a Allocator,
+    program: &mut Program<'a>,
+    options: &'a TransformOptions<'a>,
+    generate: common::GenerateMode,
+    emit_ir: bool,
+) -> Result<(Vec<Diagnostic>, Option<IrDump>), Diagnostic> {
+    match generate {
+        #[cfg(feature = "dom")]
         common::GenerateMode::Dom => {
-            let transformer = SolidTransform::new(&allocator, options_ref);
-            transformer.transform(&mut program);
+            let transformer = SolidTransform::new(allocator, options);
+            if emit_ir {
+                let (diagnostics, ir) = transformer.transform_with_ir(program);
+                Ok((diagnostics, Some(ir)))
+            } else {
+                Ok((transformer.transform(program), None))
+            }
         }
+        #[cfg(feature = "ssr")]
         common::GenerateMode::Ssr => {
-            let transformer = SSRTransform::new(&allocator, options_ref);
-            transformer.transform(&mut program);
+            let transformer = SSRTransform::new(allocator, options);
+            if emit_ir {
+                let (diagnostics, ir) = transformer.transform_with_ir(program);
+                Ok((diagnostics, Some(ir)))
+            } else {
+                Ok((transformer.transform(program), None))
+            }
         }
+        #[cfg(feature = "universal")]
         common::GenerateMode::Universal => {
             // Universal mode generates DOM with SSR fallback markers
             // For now, use DOM transform
-            let transformer = SolidTransform::new(&allocator, options_ref);
-            transformer.transform(&mut program);
+            let transformer = SolidTransform::new(allocator, options);
+            if emit_ir {
+                let (diagnostics, ir) = transformer.transform_with_ir(program);
+                Ok((diagnostics, Some(ir)))
+            } else {
+                Ok((transformer.transform(program), None))
+            }
+        }
+        // The backend for this mode isn't part of the build
+        #[allow(unreachable_patterns)]
+        mode => Err(Diagnostic::error(
+            format!(
+                "`{0}` output requires building with the `{0}` feature",
+                mode.as_str()
+            ),
+            Span::default(),
+        )),
+    }
+}
+
+fn transform_internal(source: &str, options: &TransformOptions) -> TransformOutput {
+    let allocator = Allocator::default();
+    let source_type = parse_source_type(options);
+
+    // Parse the source
+    let mut program = Parser::new(&allocator, source, source_type).parse().program;
+
+    let generate = match generate_mode(source, &program, options) {
+        Ok(generate) => generate,
+        Err(output) => return output,
+    };
+
+    let first_statement = program.body.first().map(|stmt| stmt.span().start);
+    common::remove_jsx_comments(&mut program);
+
+    let (mut diagnostics, ir) =
+        match run_backend(&allocator, &mut program, options, generate, options.emit_ir) {
+            Ok(result) => result,
+            Err(diagnostic) => {
+                return TransformOutput {
+                    code: source.to_string(),
+                    map: None,
+                    ir: None,
+                    diagnostics: vec![diagnostic],
+                }
+            }
+        };
+
+    if options.compat == common::Compat::Babel {
+        common::babel_compat(
+            &mut program,
+            AstBuilder::new(&allocator),
+            options.module_name,
+        );
+    }
+
+    if options.target == common::OutputTarget::Es5 {
+        let ast = AstBuilder::new(&allocator);
+        diagnostics.extend(common::lower_to_es5(&mut program, ast));
+    }
+
+    // Comments above the first statement (license banners, `@jsxImportSource`
+    // pragmas) are attached to it. Hand them to the statements inserted above it
+    // so they stay at the top of the file. Directives live outside the body, so
+    // they stay above the insertions.
+    if let (Some(start), Some(stmt)) = (first_statement, program.body.first_mut()) {
+        if stmt.span().start != start {
+            *stmt.span_mut() = Span::empty(start);
         }
     }
 
     // Generate code
-    Codegen::new()
+    let result = Codegen::new()
         .with_options(CodegenOptions {
             source_map_path: if options.source_map {
                 Some(PathBuf::from(options.filename))
@@ -160,10 +723,23 @@ fn transform_internal(source: &str, options: &TransformOptions) -> CodegenReturn
             indent_char: IndentChar::Space,
             ..CodegenOptions::default()
         })
-        .build(&program)
+        .build(&program);
+
+    let code = if options.prettier {
+        common::prettier_format(&result.code, source_type)
+    } else {
+        result.code
+    };
+
+    TransformOutput {
+        code,
+        map: result.map.map(|m| m.to_json_string()),
+        ir,
+        diagnostics,
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "dom", feature = "ssr"))]
 mod tests {
     use super::*;
 
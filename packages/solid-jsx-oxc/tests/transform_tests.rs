@@ -2,8 +2,12 @@
 //!
 //! These tests verify the OXC compiler output matches expected SolidJS patterns.
 
-use common::GenerateMode;
-use solid_jsx_oxc::{transform, TransformOptions};
+use common::{Compat, GenerateMode, OutputTarget, RuntimeVersion};
+use solid_jsx_oxc::ir::TransformContext;
+use solid_jsx_oxc::{
+    analyze, check, explore, ir, minimum_version, required_helpers, transform, Severity,
+    TransformOptions,
+};
 
 /// Helper to normalize whitespace for comparison
 fn normalize(s: &str) -> String {
@@ -60,6 +64,47 @@ fn test_dom_self_closing() {
     assert!(code.contains("template(`<div></div>`)"));
 }
 
+#[test]
+fn test_dom_output_has_no_jsx() {
+    // Every JSX node is replaced by a template clone or an IIFE wiring one up,
+    // so the output parses as plain JavaScript
+    let sources = [
+        r#"const a = <div />;"#,
+        r#"const b = <div class={cls()}><span>{label()}</span></div>;"#,
+        r#"const c = <><p>one</p><Comp>{(x) => <i>{x}</i>}</Comp></>;"#,
+    ];
+    for source in sources {
+        let code = transform(source, None).code;
+        let allocator = oxc_allocator::Allocator::default();
+        let parsed =
+            oxc_parser::Parser::new(&allocator, &code, oxc_span::SourceType::mjs()).parse();
+        assert!(parsed.errors.is_empty(), "not plain JavaScript: {}", code);
+    }
+}
+
+#[test]
+fn test_helper_imports_are_aliased() {
+    // User bindings named like a helper stay untouched
+    let code = transform_dom(r#"const insert = (x) => x; const el = <div>{insert(a())}</div>;"#);
+    let import = code.lines().find(|l| l.starts_with("import {")).unwrap();
+    assert!(import.contains("template as _$template"), "got: {}", code);
+    assert!(import.contains("insert as _$insert"), "got: {}", code);
+    assert!(import.ends_with(r#"from "solid-js/web";"#), "got: {}", code);
+    assert!(code.contains("_$insert(_el$"), "got: {}", code);
+    assert!(code.contains("() => insert(a())"), "got: {}", code);
+
+    let code = transform(
+        "const el = <div>{count()}</div>;",
+        Some(TransformOptions {
+            module_name: "my-renderer",
+            ..TransformOptions::solid_defaults()
+        }),
+    )
+    .code;
+    assert_eq!(code.matches("import {").count(), 1, "got: {}", code);
+    assert!(code.contains(r#"from "my-renderer";"#), "got: {}", code);
+}
+
 // ============================================================================
 // DOM: Dynamic Attributes
 // ============================================================================
@@ -72,6 +117,80 @@ fn test_dom_dynamic_class() {
     assert!(code.contains("style()"));
 }
 
+#[test]
+fn test_dom_per_tag_property_aliases() {
+    let code = transform_dom(r#"<label for={id()}><input readonly={ro()} /></label>"#);
+    assert!(code.contains("_el$1.htmlFor = id()"), "got: {}", code);
+    assert!(code.contains(".readOnly = ro()"), "got: {}", code);
+
+    // Only aliased on the listed tags
+    let code = transform_dom(r#"<div for={id()} />"#);
+    assert!(
+        code.contains(r#"_el$1.setAttribute("for", id())"#),
+        "got: {}",
+        code
+    );
+    let code = transform_dom(r#"<img ismap={m()} />"#);
+    assert!(code.contains("_el$1.isMap = m()"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_class_list_with_previous_value() {
+    let code = transform_dom(r#"<div classList={{ active: isActive(), [cls()]: true }} />"#);
+    assert!(
+        code.contains("_$effect((_p$) => _$classList(_el$1, {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("}, _p$))"), "got: {}", code);
+    assert!(code.contains("[cls()]: true"), "got: {}", code);
+
+    // Static classes alongside go in the template
+    let code = transform_dom(r#"<div class="card" classList={{ active: isActive() }} />"#);
+    assert!(code.contains(r#"<div class="card">"#), "got: {}", code);
+}
+
+#[test]
+fn test_dom_class_namespace_toggles() {
+    let code = transform_dom(
+        r#"<li class="item" class:selected={isSelected()} class:open={open} class:base class:gone={false} />"#,
+    );
+    assert!(code.contains(r#"<li class="item base">"#), "got: {}", code);
+    assert!(
+        code.contains(r#"_$effect(() => _el$1.classList.toggle("selected", isSelected()))"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"_el$1.classList.toggle("open", open)"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("gone"), "got: {}", code);
+
+    let code = transform_dom(r#"<li class:a={true} class:b />"#);
+    assert!(code.contains(r#"<li class="a b">"#), "got: {}", code);
+}
+
+#[test]
+fn test_dom_class_attributes_are_merged() {
+    let code = transform_dom(
+        r#"<div class="card" className={extra()} classList={{ active: isActive() }} />"#,
+    );
+    assert!(code.contains(r#"class="card""#), "got: {}", code);
+    assert_eq!(code.matches("className =").count(), 1, "got: {}", code);
+    assert!(!code.contains("classList("), "got: {}", code);
+    assert!(code.contains(r#"extra() || """#), "got: {}", code);
+    assert!(
+        code.contains(r#"isActive() ? "active" : """#),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<div class="a" className="b" />"#);
+    assert!(code.contains(r#"class="a b""#), "got: {}", code);
+}
+
 #[test]
 fn test_dom_dynamic_multiple_attrs() {
     let code = transform_dom(r#"<div class={cls()} id={id()}>content</div>"#);
@@ -94,6 +213,79 @@ fn test_dom_boolean_attribute() {
     assert!(code.contains("disabled"));
 }
 
+#[test]
+fn test_dom_boolean_attribute_values() {
+    let code = transform_dom(r#"<input disabled={false} required={true} />"#);
+    assert!(code.contains("<input required>"), "got: {}", code);
+    assert!(!code.contains("disabled"), "got: {}", code);
+
+    // Without a property of the same name, the attribute itself is toggled
+    let code = transform_dom(r#"<script defer={lazy()} />"#);
+    assert!(
+        code.contains(r#"_el$1.toggleAttribute("defer", !!lazy())"#),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<button disabled={busy()} />"#);
+    assert!(code.contains("_el$1.disabled = busy()"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_form_control_value_set_after_children() {
+    let code =
+        transform_dom(r#"<select value={v()} class={c()}><option value={a()}>A</option></select>"#);
+    let select = code.find("_el$1.value = v()").unwrap();
+    let option = code.find(".value = a()").unwrap();
+    let class = code.find("_el$1.className = c()").unwrap();
+    assert!(class < option && option < select, "got: {}", code);
+
+    let code = transform_dom(r#"<input type="checkbox" checked={on()} />"#);
+    assert!(code.contains("_el$1.checked = on()"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_enumerated_attributes_are_stringified() {
+    let code = transform_dom(r#"<div draggable={canDrag()} spellcheck contentEditable />"#);
+    assert!(
+        code.contains(r#"setAttribute("draggable", String(canDrag()))"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#"spellcheck="true""#), "got: {}", code);
+    assert!(code.contains(r#"contentEditable="true""#), "got: {}", code);
+}
+
+#[test]
+fn test_dom_aria_attributes() {
+    let code = transform_dom(r#"<div aria-hidden={hidden()} aria-busy>content</div>"#);
+    // The helper removes the attribute for null/undefined and stringifies booleans
+    assert!(code.contains("setAttribute(_el$"), "got: {}", code);
+    assert!(
+        code.contains(r#""aria-hidden", hidden())"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#"aria-busy="true""#), "got: {}", code);
+}
+
+#[test]
+fn test_dom_shared_dynamic_values_are_memoized() {
+    let code = transform_dom(
+        r#"<div title={props.value} id={props.value}><span class={props.value} /></div>"#,
+    );
+    assert_eq!(
+        code.matches("memo(() => props.value)").count(),
+        1,
+        "got: {}",
+        code
+    );
+    assert_eq!(code.matches("props.value").count(), 1, "got: {}", code);
+
+    let code = transform_dom(r#"<div title={a()} id={b()} />"#);
+    assert!(!code.contains("memo("), "got: {}", code);
+}
+
 // ============================================================================
 // DOM: Event Handlers
 // ============================================================================
@@ -106,6 +298,79 @@ fn test_dom_onclick_delegated() {
     assert!(code.contains("delegateEvents"));
 }
 
+#[test]
+fn test_dom_event_handler_with_data() {
+    let code = transform_dom(r#"<button onClick={[select, item.id]}>pick</button>"#);
+    assert!(code.contains("_el$1.$$click = select;"), "got: {}", code);
+    assert!(
+        code.contains("_el$1.$$clickData = item.id;"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<div onMouseEnter={[hover, 1]} />"#);
+    assert!(
+        code.contains(r#"_el$1.addEventListener("mouseenter", (e) => hover(1, e), false)"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_delegated_events_footer() {
+    let code = transform_dom(
+        r#"const a = <input onInput={f} onClick={g} />; const b = <button onClick={h} />;"#,
+    );
+    assert!(
+        code.ends_with(r#"_$delegateEvents(["click", "input"]);"#),
+        "got: {}",
+        code
+    );
+
+    // An existing call is extended instead of delegating twice
+    let code = transform_dom(
+        r#"delegateEvents(["keydown", "click"]); const a = <input onInput={f} onClick={g} />;"#,
+    );
+    assert_eq!(code.matches("delegateEvents(").count(), 1, "got: {}", code);
+    // Longer arrays are printed one element per line
+    assert!(
+        code.replace('\n', "")
+            .contains(r#"delegateEvents(["click","input","keydown"]);"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_prop_attr_bool_namespaces() {
+    let code = transform_dom(
+        r#"<input prop:value={v()} prop:name="q" attr:title={t()} attr:lang="en" bool:hidden={h()} bool:inert />"#,
+    );
+    assert!(code.contains(r#"_el$1.name = "q""#), "got: {}", code);
+    assert!(code.contains("_el$1.value = "), "got: {}", code);
+    assert!(
+        code.contains(r#"_$setAttribute(_el$1, "title", "#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"_el$1.toggleAttribute("hidden", !!"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#"<input lang="en" inert>"#), "got: {}", code);
+
+    // Static values are set once, outside any effect
+    let code = transform_dom(r#"<div attr:title={1} bool:hidden={0} />"#);
+    assert!(code.contains(r#"<div title="1">"#), "got: {}", code);
+    assert!(
+        code.contains(r#"_el$1.toggleAttribute("hidden", !!0)"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("_$effect"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_oncapture_not_delegated() {
     let code = transform_dom(r#"<button onClickCapture={handler}>click</button>"#);
@@ -113,6 +378,42 @@ fn test_dom_oncapture_not_delegated() {
     assert!(code.contains("addEventListener"));
 }
 
+#[test]
+fn test_dom_namespaced_events() {
+    let code = transform_dom(r#"<div on:custom-Event={handler} on:click={onClick} />"#);
+    assert!(
+        code.contains(r#"_$addEventListener(_el$1, "custom-Event", handler, false)"#),
+        "got: {}",
+        code
+    );
+    // `on:` is never delegated, even for delegated events
+    assert!(
+        code.contains(r#"_$addEventListener(_el$1, "click", onClick, false)"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("delegateEvents"), "got: {}", code);
+
+    let code = transform_dom(r#"<div oncapture:click={handler} />"#);
+    assert!(
+        code.contains(r#"_$addEventListener(_el$1, "click", handler, true)"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_on_prefixed_attribute_is_not_event() {
+    let code = transform_dom(r#"<div one={value()} />"#);
+    assert!(
+        code.contains(r#"_$setAttribute(_el$1, "one", "#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("$$e"), "got: {}", code);
+    assert!(!code.contains("addEventListener"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_onscroll_not_delegated() {
     let code = transform_dom(r#"<div onScroll={handler}>scroll</div>"#);
@@ -120,6 +421,26 @@ fn test_dom_onscroll_not_delegated() {
     assert!(code.contains("addEventListener") || code.contains("onscroll"));
 }
 
+#[test]
+fn test_dom_replaced_delegated_events_table() {
+    let options = TransformOptions {
+        delegated_events: vec!["scroll"],
+        replace_delegated_events: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<div onScroll={a} onClick={b} />"#;
+    let code = transform(source, Some(options)).code;
+    assert!(code.contains("$$scroll"), "got: {}", code);
+    assert!(!code.contains("$$click"), "got: {}", code);
+
+    let options = TransformOptions {
+        replace_delegated_events: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(source, Some(options)).code;
+    assert!(!code.contains("delegateEvents"), "got: {}", code);
+}
+
 // ============================================================================
 // DOM: Dynamic Children
 // ============================================================================
@@ -138,6 +459,27 @@ fn test_dom_multiple_dynamic_children() {
     assert!(code.contains("b()"));
 }
 
+#[test]
+fn test_dom_spread_child_is_inserted() {
+    let code = transform_dom(r#"<div>{...items()}</div>"#);
+    assert!(
+        code.contains("_$insert(_el$1, () => items())"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<div><span />{...items()}</div>"#);
+    assert!(
+        code.contains("_$insert(_el$1, () => items(), null)"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"const list = <>{...items()}</>;"#);
+    assert!(code.contains("items()"), "got: {}", code);
+    assert!(!code.contains("spread child"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_mixed_children() {
     let code = transform_dom(r#"<div>Hello {name()}!</div>"#);
@@ -163,9 +505,49 @@ fn test_dom_nested_element_after_text_walks_next_sibling() {
 
 #[test]
 fn test_dom_component_between_elements_inserts_before_marker() {
+    // The following element is the marker, so the template needs no placeholder
     let code = transform_dom(r#"<div><span>text</span><Counter /><p>more</p></div>"#);
-    assert!(code.contains("<span>text</span><!><p>more</p>"));
-    assert!(code.contains("insert(_el$1, createComponent(Counter, {}), _el$2)"));
+    assert!(
+        code.contains("<span>text</span><p>more</p>"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("_el$2 = _el$1.firstChild.nextSibling"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("insert(_el$1, _$createComponent(Counter, {}), _el$2)"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_insert_markers() {
+    // Last child: appended
+    let code = transform_dom(r#"<div><span>text</span>{name()}</div>"#);
+    assert!(!code.contains("<!>"), "got: {}", code);
+    assert!(
+        code.contains("insert(_el$1, () => name(), null)"),
+        "got: {}",
+        code
+    );
+
+    // Followed by another dynamic child: placeholder
+    let code = transform_dom(r#"<div>{a() ? "x" : "y"}<Comp /></div>"#);
+    assert!(code.contains("<div><!></div>"), "got: {}", code);
+    assert!(
+        code.contains(r#"insert(_el$1, () => a() ? "x" : "y", _el$2)"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("insert(_el$1, _$createComponent(Comp, {}), null)"),
+        "got: {}",
+        code
+    );
 }
 
 // ============================================================================
@@ -175,13 +557,62 @@ fn test_dom_component_between_elements_inserts_before_marker() {
 #[test]
 fn test_dom_ref_variable() {
     let code = transform_dom(r#"<div ref={myRef}>content</div>"#);
-    assert!(code.contains("myRef"));
+    assert!(code.contains("var _ref$2 = myRef;"), "got: {}", code);
+    assert!(
+        code.contains(r#"typeof _ref$2 === "function" ? _$use(_ref$2, _el$1) : myRef = _el$1"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("use as _$use"), "got: {}", code);
 }
 
 #[test]
 fn test_dom_ref_callback() {
     let code = transform_dom(r#"<div ref={el => setRef(el)}>content</div>"#);
-    assert!(code.contains("setRef"));
+    assert!(
+        code.contains("((el) => setRef(el))(_el$1)"),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("_$use"), "got: {}", code);
+}
+
+#[test]
+fn test_component_ref_forwards_the_variable() {
+    let code = transform_dom(r#"<Comp ref={myRef} />"#);
+    assert!(code.contains("ref(r$) {"), "got: {}", code);
+    assert!(
+        code.contains(r#"typeof _ref$1 === "function" ? _ref$1(r$) : myRef = r$"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("_$use"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_ref_array() {
+    let code = transform_dom(r#"<div ref={[a, setB, makeRef()]}>content</div>"#);
+    let a = code.find("a = _el$1").unwrap();
+    let b = code.find("setB = _el$1").unwrap();
+    let made = code.find("makeRef()").unwrap();
+    assert!(a < b && b < made, "got: {}", code);
+}
+
+#[test]
+fn test_dom_multiple_refs_apply_in_source_order() {
+    let code = transform_dom(r#"<div ref={a} {...props} ref={el => b(el)}>content</div>"#);
+    let a = code.find("a = _el$1").unwrap();
+    let spread = code.find("spread(").unwrap();
+    let b = code.find("b(el)").unwrap();
+    assert!(a < spread && spread < b, "got: {}", code);
+}
+
+#[test]
+fn test_component_refs_share_one_forwarder() {
+    let code = transform_dom(r#"<Comp ref={a} ref={[b, c]} />"#);
+    assert_eq!(code.matches("ref(r$)").count(), 1, "got: {}", code);
+    assert!(code.contains("a = r$"), "got: {}", code);
+    assert!(code.contains("c = r$"), "got: {}", code);
 }
 
 // ============================================================================
@@ -202,6 +633,69 @@ fn test_dom_style_object_static() {
     assert!(code.contains("font-size: 14px"));
 }
 
+#[test]
+fn test_dom_style_custom_properties() {
+    let code = transform_dom(
+        r#"<div style={{ "--brand-color": color(), "--cols": 3, fontSize: size() }} />"#,
+    );
+    assert!(
+        code.contains(r#"style.setProperty("--brand-color", color())"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"style.setProperty("--cols", 3)"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("fontSize"), "got: {}", code);
+
+    let code = transform_dom(r#"<div style={{ "--cols": 3, "--Gap": "1em" }} />"#);
+    assert!(code.contains("--cols: 3;"), "got: {}", code);
+    assert!(code.contains("--Gap: 1em"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_style_object_with_previous_value() {
+    let code = transform_dom(r#"<div style={{ color: c(), width: w }} />"#);
+    assert!(
+        code.contains("_$effect((_p$) => _$style(_el$1, {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("}, _p$))"), "got: {}", code);
+
+    let code = transform_dom(r#"<div style={"color: " + "red"} />"#);
+    assert!(
+        code.contains(r#"<div style="color: red">"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("_$style"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_style_namespace_properties() {
+    let code = transform_dom(
+        r#"<div style="color: red;" style:border-color={c()} style:background-color={bg} style:width="10px" style:z-index={2} />"#,
+    );
+    assert!(
+        code.contains(r#"<div style="color: red; width: 10px; z-index: 2">"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"_$effect(() => _el$1.style.setProperty("border-color", c()))"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"_el$1.style.setProperty("background-color", bg)"#),
+        "got: {}",
+        code
+    );
+}
+
 #[test]
 fn test_dom_style_object_dynamic() {
     let code = transform_dom(r#"<div style={styles()}>content</div>"#);
@@ -223,8 +717,65 @@ fn test_dom_innerhtml() {
 #[test]
 fn test_dom_textcontent() {
     let code = transform_dom(r#"<div textContent={text} />"#);
-    assert!(code.contains(".textContent"));
-    assert!(code.contains("text"));
+    assert!(code.contains("<div> </div>"), "got: {}", code);
+    assert!(
+        code.contains("_el$1.firstChild.data = text"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_innerhtml_is_not_escaped() {
+    let code = transform_dom(r#"<div innerHTML="<b>bold</b>" />"#);
+    assert!(
+        code.contains(r#"_el$1.innerHTML = "<b>bold</b>""#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("&lt;"), "got: {}", code);
+
+    let result = transform(r#"<div innerHTML={html()}>fallback</div>"#, None);
+    assert!(!result.code.contains("insert"), "got: {}", result.code);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(result.diagnostics[0].message.contains("`innerHTML`"));
+}
+
+#[test]
+fn test_dom_sole_text_child_updates_text_node() {
+    let code = transform_dom(r#"<span>{`${count()} items`}</span>"#);
+    assert!(code.contains("<span> </span>"), "got: {}", code);
+    assert!(
+        code.contains("_$effect(() => _el$1.firstChild.data = `${count()} items`)"),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("insert"), "got: {}", code);
+}
+
+#[test]
+fn test_inner_text_replaces_children() {
+    let code = transform_dom(r#"<div innerText={text()} />"#);
+    assert!(code.contains(".innerText = text()"), "got: {}", code);
+    assert!(!code.contains("setAttribute"), "got: {}", code);
+
+    let code = transform_dom(r#"<div innerText="a < b" />"#);
+    assert!(code.contains("<div>a &lt; b</div>"), "got: {}", code);
+
+    let code = transform_ssr(r#"<div innerText={text()} />"#);
+    assert!(code.contains("escape(text())"), "got: {}", code);
+    assert!(!code.contains("innerText"), "got: {}", code);
+
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = TransformOptions {
+            generate,
+            ..TransformOptions::solid_defaults()
+        };
+        let result = transform(r#"<p innerText={text()}>fallback</p>"#, Some(options));
+        assert!(!result.code.contains("fallback"), "got: {}", result.code);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert!(result.diagnostics[0].message.contains("`innerText`"));
+    }
 }
 
 // ============================================================================
@@ -238,6 +789,79 @@ fn test_dom_spread() {
     assert!(code.contains("props"));
 }
 
+#[test]
+fn test_dom_attributes_after_spread_are_merged() {
+    let code = transform_dom(r#"<div {...props} onClick={handler} title="x">content</div>"#);
+    let spread = code.find("spread(_el$1, _$mergeProps(props, {").unwrap();
+    let handler = code.find("\"onClick\": handler").unwrap();
+    assert!(spread < handler, "got: {}", code);
+    assert!(code.contains("\"title\": \"x\""), "got: {}", code);
+    assert!(!code.contains("$$click"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_reactive_spread_sources() {
+    let code = transform_dom(r#"<div {...getProps()} />"#);
+    assert!(
+        code.contains("_$spread(_el$1, _$mergeProps(getProps), false, false)"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<div {...props.attrs} class="x" />"#);
+    assert!(
+        code.contains("_$mergeProps(() => props.attrs, {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#""class": "x""#), "got: {}", code);
+
+    let code = transform_dom(r#"<div {...props} />"#);
+    assert!(
+        code.contains("_$spread(_el$1, props, false, false)"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_ref_directive_and_spread_in_source_order() {
+    let code = transform_dom(r#"<div ref={a} use:tip={x} {...props} ref={b}>content</div>"#);
+    let a = code.find("a = _el$1").unwrap();
+    let tip = code.find("use(tip").unwrap();
+    let spread = code.find("spread(").unwrap();
+    let b = code.find("b = _el$1").unwrap();
+    assert!(a < tip && tip < spread && spread < b, "got: {}", code);
+}
+
+#[test]
+fn test_dom_handler_before_spread_is_reported() {
+    let result = transform(r#"<div onClick={a} {...props}>content</div>"#, None);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(result.diagnostics[0].message.contains("`onClick`"));
+    assert!(result.code.contains("$$click"), "got: {}", result.code);
+}
+
+#[test]
+fn test_dom_noscript_content_is_template_text() {
+    let result = transform(
+        r#"<div><noscript><img src="a.png" alt="x" />Enable JS</noscript></div>"#,
+        None,
+    );
+    assert!(
+        result
+            .code
+            .contains(r#"<noscript><img src="a.png" alt="x">Enable JS</noscript>"#),
+        "got: {}",
+        result.code
+    );
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+
+    let result = transform(r#"<noscript>{message()}</noscript>"#, None);
+    assert!(!result.code.contains("insert"), "got: {}", result.code);
+    assert_eq!(result.diagnostics.len(), 1);
+}
+
 // ============================================================================
 // DOM: Nested Dynamic Elements
 // ============================================================================
@@ -278,6 +902,58 @@ fn test_dom_component_with_props() {
     assert!(code.contains("label"));
 }
 
+#[test]
+fn test_member_expression_components() {
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = || TransformOptions {
+            generate,
+            ..TransformOptions::solid_defaults()
+        };
+        let code = transform(r#"<Foo.Bar.Baz size={2} />"#, Some(options())).code;
+        assert!(
+            code.contains("createComponent(Foo.Bar.Baz, {"),
+            "got: {}",
+            code
+        );
+
+        // A lowercase member expression is still a component reference
+        let code = transform(r#"<ctx.item />"#, Some(options())).code;
+        assert!(
+            code.contains("createComponent(ctx.item, {"),
+            "got: {}",
+            code
+        );
+        assert!(!code.contains("<ctx.item"), "got: {}", code);
+    }
+}
+
+#[test]
+fn test_static_component_props_are_hoisted() {
+    let source =
+        r#"<ul><Item label="a" size={2} /><Item label="a" size={2} /><Item label={name} /></ul>"#;
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = TransformOptions {
+            generate,
+            hoist_static_props: true,
+            ..TransformOptions::solid_defaults()
+        };
+        let code = transform(source, Some(options)).code;
+        assert_eq!(code.matches("size: 2").count(), 1, "got: {}", code);
+        assert!(code.contains("const _props$"), "got: {}", code);
+        assert_eq!(
+            code.matches("createComponent(Item, _props$").count(),
+            2,
+            "got: {}",
+            code
+        );
+        assert!(code.contains("get label()"), "got: {}", code);
+    }
+
+    // Off by default
+    let code = transform_dom(r#"<Item label="a" />"#);
+    assert!(!code.contains("_props$"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_component_with_children() {
     let code = transform_dom(r#"<Button>Click me</Button>"#);
@@ -287,17 +963,161 @@ fn test_dom_component_with_children() {
 }
 
 #[test]
-fn test_dom_component_with_jsx_children() {
-    let code = transform_dom(r#"<Button><span>icon</span> Click</Button>"#);
-    assert!(code.contains("createComponent"));
-    // Children should include the span template
-    assert!(code.contains("template"));
+fn test_dom_component_children_props() {
+    // A render callback is passed as written
+    let code = transform_dom(r#"<List>{item => <li>{item}</li>}</List>"#);
+    assert!(code.contains("children: (item) =>"), "got: {}", code);
+    assert!(!code.contains("get children"), "got: {}", code);
+
+    // An element with bindings is created whole, not just its first binding
+    let code = transform_dom(r#"<Card><p>{label()}</p></Card>"#);
+    assert!(
+        code.contains("get children() {\nreturn (() => {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("_$insert(_el$"), "got: {}", code);
+    assert!(!code.contains("return _$insert("), "got: {}", code);
+
+    // Several children are an array
+    let code = transform_dom(r#"<Card><h1>Title</h1>{body()}</Card>"#);
+    assert!(
+        code.contains("return [_tmpl$1.cloneNode(true), body()];"),
+        "got: {}",
+        code
+    );
 }
 
 #[test]
-fn test_dom_component_nested_in_element() {
-    // This is the critical test - components inside native elements
-    // should be transformed with insert() + createComponent()
+fn test_component_spreads_merge_in_source_order() {
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = || TransformOptions {
+            generate,
+            ..TransformOptions::solid_defaults()
+        };
+        let code = transform(r#"<Comp a={1} {...x} b={y()} {...z} />"#, Some(options())).code;
+        assert!(
+            code.contains("_$mergeProps({ a: 1 }, x, {"),
+            "got: {}",
+            code
+        );
+        assert!(code.contains("get b()"), "got: {}", code);
+        assert!(code.contains("}, z)"), "got: {}", code);
+
+        // A lone spread is passed as the props object
+        let code = transform(r#"<Comp {...props} />"#, Some(options())).code;
+        assert!(
+            code.contains("createComponent(Comp, props)"),
+            "got: {}",
+            code
+        );
+        assert!(!code.contains("mergeProps"), "got: {}", code);
+    }
+}
+
+#[test]
+fn test_constant_component_children_skip_the_getter() {
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = || TransformOptions {
+            generate,
+            ..TransformOptions::solid_defaults()
+        };
+        let code = transform(r#"<Button>Click {"me"}</Button>"#, Some(options())).code;
+        assert!(code.contains("children: ["), "got: {}", code);
+        assert!(!code.contains("get children"), "got: {}", code);
+
+        let code = transform(r#"<Button>Click {label()}</Button>"#, Some(options())).code;
+        assert!(code.contains("get children()"), "got: {}", code);
+    }
+}
+
+#[test]
+fn test_jsx_in_attribute_conditionals() {
+    for generate in [GenerateMode::Dom, GenerateMode::Ssr] {
+        let options = |wrap_conditionals| TransformOptions {
+            generate,
+            wrap_conditionals,
+            ..TransformOptions::solid_defaults()
+        };
+        let source = r#"<Button icon={open() ? <Open /> : <Closed />} badge={count() && <b>{count()}</b>} />"#;
+        let code = transform(source, Some(options(true))).code;
+        assert!(!code.contains("<Open"), "got: {}", code);
+        assert!(code.contains("createComponent(Open"), "got: {}", code);
+        assert!(code.contains("memo(() => !!open())() ?"), "got: {}", code);
+        assert!(code.contains("memo(() => !!count())() &&"), "got: {}", code);
+
+        let code = transform(source, Some(options(false))).code;
+        assert!(code.contains("return open() ?"), "got: {}", code);
+        assert!(!code.contains("memo("), "got: {}", code);
+    }
+}
+
+#[test]
+fn test_constant_expressions_are_folded_into_templates() {
+    let source = r#"<div title={"a" + "b"}>{1 + 2} items {`x${"y"}`}<b>{n * 2}</b></div>"#;
+    let code = transform_dom(source);
+    assert!(
+        code.contains(r#"<div title="ab">3 items xy<b></b></div>"#),
+        "got: {}",
+        code
+    );
+    assert_eq!(code.matches("_$insert(").count(), 1, "got: {}", code);
+    assert!(!code.contains("_$effect("), "got: {}", code);
+
+    // Quotes in the markup may be escaped in the template strings
+    let code = transform_ssr(source).replace("\\\"", "\"");
+    assert!(code.contains(r#" title="ab""#), "got: {}", code);
+    assert!(code.contains(">3 items xy<b>"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_child_conditionals_are_memoized() {
+    let options = |wrap_conditionals| TransformOptions {
+        wrap_conditionals,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<div>{open() ? <Open /> : <Closed />}<p />{count() && <b>new</b>}</div>"#;
+    let code = transform(source, Some(options(true))).code;
+    assert!(code.contains("= _$memo(() => !!open());"), "got: {}", code);
+    assert!(code.contains("return () => _c$"), "got: {}", code);
+    assert!(code.contains("= _$memo(() => !!count());"), "got: {}", code);
+    assert!(!code.contains("() => open()"), "got: {}", code);
+
+    // Static tests and branches have nothing to memoize
+    let code = transform(r#"<div>{open() ? "yes" : "no"}</div>"#, Some(options(true))).code;
+    assert!(!code.contains("memo("), "got: {}", code);
+
+    let code = transform(source, Some(options(false))).code;
+    assert!(!code.contains("memo("), "got: {}", code);
+}
+
+#[test]
+fn test_dom_component_with_jsx_children() {
+    let code = transform_dom(r#"<Button><span>icon</span> Click</Button>"#);
+    assert!(code.contains("createComponent"));
+    // Children should include the span template
+    assert!(code.contains("template"));
+}
+
+#[test]
+fn test_dom_static_component_children_clone_module_templates() {
+    let code =
+        transform_dom(r#"<Show when={ready()} fallback={<p>Loading</p>}><p>Loading</p></Show>"#);
+    assert!(!code.contains("(() =>"), "got: {}", code);
+    // Both uses clone the one template
+    assert_eq!(code.matches("template(").count(), 1, "got: {}", code);
+    assert_eq!(
+        code.matches("_tmpl$1.cloneNode(true)").count(),
+        2,
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_component_nested_in_element() {
+    // This is the critical test - components inside native elements
+    // should be transformed with insert() + createComponent()
     let code = transform_dom(r#"<main><Counter /></main>"#);
 
     // Should have a template for the parent element with a placeholder marker
@@ -380,8 +1200,9 @@ fn test_dom_deeply_nested_component() {
     // Component nested multiple levels deep
     let code = transform_dom(r#"<div><main><Counter /></main></div>"#);
 
-    // Template should have placeholder in the nested element
-    assert!(code.contains("<div><main><!></main></div>"));
+    // A sole child is appended to its parent, so the template needs no marker
+    assert!(code.contains("<div><main></main></div>"));
+    assert!(!code.contains("<!>"), "got: {}", code);
 
     // Should walk to the parent element (main)
     assert!(
@@ -403,7 +1224,7 @@ fn test_dom_very_deeply_nested_component() {
     let code = transform_dom(r#"<div><section><article><MyComponent /></article></section></div>"#);
 
     // Template should have placeholder
-    assert!(code.contains("<div><section><article><!></article></section></div>"));
+    assert!(code.contains("<div><section><article></article></section></div>"));
 
     // Should walk through nested elements
     assert!(
@@ -429,16 +1250,40 @@ fn test_dom_for() {
     let code = transform_dom(r#"<For each={items}>{item => <div>{item}</div>}</For>"#);
     assert!(code.contains("createComponent"));
     assert!(code.contains("For"));
-    assert!(code.contains("each:"));
+    assert!(code.contains("get each()"));
     assert!(code.contains("items"));
 }
 
+#[test]
+fn test_dom_for_props() {
+    let code = transform_dom(
+        r#"<For each={items()} fallback={<Empty />}>{item => <li>{item}</li>}</For>"#,
+    );
+    assert!(
+        code.contains("get each() {\nreturn items();"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("get fallback() {\nreturn _$createComponent(Empty, {});"),
+        "got: {}",
+        code
+    );
+    // The callback is passed as written, with its JSX compiled
+    assert!(code.contains("children: (item) =>"), "got: {}", code);
+    assert!(!code.contains("get children"), "got: {}", code);
+
+    let code = transform_dom(r#"<For each={[1, 2]}>{n => n}</For>"#);
+    assert!(code.contains("each: [1, 2]"), "got: {}", code);
+    assert!(!code.contains("_props$"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_show() {
     let code = transform_dom(r#"<Show when={visible}><div>shown</div></Show>"#);
     assert!(code.contains("createComponent"));
     assert!(code.contains("Show"));
-    assert!(code.contains("when:"));
+    assert!(code.contains("get when()"));
     assert!(code.contains("visible"));
 }
 
@@ -448,7 +1293,7 @@ fn test_dom_show_with_fallback() {
         r#"<Show when={visible} fallback={<div>hidden</div>}><div>shown</div></Show>"#,
     );
     assert!(code.contains("Show"));
-    assert!(code.contains("fallback:"));
+    assert!(code.contains("get fallback()"));
 }
 
 #[test]
@@ -459,6 +1304,43 @@ fn test_dom_switch_match() {
     assert!(code.contains("Match"));
 }
 
+#[test]
+fn test_dom_show_props() {
+    let code = transform_dom(
+        r#"<Show when={user()} keyed fallback={<p>none</p>}>{u => <p>{u.name}</p>}</Show>"#,
+    );
+    assert!(
+        code.contains("get when() {\nreturn user();"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("keyed: true"), "got: {}", code);
+    assert!(code.contains("get fallback() {"), "got: {}", code);
+    assert!(code.contains("children: (u) =>"), "got: {}", code);
+
+    let code = transform_dom(r#"<Show when={ok()}><p>{label()}</p></Show>"#);
+    assert!(code.contains("get children() {"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_switch_passes_match_calls() {
+    let code = transform_dom(
+        r#"<Switch fallback={"none"}>
+            <Match when={a()}>A</Match>
+            <Match when={b()}>{v => v}</Match>
+        </Switch>"#,
+    );
+    assert!(code.contains("fallback: \"none\""), "got: {}", code);
+    assert!(
+        code.contains("return [_$createComponent(Match, {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("children: \"A\""), "got: {}", code);
+    assert!(code.contains("children: (v) => v"), "got: {}", code);
+    assert!(code.contains("get when() {\nreturn b();"), "got: {}", code);
+}
+
 #[test]
 fn test_dom_index() {
     let code = transform_dom(r#"<Index each={items}>{(item, i) => <div>{i()}</div>}</Index>"#);
@@ -471,7 +1353,46 @@ fn test_dom_suspense() {
     let code =
         transform_dom(r#"<Suspense fallback={<div>Loading...</div>}><Content /></Suspense>"#);
     assert!(code.contains("Suspense"));
-    assert!(code.contains("fallback:"));
+    assert!(code.contains("get fallback()"));
+    assert!(
+        code.contains("get children() {\nreturn _$createComponent(Content, {});"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_portal_props() {
+    let code = transform_dom(r#"<Portal mount={target()} useShadow><div>hi</div></Portal>"#);
+    assert!(
+        code.contains("get mount() {\nreturn target();"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("useShadow: true"), "got: {}", code);
+    assert!(!code.contains("isSVG"), "got: {}", code);
+
+    // SVG content has to be mounted in an SVG container
+    let code = transform_dom(r#"<Portal mount={layer}><circle r="4" /></Portal>"#);
+    assert!(code.contains("isSVG: true"), "got: {}", code);
+    let code = transform_dom(r#"<Portal isSVG={false}><circle r="4" /></Portal>"#);
+    assert!(code.contains("isSVG: false"), "got: {}", code);
+    assert!(!code.contains("isSVG: true"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_dynamic_merges_spread_props() {
+    let code = transform_dom(r#"<Dynamic component={view()} {...rest} title="x" />"#);
+    assert!(
+        code.contains("createComponent(Dynamic, _$mergeProps({ get component() {"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("return view();\n} }, rest, { title: \"x\" })"),
+        "got: {}",
+        code
+    );
 }
 
 #[test]
@@ -480,6 +1401,13 @@ fn test_dom_error_boundary() {
         r#"<ErrorBoundary fallback={err => <div>{err}</div>}><Content /></ErrorBoundary>"#,
     );
     assert!(code.contains("ErrorBoundary"));
+    assert!(code.contains("fallback: (err) =>"), "got: {}", code);
+
+    let code = transform_dom(
+        r#"<ErrorBoundary fallback={(err, reset) => <button onClick={reset}>{err.message}</button>}><Content /></ErrorBoundary>"#,
+    );
+    assert!(code.contains("fallback: (err, reset) =>"), "got: {}", code);
+    assert!(code.contains("get children() {"), "got: {}", code);
 }
 
 // ============================================================================
@@ -496,7 +1424,7 @@ fn test_ssr_static_element() {
 #[test]
 fn test_ssr_dynamic_attribute() {
     let code = transform_ssr(r#"<div class={style()}>content</div>"#);
-    assert!(code.contains("ssr`"));
+    assert!(code.contains("_$ssr(_tmpl$"), "got: {}", code);
     assert!(code.contains("escape"));
     assert!(code.contains("style()"));
 }
@@ -504,7 +1432,16 @@ fn test_ssr_dynamic_attribute() {
 #[test]
 fn test_ssr_dynamic_child() {
     let code = transform_ssr(r#"<div>{count()}</div>"#);
-    assert!(code.contains("ssr`"));
+    assert!(
+        code.contains(r#"const _tmpl$1 = ["<div>", "</div>"];"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("_$ssr(_tmpl$1, _$escape(count()))"),
+        "got: {}",
+        code
+    );
     assert!(code.contains("escape"));
     assert!(code.contains("count()"));
 }
@@ -516,6 +1453,64 @@ fn test_ssr_component() {
     assert!(code.contains("Button"));
 }
 
+#[test]
+fn test_ssr_nested_component_keeps_props() {
+    let code = transform_ssr(r#"<div><Layout><Header title="x" /></Layout></div>"#);
+    assert!(
+        code.contains(r#"_$createComponent(Header, { title: "x" })"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        !code.contains("_$createComponent(Header, {})"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_ssr_component_static_child_with_attribute() {
+    let code = transform_ssr(r#"<Comp><a href="/x">y</a></Comp>"#);
+    assert!(
+        code.contains(r#""<a href=\"/x\">y</a>""#) || code.contains(r#"'<a href="/x">y</a>'"#),
+        "got: {}",
+        code
+    );
+
+    let code = transform_ssr(r#"<Show when={x}><div class="a">hi</div></Show>"#);
+    assert!(
+        code.contains(r#""<div class=\"a\">hi</div>""#)
+            || code.contains(r#"'<div class="a">hi</div>'"#),
+        "got: {}",
+        code
+    );
+}
+
+// ============================================================================
+// SSR: Fragments
+// ============================================================================
+
+#[test]
+fn test_ssr_fragment_lone_expression() {
+    let code = transform_ssr(r#"const el = <>{name()}</>;"#);
+    assert!(code.contains("const el = name();"), "got: {}", code);
+    assert!(!code.contains("/* expr */"), "got: {}", code);
+}
+
+#[test]
+fn test_ssr_fragment_expression_with_markup() {
+    let code = transform_ssr(r#"const el = <><span>hi</span>{name()}</>;"#);
+    assert!(code.contains("_$escape(name())"), "got: {}", code);
+    assert!(!code.contains("/* expr */"), "got: {}", code);
+}
+
+#[test]
+fn test_ssr_fragment_spread_child() {
+    let code = transform_ssr(r#"const el = <><span>hi</span>{...items}</>;"#);
+    assert!(code.contains("_$escape(items)"), "got: {}", code);
+    assert!(!code.contains("/* spread */"), "got: {}", code);
+}
+
 #[test]
 fn test_ssr_for() {
     let code = transform_ssr(r#"<For each={items}>{item => <li>{item}</li>}</For>"#);
@@ -523,129 +1518,1751 @@ fn test_ssr_for() {
     assert!(code.contains("each:"));
 }
 
-// ============================================================================
-// Edge Cases
-// ============================================================================
-
 #[test]
-fn test_empty_fragment() {
-    let code = transform_dom(r#"<></>"#);
-    // Empty fragment should produce minimal output
-    assert!(!code.is_empty());
+fn test_ssr_strips_event_handlers() {
+    let code = transform_ssr(r#"<button onClick={handler} on:focus={onFocus}>click</button>"#);
+    assert!(!code.contains("onClick"), "got: {}", code);
+    assert!(!code.contains("on:focus"), "got: {}", code);
+    assert!(!code.contains("handler"), "got: {}", code);
 }
 
 #[test]
-fn test_fragment_with_children() {
-    let code = transform_dom(r#"<><div>a</div><div>b</div></>"#);
-    assert!(code.contains("template"));
+fn test_ssr_enumerated_attributes_are_stringified() {
+    let code = transform_ssr(r#"<div draggable={canDrag()} spellcheck />"#);
+    assert!(code.contains(r#"draggable=\""#), "got: {}", code);
+    assert!(code.contains("String(canDrag())"), "got: {}", code);
+    assert!(!code.contains("ssrAttribute"), "got: {}", code);
+    assert!(code.contains(r#"spellcheck=\"true\""#), "got: {}", code);
 }
 
 #[test]
-fn test_svg_element() {
-    let code = transform_dom(r#"<svg><circle cx="50" cy="50" r="40" /></svg>"#);
-    assert!(code.contains("svg"));
-    assert!(code.contains("circle"));
+fn test_ssr_aria_attributes() {
+    let code = transform_ssr(r#"<div aria-hidden={hidden()} aria-busy>content</div>"#);
+    assert!(
+        code.contains(r#"ssrAttribute("aria-hidden", _$escape(hidden(), true), false)"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#"aria-busy=\"true\""#), "got: {}", code);
 }
 
 #[test]
-fn test_custom_element() {
-    let code = transform_dom(r#"<my-element attr="value">content</my-element>"#);
-    assert!(code.contains("my-element"));
+fn test_ssr_conditional_and_boolean_attributes() {
+    let code = transform_ssr(r#"<a disabled={busy()} rel={link.rel || undefined} title={t()} />"#);
+    assert!(
+        code.contains(r#"ssrAttribute("disabled", busy(), true)"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"ssrAttribute("rel", _$escape(link.rel || undefined, true), false)"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains(r#"disabled=\""#), "got: {}", code);
+    assert!(code.contains(r#"title=\""#), "got: {}", code);
 }
 
 #[test]
-fn test_namespaced_attribute() {
-    let code = transform_dom(
-        r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#id" /></svg>"##,
+fn test_ssr_style_custom_properties() {
+    let code = transform_ssr(r#"<div style={{ "--brand-color": color() }} />"#);
+    assert!(code.contains("ssrStyle("), "got: {}", code);
+    assert!(
+        code.contains(r#""--brand-color": color()"#),
+        "got: {}",
+        code
     );
-    assert!(code.contains("xlink:href"));
 }
 
 #[test]
-fn test_whitespace_handling() {
-    let code = transform_dom(
-        r#"<div>
-        hello
-        world
-    </div>"#,
+fn test_ssr_style_and_class_list_objects() {
+    let code = transform_ssr(r#"<div style={{ color: "red", fontSize: 14 }} />"#);
+    assert!(
+        code.contains(r#""<div style=\"color: red; font-size: 14px\"></div>""#),
+        "got: {}",
+        code
     );
-    // Should handle whitespace appropriately
-    assert!(code.contains("hello"));
+
+    let code = transform_ssr(r#"<div style={{ color: c() }} classList={{ active: on() }} />"#);
+    assert!(code.contains("_$ssrStyle({ color: c() })"), "got: {}", code);
+    assert!(
+        code.contains("_$ssrClassList({ active: on() })"),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("[object Object]"), "got: {}", code);
 }
 
 #[test]
-fn test_special_characters() {
-    let code = transform_dom(r#"<div>&amp; &lt; &gt;</div>"#);
-    // HTML entities should be preserved or properly escaped
-    assert!(!code.is_empty());
+fn test_ssr_class_attributes_are_merged() {
+    let code = transform_ssr(
+        r#"<div class="card" className={extra()} classList={{ active: isActive() }} />"#,
+    );
+    assert_eq!(code.matches("class=").count(), 1, "got: {}", code);
+    assert!(code.contains(r#"class=\"card "#), "got: {}", code);
+    assert!(code.contains(r#"extra() || """#), "got: {}", code);
+    assert!(!code.contains("ssrClassList"), "got: {}", code);
 }
 
-// ============================================================================
-// Import Generation
-// ============================================================================
+#[test]
+fn test_ssr_keeps_attributes_starting_with_on() {
+    let code = transform_ssr(r#"<details open={isOpen()}>content</details>"#);
+    assert!(code.contains("open"), "got: {}", code);
+}
 
 #[test]
-fn test_dom_imports_template() {
-    let code = transform_dom(r#"<div>hello</div>"#);
-    assert!(code.contains("import"));
-    assert!(code.contains("template"));
-    assert!(code.contains("solid-js/web"));
+fn test_ssr_event_diagnostics() {
+    let source = r#"<button onClick={handler}>click</button>"#;
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        ssr_event_diagnostics: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let result = transform(source, Some(options));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(result.diagnostics[0].message.contains("onClick"));
+
+    let quiet = transform(
+        source,
+        Some(TransformOptions {
+            generate: GenerateMode::Ssr,
+            ..TransformOptions::solid_defaults()
+        }),
+    );
+    assert!(quiet.diagnostics.is_empty());
 }
 
 #[test]
-fn test_dom_imports_insert() {
-    let code = transform_dom(r#"<div>{dynamic()}</div>"#);
-    assert!(code.contains("insert"));
+fn test_ssr_ref_compiles_to_nothing() {
+    let code = transform_ssr(r#"<div ref={setRef(el)}>content</div>"#);
+    assert!(code.contains("\"<div>content</div>\""), "got: {}", code);
+    assert!(!code.contains("setRef"), "got: {}", code);
+    assert!(!code.contains("ref="), "got: {}", code);
 }
 
 #[test]
-fn test_dom_imports_effect() {
-    let code = transform_dom(r#"<div class={dynamic()}>content</div>"#);
-    assert!(code.contains("effect"));
+fn test_ssr_ref_dropped_with_spread() {
+    let code = transform_ssr(r#"<div {...props} ref={myRef} id="a">content</div>"#);
+    assert!(code.contains("_$ssrSpread("), "got: {}", code);
+    assert!(!code.contains("myRef"), "got: {}", code);
 }
 
 #[test]
-fn test_dom_imports_delegate_events() {
-    let code = transform_dom(r#"<button onClick={handler}>click</button>"#);
-    assert!(code.contains("delegateEvents"));
+fn test_ssr_spread_attributes() {
+    let code = transform_ssr(r#"<input {...rest} />"#);
+    assert!(
+        code.contains(r#"_$ssr(_tmpl$1, _$ssrSpread(rest, false, false))"#),
+        "got: {}",
+        code
+    );
+
+    // Attributes around the spread are merged in source order; events are dropped
+    let code = transform_ssr(r#"<div class="box" {...props} onClick={f}>hi</div>"#);
+    assert!(
+        code.contains(r#"_$ssrSpread(_$mergeProps({ "class": "box" }, props), false, true)"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(r#"["<div", ">hi</div>"]"#), "got: {}", code);
+
+    let code = transform_ssr(r#"<svg {...p} />"#);
+    assert!(
+        code.contains("_$ssrSpread(p, true, false)"),
+        "got: {}",
+        code
+    );
 }
 
 #[test]
-fn test_ssr_imports() {
-    let code = transform_ssr(r#"<div>{count()}</div>"#);
-    assert!(code.contains("import"));
-    assert!(code.contains("ssr"));
-    assert!(code.contains("escape"));
+fn test_ssr_escaping_follows_the_markup_context() {
+    let code = transform_ssr(r#"<a title={t()}>{x()}</a>"#);
+    assert!(
+        code.contains("_$escape(t(), true), _$escape(x())"),
+        "got: {}",
+        code
+    );
+
+    // Raw text elements and innerHTML are written as is
+    let code = transform_ssr(r#"<script>{code()} {"a < b"}</script>"#);
+    assert!(code.contains("_$ssr(_tmpl$1, code())"), "got: {}", code);
+    assert!(code.contains(" a < b<\\/script>"), "got: {}", code);
+    let code = transform_ssr(r#"<div innerHTML={html()} />"#);
+    assert!(code.contains("_$ssr(_tmpl$1, html())"), "got: {}", code);
+    assert!(!code.contains("escape"), "got: {}", code);
+
+    // Nested JSX is markup already
+    let code = transform_ssr(r#"<div>{<b>{y()}</b>}</div>"#);
+    assert!(
+        code.contains("_$ssr(_tmpl$2, _$ssr(_tmpl$1, _$escape(y())))"),
+        "got: {}",
+        code
+    );
 }
 
-// ============================================================================
-// Regression Tests for Nested Dynamic Content
-// ============================================================================
+#[test]
+fn test_ssr_literals_are_inlined_without_escape() {
+    let code = transform_ssr(
+        r#"<input value={"a"} size={5} disabled={true} checked={false} aria-hidden={true} />"#,
+    );
+    assert!(
+        code.contains(r#""<input value=\"a\" size=\"5\" disabled aria-hidden=\"true\">""#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("escape"), "got: {}", code);
+
+    // Booleans render nothing as children
+    let code = transform_ssr(r#"<p>{1}{true}{"<b>"}{false}</p>"#);
+    assert!(code.contains(r#""<p>1&lt;b&gt;</p>""#), "got: {}", code);
+}
 
 #[test]
-fn test_dom_nested_dynamic_content() {
-    // {x()} inside nested <span> should produce insert() without marker (single dynamic child)
-    let code = transform_dom(r#"<div><span>{x()}</span></div>"#);
+fn test_ssr_dynamic_tags() {
+    // A tag written in place renders through ssrElement, with escaped children
+    let code = transform_ssr(r#"<Dynamic component="p" class="x" onClick={f}>{name()}</Dynamic>"#);
+    assert!(
+        code.contains(r#"_$ssrElement("p", { "class": "x" }, () => _$escape(name()), false)"#),
+        "got: {}",
+        code
+    );
+    // Static children are passed as a string literal
+    let code = transform_ssr(r#"<Dynamic component="div"><span class="x">a</span></Dynamic>"#);
+    assert!(
+        code.contains(r#", "<span class=\"x\">a</span>", false)"#),
+        "got: {}",
+        code
+    );
+    let code = transform_ssr(r#"<Dynamic component="input" {...rest} />"#);
+    assert!(
+        code.contains(r#"_$ssrElement("input", rest, undefined, false)"#),
+        "got: {}",
+        code
+    );
 
-    // Template should have span without marker (single dynamic child optimization)
-    assert!(code.contains("<span></span>"), "Template should have empty span (no marker for single dynamic child), got: {}", code);
+    // Anything else is left to the runtime, with all of its props
+    let code = transform_ssr(r#"<Dynamic component={tag()} title="x" />"#);
+    assert!(
+        code.contains("_$createComponent(Dynamic, {"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("return tag();"), "got: {}", code);
+    assert!(code.contains(r#"title: "x""#), "got: {}", code);
+}
 
-    // Should walk to span element
-    assert!(code.contains("firstChild"), "Should walk to span with firstChild, got: {}", code);
+#[test]
+fn test_ssr_noscript_content_is_markup_without_hydration_keys() {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        hydratable: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<div><noscript><img src="a.png" />{message()}</noscript></div>"#;
+    let code = transform(source, Some(options)).code.replace("\\\"", "\"");
+    assert!(
+        code.contains(r#"<noscript><img src="a.png">"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("escape(message())"), "got: {}", code);
+    assert_eq!(
+        code.matches("ssrHydrationKey()").count(),
+        1,
+        "got: {}",
+        code
+    );
+}
 
-    // Should insert into span without marker argument
-    assert!(code.contains("insert("), "Should have insert() call, got: {}", code);
-    assert!(code.contains("x()"), "Should reference x(), got: {}", code);
+#[test]
+fn test_ssr_hydration_key_only_on_roots() {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        hydratable: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<div><span>{a()}</span><Comp><p>{b()}</p></Comp></div>"#;
+    let code = transform(source, Some(options)).code;
+    // The `<div>` root and the `<p>` rendered by the component children
+    assert_eq!(
+        code.matches("ssrHydrationKey()").count(),
+        2,
+        "got: {}",
+        code
+    );
 }
 
 #[test]
-fn test_dom_two_siblings_with_events() {
-    // Bug: second button should use firstChild.nextSibling not root.nextSibling
-    let code = transform_dom(r#"<div><button onClick={() => 1}>A</button><button onClick={() => 2}>B</button></div>"#);
+fn test_ssr_hydration_markers_around_dynamic_children() {
+    let hydrated = |source: &str| {
+        let options = TransformOptions {
+            generate: GenerateMode::Ssr,
+            hydratable: true,
+            ..TransformOptions::solid_defaults()
+        };
+        transform(source, Some(options)).code
+    };
+    let code = hydrated(r#"<p>Hello {name()}!</p>"#);
+    assert!(code.contains(r#"">Hello <!--$-->","#), "got: {}", code);
+    assert!(code.contains(r#""<!--/-->!</p>""#), "got: {}", code);
 
-    // Should have proper sibling traversal
-    assert!(code.contains("firstChild"), "Should walk to first button, got: {}", code);
-    // Second button should chain from first: firstChild.nextSibling
-    assert!(code.contains("firstChild.nextSibling"),
-        "Should walk to second button via firstChild.nextSibling, got: {}", code);
+    // A sole child needs no markers
+    let code = hydrated(r#"<p>{name()}</p>"#);
+    assert!(!code.contains("<!--$-->"), "got: {}", code);
+
+    let code = transform_ssr(r#"<p>Hello {name()}!</p>"#);
+    assert!(!code.contains("<!--$-->"), "got: {}", code);
+}
+
+#[test]
+fn test_ssr_component_boundaries() {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        component_boundaries: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let result = transform(r#"<div><Header /><Footer /></div>"#, Some(options));
+    let code = normalize(&result.code);
+    assert!(code.contains("<!--c:Header:1-->"), "got: {}", code);
+    assert!(code.contains("<!--/c:Header:1-->"), "got: {}", code);
+    assert!(code.contains("<!--c:Footer:2-->"), "got: {}", code);
+    assert!(code.contains("<!--/c:Footer:2-->"), "got: {}", code);
+}
+
+#[test]
+fn test_ssr_component_boundaries_off_by_default() {
+    let code = transform_ssr(r#"<div><Header /></div>"#);
+    assert!(!code.contains("<!--c:"), "got: {}", code);
+}
+
+fn transform_ssr_minified(source: &str) -> String {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        minify_whitespace: true,
+        ..TransformOptions::solid_defaults()
+    };
+    normalize(&transform(source, Some(options)).code)
+}
+
+#[test]
+fn test_ssr_minify_whitespace_next_to_blocks() {
+    let source = r#"<div>Hello <p>world</p></div>"#;
+    assert!(transform_ssr(source).contains("<div>Hello <p>world</p></div>"));
+    let code = transform_ssr_minified(source);
+    assert!(
+        code.contains("<div>Hello<p>world</p></div>"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_ssr_minify_whitespace_keeps_inline_spacing() {
+    let code = transform_ssr_minified(r#"<p>Hello <b>world</b></p>"#);
+    assert!(code.contains("<p>Hello <b>world</b></p>"), "got: {}", code);
+}
+
+#[test]
+fn test_ssr_minify_whitespace_respects_preserving_elements() {
+    let code = transform_ssr_minified(r#"<pre>Hello <div>world</div></pre>"#);
+    assert!(
+        code.contains("<pre>Hello <div>world</div></pre>"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_ssr_minified(r#"<div class="whitespace-pre">Hello <p>world</p></div>"#);
+    assert!(code.contains("Hello <p>world</p>"), "got: {}", code);
+}
+
+#[test]
+fn test_minify_whitespace_matches_between_dom_and_ssr() {
+    let source = r#"<div>Hello <p>world</p> <pre>a <p>b</p></pre></div>"#;
+    let options = |generate| TransformOptions {
+        generate,
+        hydratable: true,
+        minify_whitespace: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let dom = transform(source, Some(options(GenerateMode::Dom))).code;
+    assert!(
+        dom.contains("<div>Hello<p>world</p><pre>a <p>b</p></pre></div>"),
+        "got: {}",
+        dom
+    );
+    let ssr = transform(source, Some(options(GenerateMode::Ssr))).code;
+    assert!(ssr.contains("Hello<p"), "got: {}", ssr);
+    assert!(ssr.contains("</p><pre"), "got: {}", ssr);
+    assert!(ssr.contains("a <p"), "got: {}", ssr);
+}
+
+#[test]
+fn test_user_void_elements() {
+    let options = TransformOptions {
+        void_elements: vec!["x-icon", "!img"],
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(r#"<div><x-icon /><img /></div>"#, Some(options)).code;
+    assert!(
+        code.contains("<div><x-icon><img></img></div>"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_user_boolean_attributes() {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        boolean_attributes: vec!["collapsed"],
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(r#"<x-panel collapsed={c()} />"#, Some(options)).code;
+    assert!(
+        code.contains(r#"ssrAttribute("collapsed", c(), true)"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_ssr_value_is_not_boolean() {
+    let code = transform_ssr(r#"<input value={v()} />"#);
+    assert!(!code.contains("ssrAttribute"), "got: {}", code);
+    assert!(code.contains("escape(v(), true)"), "got: {}", code);
+}
+
+fn transform_minified_template(source: &str, nested: bool, quotes: bool) -> String {
+    let options = TransformOptions {
+        omit_last_closing_tag: true,
+        omit_nested_closing_tags: nested,
+        omit_quotes: quotes,
+        ..TransformOptions::solid_defaults()
+    };
+    transform(source, Some(options)).code
+}
+
+#[test]
+fn test_omit_last_closing_tag() {
+    let code = transform_minified_template(r#"<div><p>a</p><span>b</span></div>"#, false, false);
+    assert!(code.contains("`<div><p>a</p><span>b`"), "got: {}", code);
+
+    // Content inserted after an element keeps it closed
+    let code = transform_minified_template(r#"<div><p>a</p>{x()}<Comp /></div>"#, false, false);
+    assert!(code.contains("`<div><p>a</p><!>`"), "got: {}", code);
+
+    let code = transform(r#"<div><p>a</p></div>"#, None).code;
+    assert!(code.contains("`<div><p>a</p></div>`"), "got: {}", code);
+}
+
+#[test]
+fn test_omit_nested_closing_tags() {
+    let source = r#"<div><p><b>x</b><span>y</span></p>z</div>"#;
+    let code = transform_minified_template(source, false, false);
+    assert!(
+        code.contains("`<div><p><b>x</b><span>y</span></p>z`"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_minified_template(source, true, false);
+    assert!(
+        code.contains("`<div><p><b>x</b><span>y</p>z`"),
+        "got: {}",
+        code
+    );
+
+    // `<a>` is always closed once an ancestor is
+    let code = transform_minified_template(r#"<div><p><a>y</a></p>z</div>"#, true, false);
+    assert!(code.contains("`<div><p><a>y</a></p>z`"), "got: {}", code);
+}
+
+#[test]
+fn test_omit_quotes() {
+    let source = r#"<div class="btn" title="a b" id="" data-x="1"></div>"#;
+    let code = transform_minified_template(source, false, true);
+    assert!(
+        code.contains(r#"`<div class=btn title="a b" id="" data-x=1>`"#),
+        "got: {}",
+        code
+    );
+}
+
+// ============================================================================
+// Edge Cases
+// ============================================================================
+
+#[test]
+fn test_empty_fragment() {
+    let code = transform_dom(r#"<></>"#);
+    // Empty fragment should produce minimal output
+    assert!(!code.is_empty());
+}
+
+#[test]
+fn test_fragment_with_children() {
+    let code = transform_dom(r#"<><div>a</div><div>b</div></>"#);
+    assert!(code.contains("template"));
+}
+
+#[test]
+fn test_fragment_children_become_an_array() {
+    let code = transform_dom(r#"const a = <><A /><div /><p>{y()}</p>{x()}text</>;"#);
+    assert!(
+        code.contains("const a = [\n_$createComponent(A"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("_$memo(() => x()),\n\"text\"\n]"),
+        "got: {}",
+        code
+    );
+    assert_eq!(code.matches("_$template(").count(), 2, "got: {}", code);
+
+    // A single child is not wrapped
+    let code = transform_dom(r#"const b = <><div /></>;"#);
+    assert!(
+        code.contains("const b = _tmpl$1.cloneNode(true)"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_comment_children_emit_nothing() {
+    let code = transform_dom(r#"<div>{/* note */}<span>{x()}</span>{/* end */}</div>"#);
+    assert!(code.contains("<div><span>"), "got: {}", code);
+    assert_eq!(code.matches("_$insert(").count(), 1, "got: {}", code);
+    assert!(!code.contains("note"), "got: {}", code);
+
+    // The comment does not make the single child a memoized expression
+    let code = transform_dom(r#"const c = <>{/* note */}<A /></>;"#);
+    assert!(
+        code.contains("const c = /* @__PURE__ */ _$createComponent(A"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_dom(r#"<Show when={ok()}>{/* note */}{(v) => <p>{v}</p>}</Show>"#);
+    assert!(code.contains("children: (v) =>"), "got: {}", code);
+
+    let code = transform_ssr(r#"<div>{/* note */}<span>{x()}</span></div>"#);
+    assert!(code.contains("<div><span>"), "got: {}", code);
+    assert!(!code.contains("note"), "got: {}", code);
+}
+
+#[test]
+fn test_svg_element() {
+    let code = transform_dom(r#"<svg><circle cx="50" cy="50" r="40" /></svg>"#);
+    assert!(code.contains("svg"));
+    assert!(code.contains("circle"));
+}
+
+#[test]
+fn test_svg_namespace_follows_parent() {
+    let code = transform_dom(
+        r#"<svg><foreignObject><div class={a()} /></foreignObject><glyphRun class={b()} /></svg>"#,
+    );
+    // HTML again inside <foreignObject>
+    assert!(code.contains(".className = a()"), "got: {}", code);
+    // Unknown tags inside <svg> are still SVG
+    assert!(
+        code.contains(r#".setAttribute("class", b())"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_templates_are_hoisted_once_per_kind() {
+    let code = transform_dom(
+        r#"
+        const a = <div>same</div>;
+        const b = <div>same</div>;
+        const c = <svg><circle r="1" /></svg>;
+        const d = <my-element>same</my-element>;
+    "#,
+    );
+    assert_eq!(code.matches("_$template(").count(), 3, "got: {}", code);
+    assert_eq!(
+        code.matches("/* @__PURE__ */ _$template(").count(),
+        3,
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("const b = _tmpl$1.cloneNode(true)"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("`, false, true)"), "got: {}", code);
+    assert!(code.contains("`, true, false)"), "got: {}", code);
+}
+
+#[test]
+fn test_custom_element() {
+    let code = transform_dom(r#"<my-element attr="value">content</my-element>"#);
+    assert!(code.contains("my-element"));
+}
+
+#[test]
+fn test_custom_element_bindings() {
+    let code = transform_dom(r#"<my-chart data-id={id()} chart-title={title()} />"#);
+    assert!(
+        code.contains("_el$1._$owner = _$getOwner()"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("_el$1.chartTitle = title()"), "got: {}", code);
+    assert!(
+        code.contains(r#"_el$1.setAttribute("data-id", id())"#),
+        "got: {}",
+        code
+    );
+    // Imported into the document so the element upgrades
+    assert!(code.contains("`, true, false)"), "got: {}", code);
+
+    let code = transform_dom(r#"<button is="fancy-button">go</button>"#);
+    assert!(code.contains("`, true, false)"), "got: {}", code);
+
+    let options = TransformOptions {
+        context_to_custom_elements: false,
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(r#"<my-chart />"#, Some(options)).code;
+    assert!(!code.contains("getOwner"), "got: {}", code);
+}
+
+#[test]
+fn test_namespaced_attribute() {
+    let code = transform_dom(
+        r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#id" /></svg>"##,
+    );
+    assert!(code.contains("xlink:href"));
+}
+
+#[test]
+fn test_dynamic_namespaced_attribute() {
+    let code = transform_dom(r#"<svg xml:lang={lang()}><use xlink:href={href()} /></svg>"#);
+    assert!(
+        code.contains(
+            r#"_$setAttributeNS(_el$2, "http://www.w3.org/1999/xlink", "xlink:href", href())"#
+        ),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#""http://www.w3.org/XML/1998/namespace", "xml:lang", lang())"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("setAttributeNS as _$setAttributeNS"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_whitespace_handling() {
+    let code = transform_dom(
+        r#"<div>
+        hello
+        world
+    </div>"#,
+    );
+    // Should handle whitespace appropriately
+    assert!(code.contains("hello"));
+}
+
+#[test]
+fn test_whitespace_collapses_per_jsx_rules() {
+    // Indentation and whitespace-only lines go, words are joined by one space
+    let code = transform_dom("<p>\n    one\n    two   three\n</p>");
+    assert!(code.contains("`<p>one two three</p>`"), "got: {}", code);
+    let code = transform_dom("<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>");
+    assert!(
+        code.contains("`<ul><li>a</li><li>b</li></ul>`"),
+        "got: {}",
+        code
+    );
+
+    // Spaces next to an expression on the same line are significant
+    let code = transform_dom("<div>\n  Hello {name}\n</div>");
+    assert!(code.contains("`<div>Hello "), "got: {}", code);
+    // ...but not trailing spaces before a newline
+    let code = transform_dom("<div>\n  Hello \n</div>");
+    assert!(code.contains("`<div>Hello</div>`"), "got: {}", code);
+    let code = transform_dom("<p>{a} {b}</p>");
+    assert!(code.contains("`<p><!> </p>`"), "got: {}", code);
+}
+
+#[test]
+fn test_special_characters() {
+    let code = transform_dom(r#"<div>&amp; &lt; &gt;</div>"#);
+    // HTML entities should be preserved or properly escaped
+    assert!(!code.is_empty());
+}
+
+#[test]
+fn test_entities_are_decoded_once() {
+    let code = transform_dom(r#"<div title="a &amp; b">&amp; &lt; &#x27;x&#39;</div>"#);
+    assert!(code.contains("&amp; &lt; 'x'"), "got: {}", code);
+    assert!(code.contains("a &amp; b"), "got: {}", code);
+    assert!(!code.contains("&amp;amp;"), "got: {}", code);
+
+    let code = transform_ssr(r#"<div title="a &amp; b">&amp; &lt; &#x27;x&#39;</div>"#);
+    assert!(code.contains("&amp; &lt;"), "got: {}", code);
+    assert!(!code.contains("&amp;amp;"), "got: {}", code);
+}
+
+#[test]
+fn test_entities_in_component_props_are_decoded() {
+    let code = transform_dom(r#"<Comp title="&quot;hi&quot; &amp; bye" />"#);
+    assert!(code.contains("\" & bye"), "got: {}", code);
+    assert!(!code.contains("&quot;"), "got: {}", code);
+}
+
+#[test]
+fn test_entities_in_text_children_are_decoded() {
+    // Text outside a template becomes a text node, not HTML
+    for code in [
+        transform_dom(r#"<Comp>&quot;hi&quot;</Comp>"#),
+        transform_dom(r#"const el = <>say &quot;hi&quot;</>;"#),
+        transform_ssr(r#"<Comp>&quot;hi&quot;</Comp>"#),
+    ] {
+        assert!(
+            code.contains(r#"\"hi\""#)
+                || code.contains(r#"'"hi"'"#)
+                || code.contains(r#"'say "hi"'"#),
+            "got: {}",
+            code
+        );
+        assert!(!code.contains("&quot;"), "got: {}", code);
+    }
+
+    for code in [
+        transform_dom(r#"<Comp>a &amp; b</Comp>"#),
+        transform_dom(r#"const el = <>a &amp; b</>;"#),
+        transform_dom(r#"const el = <>a &amp; b<br /></>;"#),
+        transform_ssr(r#"<Comp>a &amp; b</Comp>"#),
+    ] {
+        assert!(code.contains(r#""a & b""#), "got: {}", code);
+        assert!(!code.contains("&amp;"), "got: {}", code);
+    }
+}
+
+// ============================================================================
+// Define
+// ============================================================================
+
+fn transform_defined(source: &str, generate: GenerateMode) -> String {
+    let options = TransformOptions {
+        generate,
+        define: vec![("__DEV__", "false"), ("import.meta.env.SSR", "true")],
+        ..TransformOptions::solid_defaults()
+    };
+    normalize(&transform(source, Some(options)).code)
+}
+
+#[test]
+fn test_define_folds_dev_only_children() {
+    let code = transform_defined(r#"<div>{__DEV__ && <Debug />}</div>"#, GenerateMode::Dom);
+    assert!(!code.contains("Debug"), "got: {}", code);
+    assert!(!code.contains("__DEV__"), "got: {}", code);
+}
+
+#[test]
+fn test_define_replaces_member_chains() {
+    let source = r#"<div title={import.meta.env.SSR ? "server" : "client"} />"#;
+    let code = transform_defined(source, GenerateMode::Ssr);
+    assert!(code.contains("server"), "got: {}", code);
+    assert!(!code.contains("client"), "got: {}", code);
+}
+
+#[test]
+fn test_define_skips_shadowed_bindings() {
+    let source = "const __DEV__ = true;\nconst el = <div>{__DEV__}</div>;";
+    let code = transform_defined(source, GenerateMode::Dom);
+    assert!(code.contains("() => __DEV__"), "got: {}", code);
+}
+
+// ============================================================================
+// Pragmas
+// ============================================================================
+
+#[test]
+fn test_foreign_jsx_import_source_is_skipped() {
+    let code = transform_dom("/** @jsxImportSource react */\nconst el = <div class={a()} />;");
+    assert!(code.contains("<div class={a()} />"), "got: {}", code);
+    assert!(!code.contains("template"), "got: {}", code);
+}
+
+#[test]
+fn test_solid_jsx_import_source_is_transformed() {
+    let code = transform_dom("/** @jsxImportSource solid-js */\nconst el = <div>hello</div>;");
+    assert!(code.contains("template"), "got: {}", code);
+}
+
+#[test]
+fn test_classic_jsx_runtime_is_skipped() {
+    let code = transform_dom("/* @jsxRuntime classic */\nconst el = <div>hello</div>;");
+    assert!(code.contains("<div>hello</div>;"), "got: {}", code);
+    assert!(!code.contains("template"), "got: {}", code);
+}
+
+#[test]
+fn test_generate_pragma_overrides_mode() {
+    let code = transform_dom("/* @solid-generate ssr */\nconst el = <div>{count()}</div>;");
+    assert!(code.contains("_$ssr(_tmpl$"), "got: {}", code);
+    assert!(!code.contains("template"), "got: {}", code);
+
+    let code = transform_ssr("// @solid-generate dom\nconst el = <div>{count()}</div>;");
+    assert!(code.contains("template"), "got: {}", code);
+    assert!(code.contains("insert"), "got: {}", code);
+}
+
+#[test]
+fn test_jsx_option_forces_jsx_parsing() {
+    let options = TransformOptions {
+        filename: "legacy.ts",
+        jsx: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform("const el = <div class=\"a\">hi</div>;", Some(options)).code;
+    assert!(
+        code.contains("template(`<div class=\"a\">hi</div>`)"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_jsx_ignore_leaves_subtree_untransformed() {
+    let code = transform_dom(
+        "const a = /* @jsx-ignore */ <div class={x()}><span>{y()}</span></div>;\nconst b = <p>{z()}</p>;",
+    );
+    assert!(code.contains("<div class={x()}>"), "got: {}", code);
+    assert!(code.contains("<span>{y()}</span>"), "got: {}", code);
+    assert!(code.contains("template(`<p>"), "got: {}", code);
+}
+
+#[test]
+fn test_transformed_output_is_not_transformed_again() {
+    let once = transform("const el = <div>{count()}</div>;", None).code;
+    let twice = transform(&once, None);
+    assert_eq!(twice.code, once);
+    assert_eq!(twice.diagnostics.len(), 1);
+    assert!(twice.diagnostics[0].message.contains("already transformed"));
+
+    let ssr = || TransformOptions {
+        generate: GenerateMode::Ssr,
+        ..TransformOptions::solid_defaults()
+    };
+    let once = transform("const el = <div>{count()}</div>;", Some(ssr())).code;
+    assert_eq!(transform(&once, Some(ssr())).code, once);
+}
+
+// ============================================================================
+// Import Generation
+// ============================================================================
+
+#[test]
+fn test_dom_imports_template() {
+    let code = transform_dom(r#"<div>hello</div>"#);
+    assert!(code.contains("import"));
+    assert!(code.contains("template"));
+    assert!(code.contains("solid-js/web"));
+}
+
+#[test]
+fn test_dom_imports_insert() {
+    let code = transform_dom(r#"<div>{dynamic()}</div>"#);
+    assert!(code.contains("insert"));
+}
+
+#[test]
+fn test_dom_imports_effect() {
+    let code = transform_dom(r#"<div class={dynamic()}>content</div>"#);
+    assert!(code.contains("effect"));
+}
+
+#[test]
+fn test_dom_imports_delegate_events() {
+    let code = transform_dom(r#"<button onClick={handler}>click</button>"#);
+    assert!(code.contains("delegateEvents"));
+}
+
+#[test]
+fn test_ssr_imports() {
+    let code = transform_ssr(r#"<div>{count()}</div>"#);
+    assert!(code.contains("import"));
+    assert!(code.contains("ssr"));
+    assert!(code.contains("escape"));
+}
+
+#[test]
+fn test_dom_imports_configured_directives() {
+    let options = || TransformOptions {
+        directive_imports: vec![("tooltip", "my-directives"), ("unused", "my-directives")],
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(r#"<div use:tooltip={msg} use:other />"#, Some(options())).code;
+    assert!(
+        code.contains(r#"import { tooltip } from "my-directives";"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("unused"), "got: {}", code);
+
+    let source = "import { tooltip } from \"./tooltip\";\nconst el = <div use:tooltip={msg} />;";
+    let code = transform(source, Some(options())).code;
+    assert!(!code.contains("my-directives"), "got: {}", code);
+}
+
+#[test]
+fn test_helpers_merge_into_existing_runtime_import() {
+    let source = r#"import { render } from "solid-js/web"; render(() => <div>{x()}</div>, root);"#;
+    let code = transform_dom(source);
+    assert_eq!(
+        code.matches(r#"from "solid-js/web""#).count(),
+        1,
+        "got: {}",
+        code
+    );
+    let import = code.lines().find(|l| l.contains("solid-js/web")).unwrap();
+    assert!(import.contains("render,"), "got: {}", code);
+    assert!(import.contains("insert as _$insert"), "got: {}", code);
+
+    // Helpers the file imports are called by its names
+    let source = r#"import { template, insert } from "solid-js/web"; const a = <div>{x()}</div>;"#;
+    let code = transform_dom(source);
+    assert_eq!(
+        code.matches(r#"from "solid-js/web""#).count(),
+        1,
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("_$"), "got: {}", code);
+    assert!(code.contains("insert(_el$1, "), "got: {}", code);
+
+    // ...unless a local binding could shadow the name
+    let source =
+        r#"import { insert } from "solid-js/web"; function f(insert) { return <div>{x()}</div>; }"#;
+    let code = transform_dom(source);
+    assert!(code.contains("insert as _$insert"), "got: {}", code);
+    assert!(code.contains("_$insert(_el$1, "), "got: {}", code);
+
+    let source = r#"import { escape } from "solid-js/web"; const a = <div>{x()}</div>;"#;
+    let code = transform_ssr(source);
+    assert_eq!(
+        code.matches(r#"from "solid-js/web""#).count(),
+        1,
+        "got: {}",
+        code
+    );
+    assert!(code.contains("escape(x())"), "got: {}", code);
+    assert!(!code.contains("_$escape"), "got: {}", code);
+}
+
+#[test]
+fn test_dom_directive_resolved_in_scope() {
+    let source = r#"
+        function App() {
+            const tooltip = makeTooltip();
+            return <div use:tooltip={msg} />;
+        }
+    "#;
+    let result = transform(source, None);
+    assert!(
+        result.code.contains("_$use(tooltip, _el$1, () => msg)"),
+        "got: {}",
+        result.code
+    );
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+
+    // Declared in another function, so not in scope here
+    let source = r#"
+        function setup() { const tooltip = makeTooltip(); }
+        const el = <div use:tooltip={msg} />;
+    "#;
+    let result = transform(source, None);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(result.diagnostics[0].message.contains("`use:tooltip`"));
+}
+
+// ============================================================================
+// Hydratable DOM
+// ============================================================================
+
+fn transform_hydratable(source: &str) -> String {
+    let options = TransformOptions {
+        hydratable: true,
+        ..TransformOptions::solid_defaults()
+    };
+    normalize(&transform(source, Some(options)).code)
+}
+
+#[test]
+fn test_hydratable_dom_adopts_server_nodes() {
+    let code = transform_hydratable(r#"const a = <p>hi</p>;"#);
+    assert!(
+        code.contains("const a = _$getNextElement(_tmpl$1)"),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("cloneNode"), "got: {}", code);
+    assert!(
+        code.contains("getNextElement as _$getNextElement"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_hydratable_dom_brackets_inserts_with_markers() {
+    let code = transform_hydratable(r#"<div>{a()}<span />{b()}</div>"#);
+    assert!(
+        code.contains("<div><!$><!/><span></span><!$><!/></div>"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("[_el$3, _co$4] = _$getNextMarker(_el$2.nextSibling)"),
+        "got: {}",
+        code
+    );
+    // Walks past server-rendered content start from its end marker
+    assert!(
+        code.contains("_el$5 = _el$3.nextSibling.nextSibling"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains(", _el$3, _co$4)"), "got: {}", code);
+    assert!(code.contains(", _el$6, _co$7)"), "got: {}", code);
+    assert!(!code.contains("<!>"), "got: {}", code);
+}
+
+#[test]
+fn test_hydratable_dom_replays_delegated_events() {
+    let code = transform_hydratable(r#"<button onClick={go}>x</button>"#);
+    assert!(code.contains("_$runHydrationEvents();"), "got: {}", code);
+
+    let code = transform_hydratable(r#"<div on:scroll={go} />"#);
+    assert!(!code.contains("runHydrationEvents"), "got: {}", code);
+
+    let code = transform_dom(r#"<button onClick={go}>x</button>"#);
+    assert!(!code.contains("runHydrationEvents"), "got: {}", code);
+}
+
+// ============================================================================
+// Runtime Helpers
+// ============================================================================
+
+fn helper_names(options: &TransformOptions) -> Vec<&'static str> {
+    required_helpers(options).iter().map(|h| h.name).collect()
+}
+
+#[test]
+fn test_runtime_helpers_follow_options() {
+    let names = helper_names(&TransformOptions::solid_defaults());
+    assert!(names.contains(&"template") && names.contains(&"delegateEvents"));
+    assert!(!names.contains(&"ssr"));
+
+    let options = TransformOptions {
+        delegate_events: false,
+        ..TransformOptions::solid_defaults()
+    };
+    assert!(!helper_names(&options).contains(&"delegateEvents"));
+
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        ..TransformOptions::solid_defaults()
+    };
+    let names = helper_names(&options);
+    assert!(names.contains(&"ssr") && names.contains(&"escape"));
+    assert!(!names.contains(&"ssrHydrationKey"));
+    assert!(!names.contains(&"getNextElement"));
+
+    let options = TransformOptions {
+        hydratable: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let names = helper_names(&options);
+    assert!(names.contains(&"getNextElement") && names.contains(&"runHydrationEvents"));
+}
+
+#[test]
+fn test_runtime_helpers_minimum_version() {
+    let helpers = required_helpers(&TransformOptions::solid_defaults());
+    assert_eq!(minimum_version(&helpers), "1.0.0");
+
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        ..TransformOptions::solid_defaults()
+    };
+    assert_eq!(minimum_version(&required_helpers(&options)), "1.4.0");
+}
+
+#[test]
+fn test_runtime_helpers_cover_emitted_imports() {
+    let names = helper_names(&TransformOptions::solid_defaults());
+    let code =
+        transform_dom(r#"<div class={a()} onClick={b} style={c()}>{d()}<Comp {...e} /></div>"#);
+    let import = code.lines().find(|l| l.starts_with("import {")).unwrap();
+    let imported = import["import {".len()..import.find('}').unwrap()].split(',');
+    for specifier in imported {
+        let name = specifier.trim().split(" as ").next().unwrap();
+        assert!(names.contains(&name), "{} is not reported", name);
+    }
+}
+
+#[test]
+fn test_missing_runtime_exports_are_reported() {
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        runtime_exports: Some(vec!["ssr", "escape"]),
+        ..TransformOptions::solid_defaults()
+    };
+    let result = transform(r#"<div classList={{ a: b() }} />"#, Some(options));
+    let messages: Vec<_> = result
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, ["`ssrClassList` is not exported by solid-js/web"]);
+
+    let options = TransformOptions {
+        runtime_exports: Some(vec!["template", "insert"]),
+        ..TransformOptions::solid_defaults()
+    };
+    let result = transform(r#"<div>{a()}</div>"#, Some(options));
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+}
+
+// ============================================================================
+// Output Target
+// ============================================================================
+
+fn transform_es5(source: &str, generate: GenerateMode) -> solid_jsx_oxc::TransformOutput {
+    let options = TransformOptions {
+        generate,
+        target: OutputTarget::Es5,
+        ..TransformOptions::solid_defaults()
+    };
+    transform(source, Some(options))
+}
+
+#[test]
+fn test_es5_target_dom() {
+    let result = transform_es5(
+        r#"const el = <div class={a()}>{b()}<Comp value={c()} /></div>;"#,
+        GenerateMode::Dom,
+    );
+    let code = result.code;
+    assert!(!code.contains("=>"), "got: {}", code);
+    assert!(!code.contains('`'), "got: {}", code);
+    assert!(!code.contains("const "), "got: {}", code);
+    assert!(code.contains("var _tmpl$"), "got: {}", code);
+    assert!(code.contains("get value()"), "got: {}", code);
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+
+    // Component refs are passed as a method
+    let result = transform_es5(r#"const el = <Comp ref={r} />;"#, GenerateMode::Dom);
+    let code = result.code;
+    assert!(code.contains("ref: function"), "got: {}", code);
+    assert!(!code.contains("ref(r$)"), "got: {}", code);
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+
+    // Block-scoped declarations in nested blocks are kept and reported
+    let result = transform_es5(
+        r#"function f() { if (x) { const y = 1; return <div>{y}</div>; } }"#,
+        GenerateMode::Dom,
+    );
+    assert!(result.code.contains("const y = 1"), "got: {}", result.code);
+    assert_eq!(result.diagnostics.len(), 1, "{:?}", result.diagnostics);
+    assert!(result.diagnostics[0].message.contains("`const`"));
+}
+
+#[test]
+fn test_es5_target_ssr() {
+    let source = r#"const el = <div class={a()}>{b()}</div>;"#;
+    let code = transform_es5(source, GenerateMode::Ssr).code;
+    assert!(code.contains("var _tmpl$"), "got: {}", code);
+    assert!(code.contains("ssr(_tmpl$"), "got: {}", code);
+    assert!(!code.contains('`'), "got: {}", code);
+}
+
+#[test]
+fn test_es5_target_keeps_arrows_using_this() {
+    let result = transform_es5(
+        "function C() { return <div onClick={() => this.go()} />; }",
+        GenerateMode::Dom,
+    );
+    assert!(result.code.contains("=> this.go()"), "got: {}", result.code);
+    assert_eq!(result.diagnostics.len(), 1);
+}
+
+#[test]
+fn test_prettier_formatting() {
+    let options = TransformOptions {
+        prettier: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"const s = 'say "hi"'; const f = function() { return <div>{s}</div>; };"#;
+    let code = transform(source, Some(options)).code;
+    assert!(code.contains(r#"'say "hi"'"#), "got: {}", code);
+    assert!(code.contains("function () {"), "got: {}", code);
+    assert!(code.contains(r#"from "solid-js/web""#), "got: {}", code);
+    assert!(code.ends_with('\n'));
+}
+
+// ============================================================================
+// Regression Tests for Nested Dynamic Content
+// ============================================================================
+
+#[test]
+fn test_dom_nested_dynamic_content() {
+    // {x()} inside nested <span> should produce insert() without marker (single dynamic child)
+    let code = transform_dom(r#"<div><span>{x()}</span></div>"#);
+
+    // Template should have span without marker (single dynamic child optimization)
+    assert!(code.contains("<span></span>"), "Template should have empty span (no marker for single dynamic child), got: {}", code);
+
+    // Should walk to span element
+    assert!(code.contains("firstChild"), "Should walk to span with firstChild, got: {}", code);
+
+    // Should insert into span without marker argument
+    assert!(code.contains("insert("), "Should have insert() call, got: {}", code);
+    assert!(code.contains("x()"), "Should reference x(), got: {}", code);
+}
+
+#[test]
+fn test_dom_two_siblings_with_events() {
+    // Bug: second button should use firstChild.nextSibling not root.nextSibling
+    let code = transform_dom(
+        r#"<div><button onClick={() => 1}>A</button><button onClick={() => 2}>B</button></div>"#,
+    );
+
+    // Should have proper sibling traversal
+    assert!(
+        code.contains("firstChild"),
+        "Should walk to first button, got: {}",
+        code
+    );
+    // Second button should chain from first: firstChild.nextSibling
+    assert!(code.contains("_el$2 = _el$1.firstChild"), "got: {}", code);
+    assert!(
+        code.contains("_el$3 = _el$2.nextSibling"),
+        "Should walk to second button from the first, got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_walks_are_declared_with_the_clone() {
+    let code =
+        transform_dom(r#"<div><p><b onClick={a} /></p><i onClick={b} /><u onClick={c} /></div>"#);
+    assert!(
+        code.contains("const _el$1 = _tmpl$1.cloneNode(true),"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("_el$2 = _el$1.firstChild.firstChild,"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("_el$3 = _el$1.firstChild.nextSibling,"),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("_el$4 = _el$3.nextSibling;"), "got: {}", code);
+}
+
+// ============================================================================
+// IR
+// ============================================================================
+
+#[test]
+fn test_ir_facade_exposes_ssr_result() {
+    let mut result = ir::ssr::SSRResult::new();
+    result.push_static("<div>");
+    result.push_dynamic("count()".to_string(), ir::ssr::EscapeContext::Text);
+    result.push_static("</div>");
+    let mut context = ir::ssr::SSRContext::default();
+    assert_eq!(
+        result.to_ssr_call(&mut context),
+        "_$ssr(_tmpl$1, _$escape(count()))"
+    );
+    assert_eq!(
+        context.module.hoisted,
+        vec![("_tmpl$1".to_string(), r#"["<div>","</div>"]"#.to_string())]
+    );
+}
+
+#[test]
+fn test_emit_ir_dumps_each_root() {
+    let source = r#"<div class={cls()}>{count()}</div>"#;
+    assert!(transform(source, None).ir.is_none());
+
+    let options = TransformOptions {
+        emit_ir: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let ir = transform(source, Some(options)).ir.unwrap().json;
+    assert!(
+        ir.starts_with(r#"{"backend":"dom","roots":[{"#),
+        "got: {}",
+        ir
+    );
+    assert!(
+        ir.contains(r#""key":"class","value":"cls()""#),
+        "got: {}",
+        ir
+    );
+    assert!(ir.contains(r#""insert""#), "got: {}", ir);
+
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        emit_ir: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let ir = transform(source, Some(options)).ir.unwrap().json;
+    assert!(ir.starts_with(r#"{"backend":"ssr""#), "got: {}", ir);
+    assert!(ir.contains(r#""expr":"count()""#), "got: {}", ir);
+}
+
+#[test]
+fn test_static_marker_reads_once() {
+    let code = transform_dom(r#"<div title={/*@once*/ title()}>{/*@once*/ count()}</div>"#);
+    assert!(code.contains("_$insert(_el$1, count())"), "got: {}", code);
+    assert!(code.contains("title()"), "got: {}", code);
+    assert!(!code.contains("effect"), "got: {}", code);
+    assert!(!code.contains("() => count()"), "got: {}", code);
+
+    let code = transform_dom(r#"<Comp value={/* @once */ props.value} />"#);
+    assert!(code.contains("value: props.value"), "got: {}", code);
+    assert!(!code.contains("get value()"), "got: {}", code);
+
+    // The marker is configurable
+    let options = TransformOptions {
+        static_marker: "@static",
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<Comp a={/*@static*/ a()} b={/*@once*/ b()} />"#;
+    let code = transform(source, Some(options)).code;
+    assert!(code.contains("a: a()"), "got: {}", code);
+    assert!(code.contains("get b()"), "got: {}", code);
+}
+
+#[test]
+fn test_solid_2_runtime() {
+    let options = || TransformOptions {
+        runtime: RuntimeVersion::Solid2,
+        ..TransformOptions::solid_defaults()
+    };
+
+    let code = transform(r#"<div class={cls()} prop:value={v()} />"#, Some(options())).code;
+    assert!(code.contains("effect(() => cls(), "), "got: {}", code);
+    assert!(code.contains("=> _el$1.className = _v$)"), "got: {}", code);
+    assert!(code.contains("effect(() => v(), "), "got: {}", code);
+    assert!(code.contains("=> _el$1.value = _v$)"), "got: {}", code);
+
+    let code = transform(r#"<Comp {...rest} a="1" />"#, Some(options())).code;
+    assert!(code.contains("merge(rest, { a: \"1\" })"), "got: {}", code);
+    assert!(!code.contains("mergeProps"), "got: {}", code);
+
+    let helpers = required_helpers(&options());
+    assert!(helpers.iter().any(|helper| helper.name == "merge"));
+    assert!(!helpers.iter().any(|helper| helper.name == "mergeProps"));
+}
+
+#[test]
+fn test_babel_compat_naming() {
+    let source = r#"
+        const a = <div class={cls()}><span>{x()}</span></div>;
+        const b = <p>static</p>;
+    "#;
+    let options = TransformOptions {
+        compat: Compat::Babel,
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(source, Some(options)).code;
+
+    assert!(
+        code.contains(r#"import { template as _$template } from "solid-js/web";"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"import { insert as _$insert } from "solid-js/web";"#),
+        "got: {}",
+        code
+    );
+    assert!(code.contains("var _tmpl$ = "), "got: {}", code);
+    assert!(code.contains("var _tmpl$2 = "), "got: {}", code);
+    assert!(code.contains("__PURE__"), "got: {}", code);
+    assert!(code.contains("var _el$ = _tmpl$()"), "got: {}", code);
+    assert!(code.contains("_$insert(_el$2, "), "got: {}", code);
+    assert!(code.contains("const b = _tmpl$2()"), "got: {}", code);
+    assert!(!code.contains("_el$1"), "got: {}", code);
+    assert!(
+        code.find("var _tmpl$ =").unwrap() < code.find("var _tmpl$2 =").unwrap(),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_check_reports_without_codegen() {
+    let diagnostics = check(r#"<p innerText={text()}>fallback</p>"#, None);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("`innerText`"));
+
+    assert!(check(r#"<div class={cls()}>ok</div>"#, None).is_empty());
+
+    // Nesting is validated even when the options leave it off
+    let diagnostics = check(r#"<p><div /></p>"#, None);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0]
+        .message
+        .contains("<div> cannot be a child of <p>"));
+
+    let diagnostics = check(r#"<div>unclosed"#, None);
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.severity == Severity::Error));
+}
+
+#[test]
+fn test_ssr_static_html() {
+    let source = r#"
+        export function About() {
+            return <main><h1>About &amp; us</h1></main>;
+        }
+        export const Card = () => <div>{title()}</div>;
+    "#;
+    let options = TransformOptions {
+        generate: GenerateMode::Ssr,
+        hydratable: true,
+        static_html: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let code = transform(source, Some(options)).code;
+
+    assert!(
+        code.contains(r#"return "<main><h1>About &amp; us</h1></main>";"#),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains(r#"export const AboutHtml = "<main><h1>About &amp; us</h1></main>";"#),
+        "got: {}",
+        code
+    );
+    // Dynamic roots keep the `ssr` tag and hydration keys
+    assert!(code.contains("ssrHydrationKey()"), "got: {}", code);
+    assert!(!code.contains("CardHtml"), "got: {}", code);
+}
+
+#[test]
+fn test_explore_returns_every_stage() {
+    let source = r#"<Show when={ok()}><p title="x">{label()}</p></Show>"#;
+    let result = explore(source, None);
+
+    assert!(
+        result
+            .jsx
+            .starts_with(r#"[{"kind":"component","tag":"Show","start":0,"#),
+        "got: {}",
+        result.jsx
+    );
+    assert!(
+        result.jsx.contains(r#"{"name":"title","value":"string"}"#),
+        "got: {}",
+        result.jsx
+    );
+    assert!(
+        result.jsx.contains(r#""kind":"expression""#),
+        "got: {}",
+        result.jsx
+    );
+    assert!(
+        result.ir.starts_with(r#"{"backend":"dom""#),
+        "got: {}",
+        result.ir
+    );
+    assert_eq!(result.templates.len(), 1);
+    assert!(
+        result.templates[0].starts_with(r#"<p title="x">"#),
+        "got: {:?}",
+        result.templates
+    );
+    assert!(result.code.contains("createComponent(Show"));
+}
+
+#[test]
+fn test_analyze_counts_without_codegen() {
+    let source = r#"
+        <Show when={ok()}>
+            <div class={cls()} onClick={go} on:scroll={log}>
+                <span title="x">{label()}</span>
+                <Button onInput={save} />
+            </div>
+        </Show>
+    "#;
+    let stats = analyze(source, None);
+    assert_eq!(stats.components, 2);
+    assert_eq!(stats.elements, 2);
+    // `class` and the `label()` child; component props aren't bindings
+    assert_eq!(stats.dynamic_bindings, 2);
+    assert_eq!(stats.delegated_events, vec!["click"]);
+    assert!(stats.template_bytes > "<div><span title=\"x\">".len());
+}
+
+#[test]
+fn test_ir_contexts_are_independent() {
+    let mut first = ir::dom::BlockContext::new();
+    let mut second = ir::dom::BlockContext::new();
+    assert_eq!(first.generate_uid("el$"), "_el$1");
+    assert_eq!(first.generate_uid("el$"), "_el$2");
+    assert_eq!(second.generate_uid("el$"), "_el$1");
+}
+
+#[test]
+fn test_parallel_transforms_match_sequential() {
+    let sources = [
+        r#"<div onClick={handler}>{count()}</div>"#,
+        r#"<ul><For each={items}>{(item) => <li>{item}</li>}</For></ul>"#,
+        r#"<span class={cls()}>text</span>"#,
+    ];
+    let sequential: Vec<String> = sources.iter().map(|s| transform_dom(s)).collect();
+    let parallel: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = sources
+            .iter()
+            .map(|s| scope.spawn(move || transform_dom(s)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn test_module_level_components_are_pure() {
+    let code = transform_dom(
+        r#"
+        export const icon = <Icon name="x" />;
+        export default <div>{value()}</div>;
+        function App() {
+            return <Icon name="y" />;
+        }
+    "#,
+    );
+    assert!(
+        code.contains("export const icon = /* @__PURE__ */ _$createComponent(Icon"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("export default /* @__PURE__ */ (() =>"),
+        "got: {}",
+        code
+    );
+    assert!(
+        code.contains("return _$createComponent(Icon"),
+        "got: {}",
+        code
+    );
+
+    let code = transform_ssr(r#"export const icon = <Icon name="x" />;"#);
+    assert!(
+        code.contains("/* @__PURE__ */ _$createComponent(Icon"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_directives_and_banner_comments_stay_above_imports() {
+    let code = transform_dom(
+        r#""use client";
+/*! @license MIT */
+const a = <div>{value()}</div>;
+"#,
+    );
+    assert!(code.starts_with("\"use client\";"), "got: {}", code);
+    let banner = code.find("@license MIT").expect("banner comment kept");
+    let import = code.find("import {").unwrap();
+    assert!(banner < import, "got: {}", code);
+    assert_eq!(code.matches("@license MIT").count(), 1, "got: {}", code);
+}
+
+#[test]
+fn test_validate_reports_invalid_nesting() {
+    let validate = |source: &str, generate: GenerateMode| {
+        let options = TransformOptions {
+            validate: true,
+            generate,
+            ..TransformOptions::solid_defaults()
+        };
+        transform(source, Some(options)).diagnostics
+    };
+
+    let diagnostics = validate(r#"<p><div /></p>"#, GenerateMode::Dom);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0]
+        .message
+        .contains("<div> cannot be a child of <p>"));
+
+    let diagnostics = validate(
+        r#"<a href="/"><span><a href="/x">x</a></span></a>"#,
+        GenerateMode::Dom,
+    );
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].message.contains("<a> cannot be nested"));
+
+    let diagnostics = validate(r#"<table><tr><td>1</td></tr></table>"#, GenerateMode::Ssr);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0]
+        .message
+        .contains("<tr> cannot be a child of <table>"));
+
+    let valid = r#"<table><tbody><tr><td><p>ok</p></td></tr></tbody></table>"#;
+    assert!(validate(valid, GenerateMode::Dom).is_empty());
+
+    // Off by default
+    let result = transform(r#"<p><div /></p>"#, None);
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+}
+
+#[test]
+fn test_dom_sole_string_child_joins_element_effects() {
+    let code = transform_dom(r#"<span title={title()}>{"Hello " + name()}</span>"#);
+    assert!(code.contains("<span> </span>"), "got: {}", code);
+    assert!(
+        code.contains(r#"_$effect(() => _el$1.firstChild.data = "Hello " + name())"#),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("insert"), "got: {}", code);
+
+    // A value that may be a node still goes through `insert`
+    let code = transform_dom(r#"<span>{name()}</span>"#);
+    assert!(
+        code.contains("_$insert(_el$1, () => name())"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_dom_adjacent_expressions_share_an_insert() {
+    let code = transform_dom(r#"<div>{a()}{b()}</div>"#);
+    assert!(code.contains("<div></div>"), "got: {}", code);
+    assert!(
+        code.contains("_$insert(_el$1, () => [a(), b()])"),
+        "got: {}",
+        code
+    );
+    assert_eq!(code.matches("_$insert(").count(), 1, "got: {}", code);
+
+    // Whitespace between them doesn't break the run, and a marker still follows
+    let code = transform_dom("<div>\n  {a()}\n  {b()}\n  <span />\n</div>");
+    assert!(
+        code.contains("_$insert(_el$1, () => [a(), b()], _el$2)"),
+        "got: {}",
+        code
+    );
+
+    // Text joins into one string, written to a single text node
+    let code = transform_dom(r#"<p>{`${first()}`}{" " + last()}</p>"#);
+    assert!(code.contains("<p> </p>"), "got: {}", code);
+    assert!(
+        code.contains(r#"_el$1.firstChild.data = `${first()}` + " " + last()"#),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_literal_conditions_compile_only_the_taken_branch() {
+    let code = transform_dom(r#"<div>{true ? <p>yes</p> : <span>no</span>}</div>"#);
+    assert!(code.contains("<p>yes</p>"), "got: {}", code);
+    assert!(!code.contains("no</span>"), "got: {}", code);
+
+    let code = transform_dom(r#"const a = false && <b>never</b>;"#);
+    assert!(code.contains("const a = false"), "got: {}", code);
+    assert!(!code.contains("_$template"), "got: {}", code);
+
+    let code = transform_dom(r#"<Show when={false} fallback={<Loading />}><p>ready</p></Show>"#);
+    assert!(
+        code.contains("_$createComponent(Loading, {})"),
+        "got: {}",
+        code
+    );
+    assert!(!code.contains("Show"), "got: {}", code);
+    assert!(!code.contains("ready"), "got: {}", code);
+
+    let code = transform_dom(r#"<div><Show when={true}><p>ready</p></Show></div>"#);
+    assert!(code.contains("<div><p>ready</p></div>"), "got: {}", code);
+    assert!(!code.contains("Show"), "got: {}", code);
+
+    // The value is read through the callback, so the component stays
+    let code = transform_dom(r#"<Show when={true}>{(value) => value}</Show>"#);
+    assert!(code.contains("_$createComponent(Show"), "got: {}", code);
+
+    let code = transform_ssr(r#"<Show when={0}><p>ready</p></Show>"#);
+    assert!(!code.contains("ready"), "got: {}", code);
+    assert!(code.contains("null"), "got: {}", code);
+}
+
+#[test]
+fn test_static_object_and_jsx_props_are_hoisted() {
+    let options = || TransformOptions {
+        hoist_static_props: true,
+        ..TransformOptions::solid_defaults()
+    };
+    let source = r#"<For each={rows()}>{(row) => <Row options={{ dense: true, sizes: [1, 2] }} label={row.name} />}</For>"#;
+    let code = transform(source, Some(options())).code;
+    assert!(code.contains("const _static$"), "got: {}", code);
+    assert!(code.contains("options: _static$"), "got: {}", code);
+    assert_eq!(code.matches("dense: true").count(), 1, "got: {}", code);
+
+    // Static JSX is still cloned per read, from a getter on the shared object
+    let code = transform(
+        r#"<Card icon={<svg><path d="M0" /></svg>} />"#,
+        Some(options()),
+    )
+    .code;
+    assert!(code.contains("const _props$"), "got: {}", code);
+    assert!(code.contains("cloneNode(true)"), "got: {}", code);
+    assert!(
+        code.contains("_$createComponent(Card, _props$"),
+        "got: {}",
+        code
+    );
+}
+
+#[test]
+fn test_jsx_nested_in_expressions_is_compiled() {
+    let source = r#"
+        const list = items.map((item) => <li>{item}</li>);
+        const pick = cond ? <A /> : <b>b</b>;
+        const many = [<i>1</i>, { icon: <Icon /> }];
+        render(() => <App />, root);
+        const view = <Show when={ready()} fallback=<p>Loading</p>><Main /></Show>;
+    "#;
+    for code in [transform_dom(source), transform_ssr(source)] {
+        assert!(!code.contains("<li>{item}"), "got: {}", code);
+        assert!(!code.contains("<A />"), "got: {}", code);
+        assert!(!code.contains("<Icon />"), "got: {}", code);
+        assert!(!code.contains("<App />"), "got: {}", code);
+        assert!(!code.contains("=<p>"), "got: {}", code);
+        assert!(code.contains("<p>Loading</p>"), "got: {}", code);
+        assert!(code.contains("fallback"), "got: {}", code);
+    }
 }